@@ -1,13 +1,36 @@
 package io.nash.openlimits;
 
+import java.math.BigDecimal;
 
 public class AskBid {
-    public final String price;
-    public final String qty;
+    public final BigDecimal price;
+    public final BigDecimal qty;
+    // Fixed-point mode only, where price and qty are null: the price is priceFixed * 10^-priceScale
+    public final long priceFixed;
+    public final long qtyFixed;
+    public final int priceScale;
+    public final int qtyScale;
 
-    public AskBid(String price, String qty) {
+    public AskBid(BigDecimal price, BigDecimal qty) {
         this.price = price;
         this.qty = qty;
+        this.priceFixed = 0;
+        this.qtyFixed = 0;
+        this.priceScale = 0;
+        this.qtyScale = 0;
+    }
+
+    public AskBid(long priceFixed, long qtyFixed, int priceScale, int qtyScale) {
+        this.price = null;
+        this.qty = null;
+        this.priceFixed = priceFixed;
+        this.qtyFixed = qtyFixed;
+        this.priceScale = priceScale;
+        this.qtyScale = qtyScale;
+    }
+
+    public AskBid(String price, String qty) {
+        this(new BigDecimal(price), new BigDecimal(qty));
     }
 
     @Override
@@ -15,6 +38,10 @@ public class AskBid {
         return "AskBid{" +
                 "price=" + price +
                 ", qty=" + qty +
+                ", priceFixed=" + priceFixed +
+                ", qtyFixed=" + qtyFixed +
+                ", priceScale=" + priceScale +
+                ", qtyScale=" + qtyScale +
                 '}';
     }
 }
@@ -4,4 +4,8 @@ public class AssetNotFound extends OpenLimitsException {
     public AssetNotFound(String s) {
         super(s);
     }
+
+    public AssetNotFound(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
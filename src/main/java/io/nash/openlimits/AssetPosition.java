@@ -0,0 +1,26 @@
+package io.nash.openlimits;
+
+import java.math.BigDecimal;
+
+public class AssetPosition {
+    public final String asset;
+    // What the tracked fills added to or took from the asset, net of fees
+    public final BigDecimal net;
+    // Fees are counted in the quote asset of their market
+    public final BigDecimal fees;
+
+    public AssetPosition(String asset, BigDecimal net, BigDecimal fees) {
+        this.asset = asset;
+        this.net = net;
+        this.fees = fees;
+    }
+
+    @Override
+    public String toString() {
+        return "AssetPosition{" +
+                "asset='" + asset + '\'' +
+                ", net=" + net +
+                ", fees=" + fees +
+                '}';
+    }
+}
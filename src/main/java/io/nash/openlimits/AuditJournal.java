@@ -0,0 +1,22 @@
+package io.nash.openlimits;
+
+// Reads the journal written by clients configured with ExchangeClientConfig.withAuditJournal. Safe to use while a
+// client is still appending to it.
+public class AuditJournal {
+    static {
+        System.loadLibrary("openlimits_java");
+        NativeLogger.ensureInstalled();
+    }
+
+    native private static AuditRecord[] readRecords(String directory, long fromMillis, long toMillis);
+
+    // Every record of the directory, oldest first
+    public static AuditRecord[] read(String directory) {
+        return readRecords(directory, Long.MIN_VALUE, Long.MAX_VALUE);
+    }
+
+    // The records between the two timestamps in milliseconds since the epoch, inclusive, oldest first
+    public static AuditRecord[] read(String directory, long fromMillis, long toMillis) {
+        return readRecords(directory, fromMillis, toMillis);
+    }
+}
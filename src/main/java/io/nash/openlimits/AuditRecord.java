@@ -0,0 +1,47 @@
+package io.nash.openlimits;
+
+// One record of the audit journal, see ExchangeClientConfig.withAuditJournal
+public class AuditRecord {
+    // Milliseconds since the epoch
+    public final long timestamp;
+    // "request", "result" or "error"
+    public final String kind;
+    // The client method, e.g. "limitBuy" or "cancelAllOrders"
+    public final String operation;
+    // nullable
+    public final String market;
+    // nullable
+    public final String clientOrderId;
+    // nullable, the order canceled, or the order the exchange created
+    public final String orderId;
+    // From the request to its result or error, -1 for requests
+    public final long latencyMicros;
+    // Size, price and time in force of requests, the order status of results, the exception class and exchange
+    // message of errors
+    public final String detail;
+
+    public AuditRecord(long timestamp, String kind, String operation, String market, String clientOrderId, String orderId, long latencyMicros, String detail) {
+        this.timestamp = timestamp;
+        this.kind = kind;
+        this.operation = operation;
+        this.market = market;
+        this.clientOrderId = clientOrderId;
+        this.orderId = orderId;
+        this.latencyMicros = latencyMicros;
+        this.detail = detail;
+    }
+
+    @Override
+    public String toString() {
+        return "AuditRecord{" +
+                "timestamp=" + timestamp +
+                ", kind='" + kind + '\'' +
+                ", operation='" + operation + '\'' +
+                ", market='" + market + '\'' +
+                ", clientOrderId='" + clientOrderId + '\'' +
+                ", orderId='" + orderId + '\'' +
+                ", latencyMicros=" + latencyMicros +
+                ", detail='" + detail + '\'' +
+                '}';
+    }
+}
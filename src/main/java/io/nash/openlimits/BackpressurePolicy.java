@@ -0,0 +1,11 @@
+package io.nash.openlimits;
+
+// What happens to websocket messages when the Java callbacks cannot keep up
+public enum BackpressurePolicy {
+    // Wait for the callbacks, stalling the websocket stream
+    Block,
+    // Drop the oldest queued message to make room
+    DropOldest,
+    // Drop the incoming message
+    DropNewest
+}
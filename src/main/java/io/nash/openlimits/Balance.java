@@ -1,22 +1,28 @@
 package io.nash.openlimits;
 
+import java.math.BigDecimal;
+
 public class Balance {
     public final String asset;
-    public final String total;
-    public final String free;
+    public final BigDecimal total;
+    public final BigDecimal free;
 
-    public Balance(String asset, String total, String free) {
+    public Balance(String asset, BigDecimal total, BigDecimal free) {
         this.asset = asset;
         this.total = total;
         this.free = free;
     }
 
+    public Balance(String asset, String total, String free) {
+        this(asset, new BigDecimal(total), new BigDecimal(free));
+    }
+
     @Override
     public String toString() {
         return "Balance{" +
                 "asset='" + asset + '\'' +
-                ", total='" + total + '\'' +
-                ", free='" + free + '\'' +
+                ", total=" + total +
+                ", free=" + free +
                 '}';
     }
 }
@@ -1,19 +1,52 @@
 package io.nash.openlimits;
 
+import java.util.Arrays;
+
+// The array constructors keep the secret out of a Java string. The array is cleared once a client was created or
+// updated with it, so such credentials can only be used once. A constructor or updateCredentials that throws leaves it
+// as it was, to retry with.
 public class BinanceCredentials {
     public final String apiKey;
+    // nullable when created from an array
     public final String apiSecret;
+    final char[] apiSecretChars;
+    final byte[] apiSecretBytes;
 
     public BinanceCredentials(String apiKey, String apiSecret) {
+        this(apiKey, apiSecret, null, null);
+    }
+
+    public BinanceCredentials(String apiKey, char[] apiSecret) {
+        this(apiKey, null, apiSecret, null);
+    }
+
+    // UTF-8
+    public BinanceCredentials(String apiKey, byte[] apiSecret) {
+        this(apiKey, null, null, apiSecret);
+    }
+
+    private BinanceCredentials(String apiKey, String apiSecret, char[] apiSecretChars, byte[] apiSecretBytes) {
         this.apiKey = apiKey;
         this.apiSecret = apiSecret;
+        this.apiSecretChars = apiSecretChars;
+        this.apiSecretBytes = apiSecretBytes;
+    }
+
+    // Called once a client was created or updated with them
+    void clear() {
+        if (apiSecretChars != null) {
+            Arrays.fill(apiSecretChars, '\0');
+        }
+        if (apiSecretBytes != null) {
+            Arrays.fill(apiSecretBytes, (byte) 0);
+        }
     }
 
     @Override
     public String toString() {
         return "BinanceCredentials{" +
                 "apiKey='" + apiKey + '\'' +
-                ", apiSecret='" + apiSecret + '\'' +
+                ", apiSecret='[REDACTED]'" +
                 '}';
     }
 }
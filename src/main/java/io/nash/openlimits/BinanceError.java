@@ -4,4 +4,8 @@ public class BinanceError extends OpenLimitsException {
     public BinanceError(String message) {
         super(message);
     }
+
+    public BinanceError(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -1,20 +1,57 @@
 package io.nash.openlimits;
 
+import java.math.BigDecimal;
+
 public class Candle {
     public final long time;
-    public final String low;
-    public final String high;
-    public final String open;
-    public final String close;
-    public final String volume;
+    public final BigDecimal low;
+    public final BigDecimal high;
+    public final BigDecimal open;
+    public final BigDecimal close;
+    public final BigDecimal volume;
+    // Fixed-point mode only, where the BigDecimal fields are null: the low is lowFixed * 10^-priceScale
+    public final long lowFixed;
+    public final long highFixed;
+    public final long openFixed;
+    public final long closeFixed;
+    public final long volumeFixed;
+    public final int priceScale;
+    public final int volumeScale;
 
-    public Candle(long time, String low, String high, String open, String close, String volume) {
+    public Candle(long time, BigDecimal low, BigDecimal high, BigDecimal open, BigDecimal close, BigDecimal volume) {
         this.time = time;
         this.low = low;
         this.high = high;
         this.open = open;
         this.close = close;
         this.volume = volume;
+        this.lowFixed = 0;
+        this.highFixed = 0;
+        this.openFixed = 0;
+        this.closeFixed = 0;
+        this.volumeFixed = 0;
+        this.priceScale = 0;
+        this.volumeScale = 0;
+    }
+
+    public Candle(long time, long lowFixed, long highFixed, long openFixed, long closeFixed, long volumeFixed, int priceScale, int volumeScale) {
+        this.time = time;
+        this.low = null;
+        this.high = null;
+        this.open = null;
+        this.close = null;
+        this.volume = null;
+        this.lowFixed = lowFixed;
+        this.highFixed = highFixed;
+        this.openFixed = openFixed;
+        this.closeFixed = closeFixed;
+        this.volumeFixed = volumeFixed;
+        this.priceScale = priceScale;
+        this.volumeScale = volumeScale;
+    }
+
+    public Candle(long time, String low, String high, String open, String close, String volume) {
+        this(time, new BigDecimal(low), new BigDecimal(high), new BigDecimal(open), new BigDecimal(close), new BigDecimal(volume));
     }
 
     @Override
@@ -26,6 +63,13 @@ public class Candle {
                 ", open=" + open +
                 ", close=" + close +
                 ", volume=" + volume +
+                ", lowFixed=" + lowFixed +
+                ", highFixed=" + highFixed +
+                ", openFixed=" + openFixed +
+                ", closeFixed=" + closeFixed +
+                ", volumeFixed=" + volumeFixed +
+                ", priceScale=" + priceScale +
+                ", volumeScale=" + volumeScale +
                 '}';
     }
 }
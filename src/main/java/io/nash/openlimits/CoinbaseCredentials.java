@@ -1,22 +1,65 @@
 package io.nash.openlimits;
 
+import java.util.Arrays;
+
+// The array constructors keep the secret and passphrase out of Java strings. The arrays are cleared once a client was
+// created or updated with them, so such credentials can only be used once. A constructor or updateCredentials that
+// throws leaves them as they were, to retry with.
 public class CoinbaseCredentials {
     public final String apiKey;
+    // nullable when created from arrays
     public final String apiSecret;
     public final String passphrase;
+    final char[] apiSecretChars;
+    final char[] passphraseChars;
+    final byte[] apiSecretBytes;
+    final byte[] passphraseBytes;
 
     public CoinbaseCredentials(String apiKey, String apiSecret, String passphrase) {
+        this(apiKey, apiSecret, passphrase, null, null, null, null);
+    }
+
+    public CoinbaseCredentials(String apiKey, char[] apiSecret, char[] passphrase) {
+        this(apiKey, null, null, apiSecret, passphrase, null, null);
+    }
+
+    // UTF-8
+    public CoinbaseCredentials(String apiKey, byte[] apiSecret, byte[] passphrase) {
+        this(apiKey, null, null, null, null, apiSecret, passphrase);
+    }
+
+    private CoinbaseCredentials(String apiKey, String apiSecret, String passphrase, char[] apiSecretChars, char[] passphraseChars, byte[] apiSecretBytes, byte[] passphraseBytes) {
         this.apiKey = apiKey;
         this.apiSecret = apiSecret;
         this.passphrase = passphrase;
+        this.apiSecretChars = apiSecretChars;
+        this.passphraseChars = passphraseChars;
+        this.apiSecretBytes = apiSecretBytes;
+        this.passphraseBytes = passphraseBytes;
+    }
+
+    // Called once a client was created or updated with them
+    void clear() {
+        if (apiSecretChars != null) {
+            Arrays.fill(apiSecretChars, '\0');
+        }
+        if (passphraseChars != null) {
+            Arrays.fill(passphraseChars, '\0');
+        }
+        if (apiSecretBytes != null) {
+            Arrays.fill(apiSecretBytes, (byte) 0);
+        }
+        if (passphraseBytes != null) {
+            Arrays.fill(passphraseBytes, (byte) 0);
+        }
     }
 
     @Override
     public String toString() {
         return "CoinbaseCredentials{" +
                 "apiKey='" + apiKey + '\'' +
-                ", apiSecret='" + apiSecret + '\'' +
-                ", passphrase='" + passphrase + '\'' +
+                ", apiSecret='[REDACTED]'" +
+                ", passphrase='[REDACTED]'" +
                 '}';
     }
 }
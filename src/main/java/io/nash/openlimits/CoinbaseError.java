@@ -4,4 +4,8 @@ public class CoinbaseError extends OpenLimitsException {
     public CoinbaseError(String s) {
         super(s);
     }
+
+    public CoinbaseError(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
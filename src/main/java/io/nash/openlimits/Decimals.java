@@ -0,0 +1,11 @@
+package io.nash.openlimits;
+
+import java.math.BigDecimal;
+
+final class Decimals {
+    private Decimals() {}
+
+    static BigDecimal parseNullable(String value) {
+        return value == null ? null : new BigDecimal(value);
+    }
+}
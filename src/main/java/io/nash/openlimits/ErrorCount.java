@@ -0,0 +1,19 @@
+package io.nash.openlimits;
+
+public class ErrorCount {
+    public final String errorClass;
+    public final long count;
+
+    public ErrorCount(String errorClass, long count) {
+        this.errorClass = errorClass;
+        this.count = count;
+    }
+
+    @Override
+    public String toString() {
+        return "ErrorCount{" +
+                "errorClass='" + errorClass + '\'' +
+                ", count=" + count +
+                '}';
+    }
+}
@@ -1,14 +1,22 @@
 package io.nash.openlimits;
 
 import java.io.IOException;
+import java.nio.ByteBuffer;
+import java.nio.ByteOrder;
 import java.util.ArrayList;
 import java.util.HashMap;
+import java.util.HashSet;
+import java.util.TreeMap;
 import java.util.function.Consumer;
+import java.util.function.LongConsumer;
 
 public class ExchangeClient {
     static {
         System.loadLibrary("openlimits_java");
+        NativeLogger.ensureInstalled();
     }
+    // The system property that enables injectFault
+    public static final String FAULT_INJECTION_PROPERTY = "openlimits.faultInjection";
     private ExchangeClientConfig config;
 
     @SuppressWarnings("unused")
@@ -26,9 +34,42 @@ public class ExchangeClient {
     @SuppressWarnings("unused")
     private long _sub_tx;
 
+    @SuppressWarnings("unused")
+    private long _stream_lock;
+
     @SuppressWarnings("unused")
     private long _handler_tx;
 
+    @SuppressWarnings("unused")
+    private long _metrics;
+
+    @SuppressWarnings("unused")
+    private long _poisoned;
+
+    @SuppressWarnings("unused")
+    private long _faults;
+
+    @SuppressWarnings("unused")
+    private long _scales;
+
+    @SuppressWarnings("unused")
+    private long _tracker;
+
+    @SuppressWarnings("unused")
+    private long _positions;
+
+    @SuppressWarnings("unused")
+    private long _risk;
+
+    @SuppressWarnings("unused")
+    private long _dead_mans_switch;
+
+    @SuppressWarnings("unused")
+    private long _journal;
+
+    @SuppressWarnings("unused")
+    private long _secrets;
+
     @SuppressWarnings("unused")
     private void onPing() {
         this.onPingCallbacks.forEach(Runnable::run);
@@ -37,7 +78,6 @@ public class ExchangeClient {
     @SuppressWarnings("unused")
     private void onDisconnect() {
         this.onDisconnectCallbacks.forEach(Runnable::run);
-        this.disposeClient(this);
     }
 
     @SuppressWarnings("unused")
@@ -45,6 +85,11 @@ public class ExchangeClient {
         this.onErrorCallbacks.forEach(callback -> callback.accept(error));
     }
 
+    @SuppressWarnings("unused")
+    private void onBackpressure(long dropped) {
+        this.onBackpressureCallbacks.forEach(callback -> callback.accept(dropped));
+    }
+
     @SuppressWarnings("unused")
     private void onOrderbook(OrderbookResponse orderbook) {
         if (!this.onOrderbookCallbacks.containsKey(orderbook.market)) {
@@ -53,6 +98,55 @@ public class ExchangeClient {
         this.onOrderbookCallbacks.get(orderbook.market).forEach(callback -> callback.accept(orderbook));
 
     }
+    // Only touched by the callback thread
+    private ByteBuffer orderbookBuffer;
+    final private OrderbookView orderbookView = new OrderbookView();
+
+    @SuppressWarnings("unused")
+    private ByteBuffer orderbookBuffer(int capacity) {
+        if (this.orderbookBuffer == null || this.orderbookBuffer.capacity() < capacity) {
+            this.orderbookBuffer = ByteBuffer.allocateDirect(Math.max(capacity, 64 * OrderbookView.LEVEL_BYTES))
+                    .order(ByteOrder.nativeOrder());
+        }
+        return this.orderbookBuffer;
+    }
+
+    @SuppressWarnings("unused")
+    private void onOrderbookView(String market, long updateId, long lastUpdateId, int askCount, int bidCount, int priceScale, int qtyScale) {
+        this.orderbookView.wrap(this.orderbookBuffer, market, updateId, lastUpdateId, askCount, bidCount, priceScale, qtyScale);
+        if (this.onOrderbookViewCallbacks.containsKey(market)) {
+            this.onOrderbookViewCallbacks.get(market).forEach(callback -> callback.accept(this.orderbookView));
+        }
+        if (this.onOrderbookCallbacks.containsKey(market)) {
+            this.onOrderbook(this.orderbookView.toResponse());
+        }
+    }
+
+    @SuppressWarnings("unused")
+    private void onOrderUpdate(Order order) {
+        if (this.onOrderUpdateCallbacks.containsKey(order.market)) {
+            this.onOrderUpdateCallbacks.get(order.market).forEach(callback -> callback.accept(order));
+        }
+    }
+
+    @SuppressWarnings("unused")
+    private void onFill(Trade fill) {
+        if (this.onFillCallbacks.containsKey(fill.marketPair)) {
+            this.onFillCallbacks.get(fill.marketPair).forEach(callback -> callback.accept(fill));
+        }
+    }
+
+    @SuppressWarnings("unused")
+    private void onBalance(Balance balance) {
+        this.onBalanceCallbacks.forEach(callback -> callback.accept(balance));
+    }
+
+    @SuppressWarnings("unused")
+    private void onOrderStateChange(Order order, OrderStatus oldStatus, OrderStatus newStatus) {
+        OrderStateChange change = new OrderStateChange(order, oldStatus, newStatus);
+        this.onOrderStateChangeCallbacks.forEach(callback -> callback.accept(change));
+    }
+
     @SuppressWarnings("unused")
     private void onTrades(String market, Trade[] trades) {
         if (!this.onTradesCallbacks.containsKey(market)) {
@@ -65,8 +159,15 @@ public class ExchangeClient {
     final private ArrayList<Consumer<OpenLimitsException>> onErrorCallbacks = new ArrayList<>();
     final private ArrayList<Runnable> onDisconnectCallbacks = new ArrayList<>();
     final private ArrayList<Runnable> onPingCallbacks = new ArrayList<>();
+    final private ArrayList<LongConsumer> onBackpressureCallbacks = new ArrayList<>();
     final private HashMap<String, ArrayList<Consumer<OrderbookResponse>>> onOrderbookCallbacks = new HashMap<>();
+    final private HashMap<String, ArrayList<Consumer<OrderbookView>>> onOrderbookViewCallbacks = new HashMap<>();
     final private HashMap<String, ArrayList<Consumer<TradesResponse>>> onTradesCallbacks = new HashMap<>();
+    // Exchanges don't always spell markets of account data the way they were subscribed
+    final private TreeMap<String, ArrayList<Consumer<Order>>> onOrderUpdateCallbacks = new TreeMap<>(String.CASE_INSENSITIVE_ORDER);
+    final private TreeMap<String, ArrayList<Consumer<Trade>>> onFillCallbacks = new TreeMap<>(String.CASE_INSENSITIVE_ORDER);
+    final private ArrayList<Consumer<Balance>> onBalanceCallbacks = new ArrayList<>();
+    final private ArrayList<Consumer<OrderStateChange>> onOrderStateChangeCallbacks = new ArrayList<>();
 
     native private OrderbookResponse orderBook(ExchangeClient client, String market);
     native private Ticker getPriceTicker(ExchangeClient client, String market);
@@ -79,19 +180,34 @@ public class ExchangeClient {
     native private Order getOrder(ExchangeClient client, GetOrderRequest request);
     native private Order[] getOrderHistory(ExchangeClient client, GetOrderHistoryRequest request);
     native private Order[] getAllOpenOrders(ExchangeClient client);
+    native private Order[] getTrackedOrders(ExchangeClient client, String market);
+    native private void reconcileOrders(ExchangeClient client);
+    native private Position[] getPositions(ExchangeClient client);
+    native private AssetPosition[] getAssetPositions(ExchangeClient client);
     native private Trade[] getTradeHistory(ExchangeClient client, TradeHistoryRequest request);
     native private Balance[] getAccountBalances(ExchangeClient client, Paginator paginator);
 
     native private OrderCanceled cancelOrder(ExchangeClient client, CancelOrderRequest req);
     native private OrderCanceled[] cancelAllOrders(ExchangeClient client, CancelAllOrdersRequest req);
     native private MarketPair[] receivePairs(ExchangeClient client);
+    native private MetricsSnapshot getMetrics(ExchangeClient client);
+    native private boolean isPoisoned(ExchangeClient client);
+    native private static void setGlobalKillSwitch(boolean engaged);
+    native private static boolean isGlobalKillSwitchEngaged();
+    native private static ExchangeClientConfig readConfigFile(String path);
+    native private static ExchangeClientConfig readConfigEnvironment(String prefix);
 
 
     native private void subscribe(ExchangeClient client, Subscription subscription);
     native private void disconnect(ExchangeClient client);
-    native private void disposeClient(ExchangeClient client);
+    native private boolean connect(ExchangeClient client);
     native private void closeClient(ExchangeClient client);
+    native private void heartbeat(ExchangeClient client);
+    native private boolean updateCredentials(ExchangeClient client, Object credentials);
     native private void simulateSocketError(ExchangeClient client);
+    native private void simulateMessages(ExchangeClient client, String market, long count);
+    native private void injectFault(ExchangeClient client, FaultRule rule);
+    native private void clearFaults(ExchangeClient client);
 
     native private void init(ExchangeClient client, ExchangeClientConfig conf);
     public void subscribeTrades(String market, Consumer<TradesResponse> onTrades) {
@@ -109,6 +225,42 @@ public class ExchangeClient {
         this.onOrderbookCallbacks.get(market).add(onOrderbook);
         this.subscribe(this, Subscription.orderbook(market));
     }
+    // Requires ExchangeClientConfig.withDirectOrderbooks(). The view is reused for every update, see OrderbookView
+    public void subscribeOrderbookView(String market, Consumer<OrderbookView> onOrderbook) {
+        if (!this.config.directOrderbooks) {
+            throw new InvalidArgument("subscribeOrderbookView requires ExchangeClientConfig.withDirectOrderbooks()");
+        }
+        if (!this.onOrderbookViewCallbacks.containsKey(market)) {
+            this.onOrderbookViewCallbacks.put(market, new ArrayList<>());
+        }
+        this.onOrderbookViewCallbacks.get(market).add(onOrderbook);
+        this.subscribe(this, Subscription.orderbook(market));
+    }
+    // Account streams need credentials. They report changes after subscribing, announced by the Nash websocket and
+    // polled from Binance and Coinbase every ExchangeClientConfig.accountPollMillis
+    public void subscribeOrderUpdates(String market, Consumer<Order> onOrderUpdate) {
+        if (!this.onOrderUpdateCallbacks.containsKey(market)) {
+            this.onOrderUpdateCallbacks.put(market, new ArrayList<>());
+        }
+        this.onOrderUpdateCallbacks.get(market).add(onOrderUpdate);
+        this.subscribe(this, Subscription.accountOrders(market));
+    }
+    public void subscribeFills(String market, Consumer<Trade> onFill) {
+        if (!this.onFillCallbacks.containsKey(market)) {
+            this.onFillCallbacks.put(market, new ArrayList<>());
+        }
+        this.onFillCallbacks.get(market).add(onFill);
+        this.subscribe(this, Subscription.accountTrades(market));
+    }
+    public void subscribeBalances(Consumer<Balance> onBalance) {
+        this.onBalanceCallbacks.add(onBalance);
+        this.subscribe(this, Subscription.accountBalances());
+    }
+    // Requires ExchangeClientConfig.withOrderTracking(). Changes caused by orders placed or canceled through this
+    // client are reported on the calling thread before the call returns, the others on the callback thread
+    public void subscribeOrderStateChanges(Consumer<OrderStateChange> onOrderStateChange) {
+        this.onOrderStateChangeCallbacks.add(onOrderStateChange);
+    }
     public void subscribeError(Consumer<OpenLimitsException> onError) {
         this.onErrorCallbacks.add(onError);
     }
@@ -118,13 +270,94 @@ public class ExchangeClient {
     public void subscribeDisconnect(Runnable onPing) {
         this.onDisconnectCallbacks.add(onPing);
     }
+    // Called with the number of websocket messages dropped or conflated since the last call,
+    // see ExchangeClientConfig.withBackpressure
+    public void subscribeBackpressure(LongConsumer onBackpressure) {
+        this.onBackpressureCallbacks.add(onBackpressure);
+    }
+    // Closes the websocket. REST calls keep working, and streaming can be resumed with reconnect()
     public void disconnect() {
         this.disconnect(this);
     }
+    // Opens a new websocket and subscribes again to every market with registered callbacks.
+    // Does nothing while the websocket is still connected.
+    public void reconnect() {
+        if (!this.connect(this)) {
+            return;
+        }
+        this.resubscribe();
+    }
+
+    // Switches to rotated credentials or a new session without closing the client. The credentials have to be of the
+    // client's exchange. An open websocket is replaced by one with the new credentials and subscribed again to every
+    // market with registered callbacks, onDisconnect is called for the old one. Callbacks stay registered either way.
+    // If the new credentials can't open a websocket, the call throws and the client keeps the previous ones.
+    public void updateCredentials(NashCredentials credentials) {
+        this.rotateCredentials(credentials);
+        credentials.clear();
+    }
+    public void updateCredentials(BinanceCredentials credentials) {
+        this.rotateCredentials(credentials);
+        credentials.clear();
+    }
+    public void updateCredentials(CoinbaseCredentials credentials) {
+        this.rotateCredentials(credentials);
+        credentials.clear();
+    }
+
+    private void rotateCredentials(Object credentials) {
+        if (this.updateCredentials(this, credentials)) {
+            this.resubscribe();
+        }
+    }
+
+    private void resubscribe() {
+        this.onTradesCallbacks.keySet().forEach(market -> this.subscribe(this, Subscription.trade(market)));
+        HashSet<String> orderbookMarkets = new HashSet<>(this.onOrderbookCallbacks.keySet());
+        orderbookMarkets.addAll(this.onOrderbookViewCallbacks.keySet());
+        orderbookMarkets.forEach(market -> this.subscribe(this, Subscription.orderbook(market)));
+        this.onOrderUpdateCallbacks.keySet().forEach(market -> this.subscribe(this, Subscription.accountOrders(market)));
+        this.onFillCallbacks.keySet().forEach(market -> this.subscribe(this, Subscription.accountTrades(market)));
+        if (!this.onBalanceCallbacks.isEmpty()) {
+            this.subscribe(this, Subscription.accountBalances());
+        }
+        // Whatever happened to the orders while disconnected was never polled
+        if (this.config.trackOrders) {
+            this.reconcileOrders(this);
+        }
+    }
+
+    // Tells the dead man's switch that Java is alive, call it well within the timeout given to
+    // ExchangeClientConfig.withDeadMansSwitch from the thread whose stall should pull the orders
+    public void heartbeat() {
+        this.heartbeat(this);
+    }
 
     public void simulateSocketError() {
         this.simulateSocketError(this);
     }
+    // For tests only: delivers count generated order book snapshots and trades for market to the subscribed
+    // callbacks, without involving the exchange. A Lazy client opens its websocket without subscribing anything.
+    void simulateMessages(String market, long count) {
+        this.simulateMessages(this, market, count);
+    }
+
+    // For tests only: makes matching calls and websocket messages of this client fail, slow down,
+    // get dropped or duplicated, or disconnect. Rules stay installed until they run out or clearFaults() is called.
+    // Refused unless the JVM runs with -Dopenlimits.faultInjection=true, so production clients can't misbehave.
+    public void injectFault(FaultRule rule) {
+        checkFaultInjection();
+        this.injectFault(this, rule);
+    }
+    public void clearFaults() {
+        checkFaultInjection();
+        this.clearFaults(this);
+    }
+    private static void checkFaultInjection() {
+        if (!Boolean.getBoolean(FAULT_INJECTION_PROPERTY)) {
+            throw new IllegalStateException("Fault injection is disabled, set -D" + FAULT_INJECTION_PROPERTY + "=true in tests");
+        }
+    }
 
     public Order limitBuy(LimitRequest request) {
         return this.limitBuy(this, request);
@@ -144,6 +377,25 @@ public class ExchangeClient {
     public Order[] getAllOpenOrders() {
         return this.getAllOpenOrders(this);
     }
+    // The tracked orders of a market, or of every market when null, oldest first. Finished orders are kept for ten
+    // minutes.
+    // Requires ExchangeClientConfig.withOrderTracking()
+    public Order[] getTrackedOrders(String market) {
+        return this.getTrackedOrders(this, market);
+    }
+    // Updates the tracked orders from the exchange. Done by reconnect(), call it after anything else that could
+    // have missed order updates
+    public void reconcileOrders() {
+        this.reconcileOrders(this);
+    }
+    // The positions of the markets given to ExchangeClientConfig.withPositionTracking, by market
+    public Position[] getPositions() {
+        return this.getPositions(this);
+    }
+    // What the fills of those markets moved of every asset, by asset
+    public AssetPosition[] getAssetPositions() {
+        return this.getAssetPositions(this);
+    }
     public Order[] getOrderHistory(GetOrderHistoryRequest request) {
         return this.getOrderHistory(this, request);
     }
@@ -174,10 +426,40 @@ public class ExchangeClient {
     public MarketPair[] receivePairs() {
         return this.receivePairs(this);
     }
+    public MetricsSnapshot getMetrics() {
+        return this.getMetrics(this);
+    }
+    // True after native code panicked. A poisoned client rejects every call with NativePanic apart from
+    // getMetrics and close, and should be replaced by a new ExchangeClient.
+    public boolean isPoisoned() {
+        return this.isPoisoned(this);
+    }
+    // While engaged, every order of every client in the process is rejected with RiskRejected before it is sent.
+    // Open orders are left alone, cancel them separately.
+    public static void setKillSwitch(boolean engaged) {
+        setGlobalKillSwitch(engaged);
+    }
+    public static boolean isKillSwitchEngaged() {
+        return isGlobalKillSwitchEngaged();
+    }
     public ExchangeClient(ExchangeClientConfig conf) {
         this.config = conf;
         this.init(this, conf);
+        conf.clearCredentials();
+    }
+
+    // A client configured by a TOML file, or a JSON file when path ends in .json, see the README for its fields.
+    // Raises InvalidArgument listing every field that fails validation.
+    public static ExchangeClient fromConfigFile(String path) {
+        return new ExchangeClient(readConfigFile(path));
     }
+    // A client configured by environment variables named like the fields of a configuration file, e.g.
+    // <prefix>_EXCHANGE or <prefix>_CREDENTIALS_API_KEY. Lists are comma separated. Raises InvalidArgument listing
+    // every field that fails validation.
+    public static ExchangeClient fromEnvironment(String prefix) {
+        return new ExchangeClient(readConfigEnvironment(prefix));
+    }
+
     public static void run() throws InterruptedException, IOException {
         String apiKey = System.getenv("NASH_API_SESSION_PROD");
         String secret = System.getenv("NASH_API_SECRET_PROD");
@@ -1,24 +1,142 @@
 package io.nash.openlimits;
 
+import java.util.Arrays;
+
 public class ExchangeClientConfig {
     public final NashConfig nash;
     public final BinanceConfig binance;
     public final CoinbaseConfig coinbase;
+    public final BackpressurePolicy backpressurePolicy;
+    // Maximum number of websocket messages waiting for the Java callbacks
+    public final int callbackQueueCapacity;
+    // Keep only the latest queued order book snapshot of each market
+    public final boolean conflateOrderbooks;
+    public final StreamingMode streamingMode;
+    // Deliver order books through a reused direct ByteBuffer, see ExchangeClient.subscribeOrderbookView
+    public final boolean directOrderbooks;
+    // Prices and sizes as longs scaled per market instead of BigDecimal, see withFixedPoint
+    public final boolean fixedPoint;
+    // How often account streams look for order, fill and balance changes
+    public final long accountPollMillis;
+    // Keep the state of the account's orders natively, see ExchangeClient.getTrackedOrders
+    public final boolean trackOrders;
+    // Markets whose position is built from fills, see ExchangeClient.getPositions. Empty when not tracked
+    public final String[] positionMarkets;
+    // nullable, checked before every order. The kill switch of ExchangeClient applies without limits too
+    public final RiskLimits riskLimits;
+    // How long without ExchangeClient.heartbeat() before the orders of deadMansSwitchMarkets are canceled, 0 when off
+    public final long deadMansSwitchMillis;
+    public final String[] deadMansSwitchMarkets;
+    // nullable, where order requests, results and errors are journaled, see AuditJournal
+    public final String auditJournalDirectory;
+    public final long auditJournalMaxFileBytes;
 
     public ExchangeClientConfig(NashConfig nash) {
-        this.nash = nash;
-        this.binance = null;
-        this.coinbase = null;
+        this(nash, null, null, BackpressurePolicy.Block, 100, false, StreamingMode.Eager, false, false, 1000, false, new String[0], null, 0, new String[0], null, 0);
     }
     public ExchangeClientConfig(BinanceConfig binance) {
-        this.nash = null;
-        this.binance = binance;
-        this.coinbase = null;
+        this(null, binance, null, BackpressurePolicy.Block, 100, false, StreamingMode.Eager, false, false, 1000, false, new String[0], null, 0, new String[0], null, 0);
     }
     public ExchangeClientConfig(CoinbaseConfig coinbase) {
-        this.nash = null;
-        this.binance = null;
+        this(null, null, coinbase, BackpressurePolicy.Block, 100, false, StreamingMode.Eager, false, false, 1000, false, new String[0], null, 0, new String[0], null, 0);
+    }
+
+    private ExchangeClientConfig(NashConfig nash, BinanceConfig binance, CoinbaseConfig coinbase, BackpressurePolicy backpressurePolicy, int callbackQueueCapacity, boolean conflateOrderbooks, StreamingMode streamingMode, boolean directOrderbooks, boolean fixedPoint, long accountPollMillis, boolean trackOrders, String[] positionMarkets, RiskLimits riskLimits, long deadMansSwitchMillis, String[] deadMansSwitchMarkets, String auditJournalDirectory, long auditJournalMaxFileBytes) {
+        this.nash = nash;
+        this.binance = binance;
         this.coinbase = coinbase;
+        this.backpressurePolicy = backpressurePolicy;
+        this.callbackQueueCapacity = callbackQueueCapacity;
+        this.conflateOrderbooks = conflateOrderbooks;
+        this.streamingMode = streamingMode;
+        this.directOrderbooks = directOrderbooks;
+        this.fixedPoint = fixedPoint;
+        this.accountPollMillis = accountPollMillis;
+        this.trackOrders = trackOrders;
+        this.positionMarkets = positionMarkets;
+        this.riskLimits = riskLimits;
+        this.deadMansSwitchMillis = deadMansSwitchMillis;
+        this.deadMansSwitchMarkets = deadMansSwitchMarkets;
+        this.auditJournalDirectory = auditJournalDirectory;
+        this.auditJournalMaxFileBytes = auditJournalMaxFileBytes;
+    }
+
+    public ExchangeClientConfig withBackpressure(BackpressurePolicy policy, int callbackQueueCapacity, boolean conflateOrderbooks) {
+        return new ExchangeClientConfig(nash, binance, coinbase, policy, callbackQueueCapacity, conflateOrderbooks, streamingMode, directOrderbooks, fixedPoint, accountPollMillis, trackOrders, positionMarkets, riskLimits, deadMansSwitchMillis, deadMansSwitchMarkets, auditJournalDirectory, auditJournalMaxFileBytes);
+    }
+
+    public ExchangeClientConfig withStreamingMode(StreamingMode streamingMode) {
+        return new ExchangeClientConfig(nash, binance, coinbase, backpressurePolicy, callbackQueueCapacity, conflateOrderbooks, streamingMode, directOrderbooks, fixedPoint, accountPollMillis, trackOrders, positionMarkets, riskLimits, deadMansSwitchMillis, deadMansSwitchMarkets, auditJournalDirectory, auditJournalMaxFileBytes);
+    }
+
+    // Order books are written as fixed-point longs into a buffer shared with Java instead of allocating an AskBid
+    // per price level. Plain subscribeOrderbook callbacks keep working, at the cost of copying the book.
+    public ExchangeClientConfig withDirectOrderbooks() {
+        return new ExchangeClientConfig(nash, binance, coinbase, backpressurePolicy, callbackQueueCapacity, conflateOrderbooks, streamingMode, true, fixedPoint, accountPollMillis, trackOrders, positionMarkets, riskLimits, deadMansSwitchMillis, deadMansSwitchMarkets, auditJournalDirectory, auditJournalMaxFileBytes);
+    }
+
+    // Trade, AskBid, Order and Candle carry prices and sizes as longs in the priceFixed, qtyFixed, sizeFixed, ...
+    // fields, scaled to the decimal places of the market's quoteIncrement and baseIncrement. Their BigDecimal fields
+    // are null. A value that isn't exact at its market's scale raises FixedPointError.
+    public ExchangeClientConfig withFixedPoint() {
+        return new ExchangeClientConfig(nash, binance, coinbase, backpressurePolicy, callbackQueueCapacity, conflateOrderbooks, streamingMode, directOrderbooks, true, accountPollMillis, trackOrders, positionMarkets, riskLimits, deadMansSwitchMillis, deadMansSwitchMarkets, auditJournalDirectory, auditJournalMaxFileBytes);
+    }
+
+    // How often the account streams of exchanges without account websocket subscriptions, Binance and Coinbase, and
+    // the orders of the order tracker are read natively
+    public ExchangeClientConfig withAccountPollInterval(long accountPollMillis) {
+        return new ExchangeClientConfig(nash, binance, coinbase, backpressurePolicy, callbackQueueCapacity, conflateOrderbooks, streamingMode, directOrderbooks, fixedPoint, accountPollMillis, trackOrders, positionMarkets, riskLimits, deadMansSwitchMillis, deadMansSwitchMarkets, auditJournalDirectory, auditJournalMaxFileBytes);
+    }
+
+    // Orders placed and canceled through the client, and the open orders of the account polled every
+    // accountPollMillis while the websocket is open, are tracked natively. Changes of their status are reported
+    // through ExchangeClient.subscribeOrderStateChanges.
+    public ExchangeClientConfig withOrderTracking() {
+        return new ExchangeClientConfig(nash, binance, coinbase, backpressurePolicy, callbackQueueCapacity, conflateOrderbooks, streamingMode, directOrderbooks, fixedPoint, accountPollMillis, true, positionMarkets, riskLimits, deadMansSwitchMillis, deadMansSwitchMarkets, auditJournalDirectory, auditJournalMaxFileBytes);
+    }
+
+    // Keeps the position, average entry price, realised PnL and fees of the markets, seeded from their trade history
+    // when the client is created and updated from fills polled every accountPollMillis while the websocket is open.
+    // Unrealised PnL is marked to the mid of subscribed order books.
+    public ExchangeClientConfig withPositionTracking(String... markets) {
+        return new ExchangeClientConfig(nash, binance, coinbase, backpressurePolicy, callbackQueueCapacity, conflateOrderbooks, streamingMode, directOrderbooks, fixedPoint, accountPollMillis, trackOrders, markets.clone(), riskLimits, deadMansSwitchMillis, deadMansSwitchMarkets, auditJournalDirectory, auditJournalMaxFileBytes);
+    }
+
+    // The price band and the notional of market orders are measured from the last trade or order book mid of the
+    // market, so orders of markets without a trade or order book subscription are rejected by those limits
+    public ExchangeClientConfig withRiskLimits(RiskLimits riskLimits) {
+        return new ExchangeClientConfig(nash, binance, coinbase, backpressurePolicy, callbackQueueCapacity, conflateOrderbooks, streamingMode, directOrderbooks, fixedPoint, accountPollMillis, trackOrders, positionMarkets, riskLimits, deadMansSwitchMillis, deadMansSwitchMarkets, auditJournalDirectory, auditJournalMaxFileBytes);
+    }
+
+    // Cancels every order of the markets natively when heartbeat() isn't called for heartbeatTimeoutMillis, or when
+    // the websocket is lost. Runs on a native thread with a REST client of its own, so it works while Java is stalled.
+    // It fires once and is armed again by the next heartbeat. Cancels are retried a few times with backoff.
+    public ExchangeClientConfig withDeadMansSwitch(long heartbeatTimeoutMillis, String... markets) {
+        return new ExchangeClientConfig(nash, binance, coinbase, backpressurePolicy, callbackQueueCapacity, conflateOrderbooks, streamingMode, directOrderbooks, fixedPoint, accountPollMillis, trackOrders, positionMarkets, riskLimits, heartbeatTimeoutMillis, markets.clone(), auditJournalDirectory, auditJournalMaxFileBytes);
+    }
+
+    // Appends every order, cancel and cancel all request with its result or error to files in directory, starting a
+    // new file every UTC day and before one grows beyond maxFileBytes. Credentials are never written.
+    public ExchangeClientConfig withAuditJournal(String directory, long maxFileBytes) {
+        return new ExchangeClientConfig(nash, binance, coinbase, backpressurePolicy, callbackQueueCapacity, conflateOrderbooks, streamingMode, directOrderbooks, fixedPoint, accountPollMillis, trackOrders, positionMarkets, riskLimits, deadMansSwitchMillis, deadMansSwitchMarkets, directory, maxFileBytes);
+    }
+
+    // The credentials passed as arrays, once the client was created
+    void clearCredentials() {
+        if (nash != null && nash.credentials != null) {
+            nash.credentials.clear();
+        }
+        if (binance != null && binance.credentials != null) {
+            binance.credentials.clear();
+        }
+        if (coinbase != null && coinbase.credentials != null) {
+            coinbase.credentials.clear();
+        }
+    }
+
+    // A client that never opens a websocket
+    public ExchangeClientConfig restOnly() {
+        return withStreamingMode(StreamingMode.Disabled);
     }
 
     @Override
@@ -27,6 +145,20 @@ public class ExchangeClientConfig {
                 "nash=" + nash +
                 ", binance=" + binance +
                 ", coinbase=" + coinbase +
+                ", backpressurePolicy=" + backpressurePolicy +
+                ", callbackQueueCapacity=" + callbackQueueCapacity +
+                ", conflateOrderbooks=" + conflateOrderbooks +
+                ", streamingMode=" + streamingMode +
+                ", directOrderbooks=" + directOrderbooks +
+                ", fixedPoint=" + fixedPoint +
+                ", accountPollMillis=" + accountPollMillis +
+                ", trackOrders=" + trackOrders +
+                ", positionMarkets=" + Arrays.toString(positionMarkets) +
+                ", riskLimits=" + riskLimits +
+                ", deadMansSwitchMillis=" + deadMansSwitchMillis +
+                ", deadMansSwitchMarkets=" + Arrays.toString(deadMansSwitchMarkets) +
+                ", auditJournalDirectory='" + auditJournalDirectory + '\'' +
+                ", auditJournalMaxFileBytes=" + auditJournalMaxFileBytes +
                 '}';
     }
 }
@@ -0,0 +1,63 @@
+package io.nash.openlimits;
+
+// A fault injected into the native layer, for testing how an application copes with failures.
+// Matches REST calls by method name, e.g. "limitBuy", and websocket messages by the callback they are
+// delivered to: "onTrades", "onOrderbook" or "onPing".
+public class FaultRule {
+    // nullable, null matches every operation
+    public final String operation;
+    // nullable, null matches every market
+    public final String market;
+    public final FaultType type;
+    // Java name of the exception class raised by FaultType.Error, null otherwise
+    public final String error;
+    public final long latencyMs;
+    // How many times the rule fires before it is removed, 0 fires forever
+    public final int times;
+
+    private FaultRule(String operation, String market, FaultType type, String error, long latencyMs, int times) {
+        this.operation = operation;
+        this.market = market;
+        this.type = type;
+        this.error = error;
+        this.latencyMs = latencyMs;
+        this.times = times;
+    }
+
+    // Any exception an exchange error is raised as, e.g. ServiceUnavailable, SocketError or BinanceError
+    public static FaultRule error(String operation, String market, Class<? extends OpenLimitsException> error) {
+        return new FaultRule(operation, market, FaultType.Error, error.getName(), 0, 0);
+    }
+
+    public static FaultRule latency(String operation, String market, long latencyMs) {
+        return new FaultRule(operation, market, FaultType.Latency, null, latencyMs, 0);
+    }
+
+    public static FaultRule dropMessages(String market) {
+        return new FaultRule(null, market, FaultType.DropMessage, null, 0, 0);
+    }
+
+    public static FaultRule duplicateMessages(String market) {
+        return new FaultRule(null, market, FaultType.DuplicateMessage, null, 0, 0);
+    }
+
+    public static FaultRule disconnect(String market) {
+        return new FaultRule(null, market, FaultType.Disconnect, null, 0, 1);
+    }
+
+    public FaultRule times(int times) {
+        return new FaultRule(operation, market, type, error, latencyMs, times);
+    }
+
+    @Override
+    public String toString() {
+        return "FaultRule{" +
+                "operation='" + operation + '\'' +
+                ", market='" + market + '\'' +
+                ", type=" + type +
+                ", error='" + error + '\'' +
+                ", latencyMs=" + latencyMs +
+                ", times=" + times +
+                '}';
+    }
+}
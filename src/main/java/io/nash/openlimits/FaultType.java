@@ -0,0 +1,13 @@
+package io.nash.openlimits;
+
+// What a FaultRule does when it matches
+public enum FaultType {
+    // Raise the rule's exception instead of calling the exchange or delivering the message
+    Error,
+    // Delay the call or message
+    Latency,
+    // The remaining types only apply to websocket messages
+    DropMessage,
+    DuplicateMessage,
+    Disconnect
+}
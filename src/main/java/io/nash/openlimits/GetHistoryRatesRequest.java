@@ -3,27 +3,35 @@ package io.nash.openlimits;
 
 public class GetHistoryRatesRequest {
     public final String market;
-    public final String interval;
+    public final Interval interval;
 
     public final Paginator paginator;
 
-    public GetHistoryRatesRequest(String market,  String interval, Paginator paginator) {
+    public GetHistoryRatesRequest(String market,  Interval interval, Paginator paginator) {
         this.market = market;
         this.interval = interval;
         this.paginator = paginator;
     }
 
-    public GetHistoryRatesRequest(String market,  String interval) {
+    public GetHistoryRatesRequest(String market,  Interval interval) {
         this.market = market;
         this.interval = interval;
         this.paginator = null;
     }
 
+    public GetHistoryRatesRequest(String market,  String interval, Paginator paginator) {
+        this(market, Interval.valueOf(interval), paginator);
+    }
+
+    public GetHistoryRatesRequest(String market,  String interval) {
+        this(market, Interval.valueOf(interval));
+    }
+
     @Override
     public String toString() {
         return "GetHistoryRatesRequest{" +
                 "market='" + market + '\'' +
-                ", interval='" + interval + '\'' +
+                ", interval=" + interval +
                 ", paginator=" + paginator +
                 '}';
     }
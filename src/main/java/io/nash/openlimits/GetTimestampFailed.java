@@ -4,4 +4,8 @@ public class GetTimestampFailed extends OpenLimitsException {
     public GetTimestampFailed(String s) {
         super(s);
     }
+
+    public GetTimestampFailed(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -0,0 +1,8 @@
+package io.nash.openlimits;
+
+// Thrown by System.loadLibrary when the native library doesn't match this jar
+public class IncompatibleNativeLibrary extends UnsatisfiedLinkError {
+    public IncompatibleNativeLibrary(String s) {
+        super(s);
+    }
+}
@@ -4,4 +4,8 @@ public class InitializeException extends OpenLimitsException {
     public InitializeException(String s) {
         super(s);
     }
+
+    public InitializeException(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -4,4 +4,8 @@ public class InternalServerError extends OpenLimitsException {
     public InternalServerError(String s) {
         super(s);
     }
+
+    public InternalServerError(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -4,5 +4,9 @@ public class InvalidArgument extends OpenLimitsException {
     public InvalidArgument(String s) {
         super(s);
     }
+
+    public InvalidArgument(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
 
@@ -4,4 +4,8 @@ public class InvalidHeaderError extends OpenLimitsException {
     public InvalidHeaderError(String s) {
         super(s);
     }
+
+    public InvalidHeaderError(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
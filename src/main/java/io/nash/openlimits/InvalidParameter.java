@@ -4,4 +4,8 @@ public class InvalidParameter extends OpenLimitsException {
     public InvalidParameter(String message) {
         super(message);
     }
+
+    public InvalidParameter(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
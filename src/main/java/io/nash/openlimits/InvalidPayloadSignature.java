@@ -4,4 +4,8 @@ public class InvalidPayloadSignature extends OpenLimitsException {
     public InvalidPayloadSignature(String s) {
         super(s);
     }
+
+    public InvalidPayloadSignature(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -4,4 +4,8 @@ public class IoError extends OpenLimitsException {
     public IoError(String s) {
         super(s);
     }
+
+    public IoError(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -4,4 +4,8 @@ public class JsonError extends OpenLimitsException {
     public JsonError(String s) {
         super(s);
     }
+
+    public JsonError(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
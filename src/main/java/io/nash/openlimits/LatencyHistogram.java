@@ -0,0 +1,38 @@
+package io.nash.openlimits;
+
+import java.util.Arrays;
+
+public class LatencyHistogram {
+    public final String operation;
+    public final long count;
+    public final long sumMicros;
+    public final long maxMicros;
+    // Inclusive upper bounds, bucketCounts has one extra entry for samples above the last bound
+    public final long[] bucketBoundsMicros;
+    public final long[] bucketCounts;
+
+    public LatencyHistogram(String operation, long count, long sumMicros, long maxMicros, long[] bucketBoundsMicros, long[] bucketCounts) {
+        this.operation = operation;
+        this.count = count;
+        this.sumMicros = sumMicros;
+        this.maxMicros = maxMicros;
+        this.bucketBoundsMicros = bucketBoundsMicros;
+        this.bucketCounts = bucketCounts;
+    }
+
+    public double meanMicros() {
+        return count == 0 ? 0 : (double) sumMicros / count;
+    }
+
+    @Override
+    public String toString() {
+        return "LatencyHistogram{" +
+                "operation='" + operation + '\'' +
+                ", count=" + count +
+                ", sumMicros=" + sumMicros +
+                ", maxMicros=" + maxMicros +
+                ", bucketBoundsMicros=" + Arrays.toString(bucketBoundsMicros) +
+                ", bucketCounts=" + Arrays.toString(bucketCounts) +
+                '}';
+    }
+}
@@ -1,16 +1,18 @@
 package io.nash.openlimits;
 
+import java.math.BigDecimal;
+
 public class LimitRequest {
-    public final String price;
-    public final String size;
+    public final BigDecimal price;
+    public final BigDecimal size;
     public final String market;
-    public final String timeInForce;
+    public final TimeInForce timeInForce;
     public final String clientOrderId;
     public final long timeInForceDurationMs;
     public final boolean postOnly;
 
 
-    private LimitRequest(String price, String size, String market, String timeInForce, long timeInForceDurationMs, boolean postOnly, String clientOrderId) {
+    private LimitRequest(BigDecimal price, BigDecimal size, String market, TimeInForce timeInForce, long timeInForceDurationMs, boolean postOnly, String clientOrderId) {
         this.price = price;
         this.size = size;
         this.market = market;
@@ -20,31 +22,61 @@ public class LimitRequest {
         this.clientOrderId = clientOrderId;
     }
 
+    private LimitRequest(String price, String size, String market, TimeInForce timeInForce, long timeInForceDurationMs, boolean postOnly, String clientOrderId) {
+        this(new BigDecimal(price), new BigDecimal(size), market, timeInForce, timeInForceDurationMs, postOnly, clientOrderId);
+    }
+
 
     public static LimitRequest immediateOrCancel(String price, String size, String market) {
-        return new LimitRequest(price, size, market, "IOC", 0, false, null);
+        return new LimitRequest(price, size, market, TimeInForce.IOC, 0, false, null);
     }
     public static LimitRequest goodTillCancelled(String price, String size, String market) {
-        return new LimitRequest(price, size, market, "GTC", 0, false, null);
+        return new LimitRequest(price, size, market, TimeInForce.GTC, 0, false, null);
     }
     public static LimitRequest fillOrKill(String price, String size, String market) {
-        return new LimitRequest(price, size, market, "FOK", 0, false, null);
+        return new LimitRequest(price, size, market, TimeInForce.FOK, 0, false, null);
     }
     public static LimitRequest goodTillTIme(String price, String size, String market, long timeInForceDurationMs) {
-        return new LimitRequest(price, size, market, "GTT", timeInForceDurationMs, false, null);
+        return new LimitRequest(price, size, market, TimeInForce.GTT, timeInForceDurationMs, false, null);
     }
 
     public static LimitRequest immediateOrCancel(String price, String size, String market, String clientOrderId) {
-        return new LimitRequest(price, size, market, "IOC", 0, false, clientOrderId);
+        return new LimitRequest(price, size, market, TimeInForce.IOC, 0, false, clientOrderId);
     }
     public static LimitRequest goodTillCancelled(String price, String size, String market, String clientOrderId) {
-        return new LimitRequest(price, size, market, "GTC", 0, false, clientOrderId);
+        return new LimitRequest(price, size, market, TimeInForce.GTC, 0, false, clientOrderId);
     }
     public static LimitRequest fillOrKill(String price, String size, String market, String clientOrderId) {
-        return new LimitRequest(price, size, market, "FOK", 0, false, clientOrderId);
+        return new LimitRequest(price, size, market, TimeInForce.FOK, 0, false, clientOrderId);
     }
     public static LimitRequest goodTillTIme(String price, String size, String market, long timeInForceDurationMs, String clientOrderId) {
-        return new LimitRequest(price, size, market, "GTT", timeInForceDurationMs, false, clientOrderId);
+        return new LimitRequest(price, size, market, TimeInForce.GTT, timeInForceDurationMs, false, clientOrderId);
+    }
+
+    public static LimitRequest immediateOrCancel(BigDecimal price, BigDecimal size, String market) {
+        return new LimitRequest(price, size, market, TimeInForce.IOC, 0, false, null);
+    }
+    public static LimitRequest goodTillCancelled(BigDecimal price, BigDecimal size, String market) {
+        return new LimitRequest(price, size, market, TimeInForce.GTC, 0, false, null);
+    }
+    public static LimitRequest fillOrKill(BigDecimal price, BigDecimal size, String market) {
+        return new LimitRequest(price, size, market, TimeInForce.FOK, 0, false, null);
+    }
+    public static LimitRequest goodTillTIme(BigDecimal price, BigDecimal size, String market, long timeInForceDurationMs) {
+        return new LimitRequest(price, size, market, TimeInForce.GTT, timeInForceDurationMs, false, null);
+    }
+
+    public static LimitRequest immediateOrCancel(BigDecimal price, BigDecimal size, String market, String clientOrderId) {
+        return new LimitRequest(price, size, market, TimeInForce.IOC, 0, false, clientOrderId);
+    }
+    public static LimitRequest goodTillCancelled(BigDecimal price, BigDecimal size, String market, String clientOrderId) {
+        return new LimitRequest(price, size, market, TimeInForce.GTC, 0, false, clientOrderId);
+    }
+    public static LimitRequest fillOrKill(BigDecimal price, BigDecimal size, String market, String clientOrderId) {
+        return new LimitRequest(price, size, market, TimeInForce.FOK, 0, false, clientOrderId);
+    }
+    public static LimitRequest goodTillTIme(BigDecimal price, BigDecimal size, String market, long timeInForceDurationMs, String clientOrderId) {
+        return new LimitRequest(price, size, market, TimeInForce.GTT, timeInForceDurationMs, false, clientOrderId);
     }
 
 
@@ -0,0 +1,6 @@
+package io.nash.openlimits;
+
+public enum Liquidity {
+    Maker,
+    Taker
+}
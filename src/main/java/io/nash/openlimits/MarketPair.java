@@ -1,15 +1,19 @@
 package io.nash.openlimits;
 
+import java.math.BigDecimal;
+
 public class MarketPair {
     public final String base;
     public final String quote;
     public final String symbol;
-    public final String baseIncrement;
-    public final String quoteIncrement;
-    public final String minBaseTradeSize;
-    public final String minQuoteTradeSize;
+    public final BigDecimal baseIncrement;
+    public final BigDecimal quoteIncrement;
+    // nullable
+    public final BigDecimal minBaseTradeSize;
+    // nullable
+    public final BigDecimal minQuoteTradeSize;
 
-    public MarketPair(String base, String quote, String symbol, String baseIncrement, String quoteIncrement, String minBaseTradeSize, String minQuoteTradeSize) {
+    public MarketPair(String base, String quote, String symbol, BigDecimal baseIncrement, BigDecimal quoteIncrement, BigDecimal minBaseTradeSize, BigDecimal minQuoteTradeSize) {
         this.base = base;
         this.quote = quote;
         this.symbol = symbol;
@@ -19,16 +23,20 @@ public class MarketPair {
         this.minQuoteTradeSize = minQuoteTradeSize;
     }
 
+    public MarketPair(String base, String quote, String symbol, String baseIncrement, String quoteIncrement, String minBaseTradeSize, String minQuoteTradeSize) {
+        this(base, quote, symbol, new BigDecimal(baseIncrement), new BigDecimal(quoteIncrement), Decimals.parseNullable(minBaseTradeSize), Decimals.parseNullable(minQuoteTradeSize));
+    }
+
     @Override
     public String toString() {
         return "MarketPair{" +
                 "base='" + base + '\'' +
                 ", quote='" + quote + '\'' +
                 ", symbol='" + symbol + '\'' +
-                ", baseIncrement='" + baseIncrement + '\'' +
-                ", quoteIncrement='" + quoteIncrement + '\'' +
-                ", minBaseTradeSize='" + minBaseTradeSize + '\'' +
-                ", minQuoteTradeSize='" + minQuoteTradeSize + '\'' +
+                ", baseIncrement=" + baseIncrement +
+                ", quoteIncrement=" + quoteIncrement +
+                ", minBaseTradeSize=" + minBaseTradeSize +
+                ", minQuoteTradeSize=" + minQuoteTradeSize +
                 '}';
     }
 }
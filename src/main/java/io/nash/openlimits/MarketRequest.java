@@ -1,25 +1,35 @@
 package io.nash.openlimits;
 
+import java.math.BigDecimal;
+
 public class MarketRequest {
-    public final String size;
+    public final BigDecimal size;
     public final String market;
     public final String clientOrderId;
-    public MarketRequest(String size, String market) {
+    public MarketRequest(BigDecimal size, String market) {
         this.size = size;
         this.market = market;
         this.clientOrderId = null;
     }
 
-    public MarketRequest(String size, String market, String clientOrderId) {
+    public MarketRequest(BigDecimal size, String market, String clientOrderId) {
         this.size = size;
         this.market = market;
         this.clientOrderId = clientOrderId;
     }
 
+    public MarketRequest(String size, String market) {
+        this(new BigDecimal(size), market);
+    }
+
+    public MarketRequest(String size, String market, String clientOrderId) {
+        this(new BigDecimal(size), market, clientOrderId);
+    }
+
     @Override
     public String toString() {
         return "MarketRequest{" +
-                "size='" + size + '\'' +
+                "size=" + size +
                 ", market='" + market + '\'' +
                 '}';
     }
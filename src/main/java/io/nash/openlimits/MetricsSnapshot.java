@@ -0,0 +1,39 @@
+package io.nash.openlimits;
+
+import java.util.Arrays;
+
+public class MetricsSnapshot {
+    public final LatencyHistogram[] latencies;
+    public final ErrorCount[] errors;
+    public final SubscriptionMetrics[] subscriptions;
+    public final long queueDepth;
+    public final long maxQueueDepth;
+
+    public MetricsSnapshot(LatencyHistogram[] latencies, ErrorCount[] errors, SubscriptionMetrics[] subscriptions, long queueDepth, long maxQueueDepth) {
+        this.latencies = latencies;
+        this.errors = errors;
+        this.subscriptions = subscriptions;
+        this.queueDepth = queueDepth;
+        this.maxQueueDepth = maxQueueDepth;
+    }
+
+    public LatencyHistogram latency(String operation) {
+        for (LatencyHistogram histogram : latencies) {
+            if (histogram.operation.equals(operation)) {
+                return histogram;
+            }
+        }
+        return null;
+    }
+
+    @Override
+    public String toString() {
+        return "MetricsSnapshot{" +
+                "latencies=" + Arrays.toString(latencies) +
+                ", errors=" + Arrays.toString(errors) +
+                ", subscriptions=" + Arrays.toString(subscriptions) +
+                ", queueDepth=" + queueDepth +
+                ", maxQueueDepth=" + maxQueueDepth +
+                '}';
+    }
+}
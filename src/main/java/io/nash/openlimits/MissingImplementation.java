@@ -4,4 +4,8 @@ public class MissingImplementation extends OpenLimitsException {
     public MissingImplementation(String s) {
         super(s);
     }
+
+    public MissingImplementation(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -4,4 +4,8 @@ public class MissingParameter extends OpenLimitsException {
     public MissingParameter(String s) {
         super(s);
     }
+
+    public MissingParameter(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
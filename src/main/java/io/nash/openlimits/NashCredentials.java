@@ -1,19 +1,62 @@
 package io.nash.openlimits;
 
+import java.util.Arrays;
+
+// The array constructors keep the secrets out of Java strings. The arrays are cleared once a client was created or
+// updated with them, so such credentials can only be used once. A constructor or updateCredentials that throws leaves
+// them as they were, to retry with.
 public class NashCredentials {
+    // nullable when created from arrays
     public final String secret;
     public final String session;
+    final char[] secretChars;
+    final char[] sessionChars;
+    final byte[] secretBytes;
+    final byte[] sessionBytes;
 
     public NashCredentials(String secret, String session) {
+        this(secret, session, null, null, null, null);
+    }
+
+    public NashCredentials(char[] secret, char[] session) {
+        this(null, null, secret, session, null, null);
+    }
+
+    // UTF-8
+    public NashCredentials(byte[] secret, byte[] session) {
+        this(null, null, null, null, secret, session);
+    }
+
+    private NashCredentials(String secret, String session, char[] secretChars, char[] sessionChars, byte[] secretBytes, byte[] sessionBytes) {
         this.secret = secret;
         this.session = session;
+        this.secretChars = secretChars;
+        this.sessionChars = sessionChars;
+        this.secretBytes = secretBytes;
+        this.sessionBytes = sessionBytes;
+    }
+
+    // Called once a client was created or updated with them
+    void clear() {
+        if (secretChars != null) {
+            Arrays.fill(secretChars, '\0');
+        }
+        if (sessionChars != null) {
+            Arrays.fill(sessionChars, '\0');
+        }
+        if (secretBytes != null) {
+            Arrays.fill(secretBytes, (byte) 0);
+        }
+        if (sessionBytes != null) {
+            Arrays.fill(sessionBytes, (byte) 0);
+        }
     }
 
     @Override
     public String toString() {
         return "NashCredentials{" +
-                "secret='" + secret + '\'' +
-                ", session='" + session + '\'' +
+                "secret='[REDACTED]'" +
+                ", session='[REDACTED]'" +
                 '}';
     }
 }
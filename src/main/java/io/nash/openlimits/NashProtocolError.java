@@ -4,4 +4,8 @@ public class NashProtocolError extends OpenLimitsException {
     public NashProtocolError(String s) {
         super(s);
     }
+
+    public NashProtocolError(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
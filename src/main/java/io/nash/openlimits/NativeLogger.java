@@ -0,0 +1,64 @@
+package io.nash.openlimits;
+
+import java.util.logging.Level;
+import java.util.logging.Logger;
+
+/**
+ * Receives log records from the native library and forwards them to java.util.logging,
+ * under loggers named "io.nash.openlimits.native.&lt;rust target&gt;".
+ * Use the jul-to-slf4j bridge to route them to SLF4J.
+ */
+public class NativeLogger {
+    public static final String LOGGER_PREFIX = "io.nash.openlimits.native";
+
+    static {
+        System.loadLibrary("openlimits_java");
+        install(toNativeLevel(Level.INFO));
+    }
+
+    native private static void install(int level);
+    native private static void setNativeLevel(int level);
+
+    /**
+     * Sets the most verbose level the native library will emit. Records below it are
+     * discarded before crossing JNI.
+     */
+    public static void setLevel(Level level) {
+        setNativeLevel(toNativeLevel(level));
+    }
+
+    static void ensureInstalled() {
+        // Loading the class runs the static initializer
+    }
+
+    private static int toNativeLevel(Level level) {
+        int value = level.intValue();
+        if (value == Level.OFF.intValue()) {
+            return 0;
+        } else if (value >= Level.SEVERE.intValue()) {
+            return 1;
+        } else if (value >= Level.WARNING.intValue()) {
+            return 2;
+        } else if (value >= Level.CONFIG.intValue()) {
+            return 3;
+        } else if (value >= Level.FINE.intValue()) {
+            return 4;
+        }
+        return 5;
+    }
+
+    private static Level fromNativeLevel(int level) {
+        switch (level) {
+            case 1: return Level.SEVERE;
+            case 2: return Level.WARNING;
+            case 3: return Level.INFO;
+            case 4: return Level.FINE;
+            default: return Level.FINEST;
+        }
+    }
+
+    @SuppressWarnings("unused")
+    private static void log(int level, String target, String message) {
+        Logger.getLogger(LOGGER_PREFIX + "." + target.replace("::", ".")).log(fromNativeLevel(level), message);
+    }
+}
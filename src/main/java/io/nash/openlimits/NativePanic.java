@@ -0,0 +1,11 @@
+package io.nash.openlimits;
+
+public class NativePanic extends OpenLimitsException {
+    public NativePanic(String s) {
+        super(s);
+    }
+
+    public NativePanic(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
+}
@@ -0,0 +1,11 @@
+package io.nash.openlimits;
+
+// Checked by the native library when it is loaded, so a jar and a library from different builds fail
+// with IncompatibleNativeLibrary instead of a confusing error on first use
+public final class NativeProtocol {
+    // Bump together with PROTOCOL_VERSION in natives.rs whenever a native method or a class the library builds changes
+    public static final int VERSION = 13;
+
+    private NativeProtocol() {
+    }
+}
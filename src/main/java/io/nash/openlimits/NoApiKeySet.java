@@ -4,4 +4,8 @@ public class NoApiKeySet extends OpenLimitsException {
     public NoApiKeySet(String s) {
         super(s);
     }
+
+    public NoApiKeySet(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
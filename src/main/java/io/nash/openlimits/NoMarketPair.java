@@ -4,4 +4,8 @@ public class NoMarketPair extends OpenLimitsException {
     public NoMarketPair(String s) {
         super(s);
     }
+
+    public NoMarketPair(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -4,4 +4,8 @@ public class NotParsableResponse extends OpenLimitsException {
     public NotParsableResponse(String s) {
         super(s);
     }
+
+    public NotParsableResponse(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -1,7 +1,47 @@
 package io.nash.openlimits;
 
 public class OpenLimitsException extends RuntimeException {
+    // nullable, "nash", "binance" or "coinbase"
+    public final String exchange;
+    // nullable, exchange specific error code such as Binance's -2010
+    public final Long errorCode;
+    // 0 when the error did not come from an HTTP response
+    public final int httpStatus;
+    // nullable, the error message as reported by the exchange
+    public final String exchangeMessage;
+    // nullable, the client method that failed, e.g. "limitBuy"
+    public final String operation;
+    // nullable
+    public final String market;
+    // true when repeating the same request may succeed, e.g. timeouts and 5xx responses
+    public final boolean retryable;
+
     public OpenLimitsException(String message) {
+        this(message, null, null, 0, null, null, null, false);
+    }
+
+    public OpenLimitsException(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
         super(message);
+        this.exchange = exchange;
+        this.errorCode = errorCode;
+        this.httpStatus = httpStatus;
+        this.exchangeMessage = exchangeMessage;
+        this.operation = operation;
+        this.market = market;
+        this.retryable = retryable;
+    }
+
+    @Override
+    public String toString() {
+        return getClass().getName() + "{" +
+                "message='" + getMessage() + '\'' +
+                ", exchange='" + exchange + '\'' +
+                ", errorCode=" + errorCode +
+                ", httpStatus=" + httpStatus +
+                ", exchangeMessage='" + exchangeMessage + '\'' +
+                ", operation='" + operation + '\'' +
+                ", market='" + market + '\'' +
+                ", retryable=" + retryable +
+                '}';
     }
 }
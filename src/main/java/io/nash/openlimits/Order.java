@@ -1,5 +1,6 @@
 package io.nash.openlimits;
 
+import java.math.BigDecimal;
 import java.util.Arrays;
 
 public class Order {
@@ -7,14 +8,29 @@ public class Order {
     public final String market;
     public final String clientOrderId;
     public final long createdAt;
-    public final String orderType;
-    public final String side;
-    public final String status;
-    public final String size;
-    public final String price;
+    public final OrderType orderType;
+    public final Side side;
+    public final OrderStatus status;
+    public final BigDecimal size;
+    // nullable
+    public final BigDecimal price;
     public final Trade[] trades;
+    // Fixed-point mode only, where size and price are null: the size is sizeFixed * 10^-sizeScale. priceFixed is
+    // zero for orders without a price
+    public final long sizeFixed;
+    public final long priceFixed;
+    public final int priceScale;
+    public final int sizeScale;
 
-    public Order(String id, String market, String clientOrderId, long createdAt, String orderType, String side, String status, String size, String price, Trade[] trades) {
+    public Order(String id, String market, String clientOrderId, long createdAt, OrderType orderType, Side side, OrderStatus status, BigDecimal size, BigDecimal price, Trade[] trades) {
+        this(id, market, clientOrderId, createdAt, orderType, side, status, size, price, trades, 0, 0, 0, 0);
+    }
+
+    public Order(String id, String market, String clientOrderId, long createdAt, OrderType orderType, Side side, OrderStatus status, long sizeFixed, long priceFixed, int priceScale, int sizeScale, Trade[] trades) {
+        this(id, market, clientOrderId, createdAt, orderType, side, status, null, null, trades, sizeFixed, priceFixed, priceScale, sizeScale);
+    }
+
+    private Order(String id, String market, String clientOrderId, long createdAt, OrderType orderType, Side side, OrderStatus status, BigDecimal size, BigDecimal price, Trade[] trades, long sizeFixed, long priceFixed, int priceScale, int sizeScale) {
         this.id = id;
         this.market = market;
         this.clientOrderId = clientOrderId;
@@ -25,6 +41,14 @@ public class Order {
         this.size = size;
         this.price = price;
         this.trades = trades;
+        this.sizeFixed = sizeFixed;
+        this.priceFixed = priceFixed;
+        this.priceScale = priceScale;
+        this.sizeScale = sizeScale;
+    }
+
+    public Order(String id, String market, String clientOrderId, long createdAt, OrderType orderType, Side side, OrderStatus status, String size, String price, Trade[] trades) {
+        this(id, market, clientOrderId, createdAt, orderType, side, status, new BigDecimal(size), Decimals.parseNullable(price), trades);
     }
 
     @Override
@@ -34,11 +58,15 @@ public class Order {
                 ", market='" + market + '\'' +
                 ", clientOrderId='" + clientOrderId + '\'' +
                 ", createdAt=" + createdAt +
-                ", orderType='" + orderType + '\'' +
-                ", side='" + side + '\'' +
-                ", status='" + status + '\'' +
-                ", size='" + size + '\'' +
-                ", price='" + price + '\'' +
+                ", orderType=" + orderType +
+                ", side=" + side +
+                ", status=" + status +
+                ", size=" + size +
+                ", price=" + price +
+                ", sizeFixed=" + sizeFixed +
+                ", priceFixed=" + priceFixed +
+                ", priceScale=" + priceScale +
+                ", sizeScale=" + sizeScale +
                 ", trades=" + Arrays.toString(trades) + '\'' +
                 '}';
     }
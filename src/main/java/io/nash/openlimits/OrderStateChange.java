@@ -0,0 +1,23 @@
+package io.nash.openlimits;
+
+public class OrderStateChange {
+    public final Order order;
+    // null for an order the tracker hadn't seen before
+    public final OrderStatus oldStatus;
+    public final OrderStatus newStatus;
+
+    public OrderStateChange(Order order, OrderStatus oldStatus, OrderStatus newStatus) {
+        this.order = order;
+        this.oldStatus = oldStatus;
+        this.newStatus = newStatus;
+    }
+
+    @Override
+    public String toString() {
+        return "OrderStateChange{" +
+                "order=" + order +
+                ", oldStatus=" + oldStatus +
+                ", newStatus=" + newStatus +
+                '}';
+    }
+}
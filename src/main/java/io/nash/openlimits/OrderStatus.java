@@ -0,0 +1,14 @@
+package io.nash.openlimits;
+
+public enum OrderStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    PendingCancel,
+    Rejected,
+    Expired,
+    Open,
+    Pending,
+    Active
+}
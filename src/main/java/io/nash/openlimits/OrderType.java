@@ -0,0 +1,9 @@
+package io.nash.openlimits;
+
+public enum OrderType {
+    Limit,
+    Market,
+    StopLimit,
+    StopMarket,
+    Unknown
+}
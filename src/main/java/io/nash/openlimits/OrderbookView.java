@@ -0,0 +1,118 @@
+package io.nash.openlimits;
+
+import java.math.BigDecimal;
+import java.nio.ByteBuffer;
+
+// A flyweight over an order book the native library wrote into a direct ByteBuffer. Prices and quantities are
+// fixed-point longs: the actual price is price * 10^-priceScale. The instance and its buffer are reused for the
+// next update, so only read it inside the callback and copy out whatever has to outlive it.
+public final class OrderbookView {
+    // Every level is a price followed by a quantity, asks first
+    static final int LEVEL_BYTES = 16;
+
+    private ByteBuffer buffer;
+    private String market;
+    private long updateId;
+    private long lastUpdateId;
+    private int askCount;
+    private int bidCount;
+    private int priceScale;
+    private int qtyScale;
+
+    OrderbookView() {
+    }
+
+    void wrap(ByteBuffer buffer, String market, long updateId, long lastUpdateId, int askCount, int bidCount, int priceScale, int qtyScale) {
+        this.buffer = buffer;
+        this.market = market;
+        this.updateId = updateId;
+        this.lastUpdateId = lastUpdateId;
+        this.askCount = askCount;
+        this.bidCount = bidCount;
+        this.priceScale = priceScale;
+        this.qtyScale = qtyScale;
+    }
+
+    public String market() {
+        return market;
+    }
+    public long updateId() {
+        return updateId;
+    }
+    public long lastUpdateId() {
+        return lastUpdateId;
+    }
+    public int askCount() {
+        return askCount;
+    }
+    public int bidCount() {
+        return bidCount;
+    }
+    public int priceScale() {
+        return priceScale;
+    }
+    public int qtyScale() {
+        return qtyScale;
+    }
+
+    public long askPrice(int level) {
+        return buffer.getLong(askOffset(level));
+    }
+    public long askQty(int level) {
+        return buffer.getLong(askOffset(level) + 8);
+    }
+    public long bidPrice(int level) {
+        return buffer.getLong(bidOffset(level));
+    }
+    public long bidQty(int level) {
+        return buffer.getLong(bidOffset(level) + 8);
+    }
+
+    // Allocates, convenient outside of hot paths
+    public AskBid ask(int level) {
+        return new AskBid(BigDecimal.valueOf(askPrice(level), priceScale), BigDecimal.valueOf(askQty(level), qtyScale));
+    }
+    public AskBid bid(int level) {
+        return new AskBid(BigDecimal.valueOf(bidPrice(level), priceScale), BigDecimal.valueOf(bidQty(level), qtyScale));
+    }
+
+    // Copies the book into the regular representation. Every BigDecimal has the scale of the book, so a level of 0.1
+    // in a book that also has 0.25 comes out as 0.10, where a plain callback gets 0.1. Compare with compareTo
+    public OrderbookResponse toResponse() {
+        AskBid[] asks = new AskBid[askCount];
+        for (int i = 0; i < askCount; i++) {
+            asks[i] = ask(i);
+        }
+        AskBid[] bids = new AskBid[bidCount];
+        for (int i = 0; i < bidCount; i++) {
+            bids[i] = bid(i);
+        }
+        return new OrderbookResponse(market, asks, bids, lastUpdateId, updateId);
+    }
+
+    private int askOffset(int level) {
+        if (level < 0 || level >= askCount) {
+            throw new IndexOutOfBoundsException("Ask level " + level + " of " + askCount);
+        }
+        return level * LEVEL_BYTES;
+    }
+    private int bidOffset(int level) {
+        if (level < 0 || level >= bidCount) {
+            throw new IndexOutOfBoundsException("Bid level " + level + " of " + bidCount);
+        }
+        return (askCount + level) * LEVEL_BYTES;
+    }
+
+    @Override
+    public String toString() {
+        return "OrderbookView{" +
+                "market='" + market + '\'' +
+                ", updateId=" + updateId +
+                ", lastUpdateId=" + lastUpdateId +
+                ", askCount=" + askCount +
+                ", bidCount=" + bidCount +
+                ", priceScale=" + priceScale +
+                ", qtyScale=" + qtyScale +
+                '}';
+    }
+}
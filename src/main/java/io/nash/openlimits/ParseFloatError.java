@@ -4,4 +4,8 @@ public class ParseFloatError extends OpenLimitsException {
     public ParseFloatError(String s) {
         super(s);
     }
+
+    public ParseFloatError(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -4,4 +4,8 @@ public class PoisonError extends OpenLimitsException {
     public PoisonError(String s) {
         super(s);
     }
+
+    public PoisonError(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
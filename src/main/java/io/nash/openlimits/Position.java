@@ -0,0 +1,40 @@
+package io.nash.openlimits;
+
+import java.math.BigDecimal;
+
+public class Position {
+    public final String market;
+    // In the base asset, negative when short
+    public final BigDecimal net;
+    // Zero while flat
+    public final BigDecimal averageEntryPrice;
+    public final BigDecimal realisedPnl;
+    // nullable, until an order book snapshot of the market arrived
+    public final BigDecimal unrealisedPnl;
+    public final BigDecimal fees;
+    // nullable
+    public final BigDecimal midPrice;
+
+    public Position(String market, BigDecimal net, BigDecimal averageEntryPrice, BigDecimal realisedPnl, BigDecimal unrealisedPnl, BigDecimal fees, BigDecimal midPrice) {
+        this.market = market;
+        this.net = net;
+        this.averageEntryPrice = averageEntryPrice;
+        this.realisedPnl = realisedPnl;
+        this.unrealisedPnl = unrealisedPnl;
+        this.fees = fees;
+        this.midPrice = midPrice;
+    }
+
+    @Override
+    public String toString() {
+        return "Position{" +
+                "market='" + market + '\'' +
+                ", net=" + net +
+                ", averageEntryPrice=" + averageEntryPrice +
+                ", realisedPnl=" + realisedPnl +
+                ", unrealisedPnl=" + unrealisedPnl +
+                ", fees=" + fees +
+                ", midPrice=" + midPrice +
+                '}';
+    }
+}
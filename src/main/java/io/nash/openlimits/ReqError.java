@@ -4,4 +4,8 @@ public class ReqError extends OpenLimitsException {
     public ReqError(String s) {
         super(s);
     }
+
+    public ReqError(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -0,0 +1,55 @@
+package io.nash.openlimits;
+
+import java.math.BigDecimal;
+
+// Pre-trade limits checked natively before an order is sent, see ExchangeClientConfig.withRiskLimits.
+// An order breaking one raises RiskRejected naming the field of the limit.
+public class RiskLimits {
+    // nullable, price times size in the quote asset. Market orders are valued at the reference price
+    public final BigDecimal maxOrderNotional;
+    // nullable, the net position of a market either side of flat, in the base asset, as if every open order on the
+    // side of the order filled. Orders that bring it closer to flat always pass. Requires
+    // ExchangeClientConfig.withOrderTracking and withPositionTracking of the markets traded
+    public final BigDecimal maxPosition;
+    // 0 for no limit, counting the orders being sent. Requires ExchangeClientConfig.withOrderTracking
+    public final int maxOpenOrders;
+    // nullable, how far in percent a limit price may be from the reference price
+    public final BigDecimal priceBandPercent;
+
+    public RiskLimits() {
+        this(null, null, 0, null);
+    }
+
+    private RiskLimits(BigDecimal maxOrderNotional, BigDecimal maxPosition, int maxOpenOrders, BigDecimal priceBandPercent) {
+        this.maxOrderNotional = maxOrderNotional;
+        this.maxPosition = maxPosition;
+        this.maxOpenOrders = maxOpenOrders;
+        this.priceBandPercent = priceBandPercent;
+    }
+
+    public RiskLimits withMaxOrderNotional(BigDecimal maxOrderNotional) {
+        return new RiskLimits(maxOrderNotional, maxPosition, maxOpenOrders, priceBandPercent);
+    }
+
+    public RiskLimits withMaxPosition(BigDecimal maxPosition) {
+        return new RiskLimits(maxOrderNotional, maxPosition, maxOpenOrders, priceBandPercent);
+    }
+
+    public RiskLimits withMaxOpenOrders(int maxOpenOrders) {
+        return new RiskLimits(maxOrderNotional, maxPosition, maxOpenOrders, priceBandPercent);
+    }
+
+    public RiskLimits withPriceBand(BigDecimal priceBandPercent) {
+        return new RiskLimits(maxOrderNotional, maxPosition, maxOpenOrders, priceBandPercent);
+    }
+
+    @Override
+    public String toString() {
+        return "RiskLimits{" +
+                "maxOrderNotional=" + maxOrderNotional +
+                ", maxPosition=" + maxPosition +
+                ", maxOpenOrders=" + maxOpenOrders +
+                ", priceBandPercent=" + priceBandPercent +
+                '}';
+    }
+}
@@ -0,0 +1,21 @@
+package io.nash.openlimits;
+
+// An order stopped by a pre-trade risk check before it reached the exchange
+public class RiskRejected extends OpenLimitsException {
+    // The broken limit: "maxOrderNotional", "maxPosition", "maxOpenOrders", "priceBand" or "killSwitch"
+    public final String limit;
+
+    public RiskRejected(String s) {
+        this(s, null, null, 0, null, null, null, false, null);
+    }
+
+    public RiskRejected(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable, String limit) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+        this.limit = limit;
+    }
+
+    @Override
+    public String toString() {
+        return super.toString() + "{limit='" + limit + "'}";
+    }
+}
@@ -4,4 +4,8 @@ public class ServiceUnavailable extends OpenLimitsException {
     public ServiceUnavailable(String s) {
         super(s);
     }
+
+    public ServiceUnavailable(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -0,0 +1,6 @@
+package io.nash.openlimits;
+
+public enum Side {
+    Buy,
+    Sell
+}
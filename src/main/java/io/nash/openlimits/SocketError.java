@@ -4,4 +4,8 @@ public class SocketError extends OpenLimitsException {
     public SocketError(String s) {
         super(s);
     }
+
+    public SocketError(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
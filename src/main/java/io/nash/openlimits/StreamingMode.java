@@ -0,0 +1,11 @@
+package io.nash.openlimits;
+
+// When the websocket of an ExchangeClient is opened
+public enum StreamingMode {
+    // When the client is created
+    Eager,
+    // On the first subscribe, so clients that only use REST never start the websocket threads
+    Lazy,
+    // Never, subscribing fails
+    Disabled
+}
@@ -4,4 +4,8 @@ public class SubscribeException extends OpenLimitsException {
     public SubscribeException(String s) {
         super(s);
     }
+
+    public SubscribeException(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -15,4 +15,14 @@ public class Subscription {
     public static Subscription trade(String market) {
         return new Subscription("Trade", market);
     }
+    public static Subscription accountOrders(String market) {
+        return new Subscription("AccountOrders", market);
+    }
+    public static Subscription accountTrades(String market) {
+        return new Subscription("AccountTrades", market);
+    }
+    // Every asset of the account
+    public static Subscription accountBalances() {
+        return new Subscription("AccountBalances", null);
+    }
 }
@@ -0,0 +1,19 @@
+package io.nash.openlimits;
+
+public class SubscriptionMetrics {
+    public final String subscription;
+    public final long messages;
+
+    public SubscriptionMetrics(String subscription, long messages) {
+        this.subscription = subscription;
+        this.messages = messages;
+    }
+
+    @Override
+    public String toString() {
+        return "SubscriptionMetrics{" +
+                "subscription='" + subscription + '\'' +
+                ", messages=" + messages +
+                '}';
+    }
+}
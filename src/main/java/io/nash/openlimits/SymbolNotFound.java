@@ -4,4 +4,8 @@ public class SymbolNotFound extends OpenLimitsException {
     public SymbolNotFound(String s) {
         super(s);
     }
+
+    public SymbolNotFound(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -1,12 +1,18 @@
 package io.nash.openlimits;
 
+import java.math.BigDecimal;
+
 public class Ticker {
-    public final String price;
+    public final BigDecimal price;
 
-    public Ticker(String price) {
+    public Ticker(BigDecimal price) {
         this.price = price;
     }
 
+    public Ticker(String price) {
+        this(new BigDecimal(price));
+    }
+
     @Override
     public String toString() {
         return "Ticker{" +
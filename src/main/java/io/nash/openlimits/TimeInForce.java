@@ -0,0 +1,12 @@
+package io.nash.openlimits;
+
+public enum TimeInForce {
+    // Good till cancelled
+    GTC,
+    // Good till time, see LimitRequest.timeInForceDurationMs
+    GTT,
+    // Immediate or cancel
+    IOC,
+    // Fill or kill
+    FOK
+}
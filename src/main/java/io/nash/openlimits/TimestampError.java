@@ -4,4 +4,8 @@ public class TimestampError extends OpenLimitsException {
     public TimestampError(String s) {
         super(s);
     }
+
+    public TimestampError(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -1,5 +1,7 @@
 package io.nash.openlimits;
 
+import java.math.BigDecimal;
+
 public class Trade {
     public final String id;
     // nullable
@@ -7,16 +9,29 @@ public class Trade {
     // nullable
     public final String sellerOrderId;
     public final String marketPair;
-    public final String price;
-    public final String qty;
+    public final BigDecimal price;
+    public final BigDecimal qty;
     // nullable
-    public final String fees;
-    public final String side;
+    public final BigDecimal fees;
+    public final Side side;
     // nullable
-    public final String liquidity;
+    public final Liquidity liquidity;
     public final long createdAt;
+    // Fixed-point mode only, where price and qty are null: the price is priceFixed * 10^-priceScale
+    public final long priceFixed;
+    public final long qtyFixed;
+    public final int priceScale;
+    public final int qtyScale;
+
+    public Trade(String id, String buyerOrderId, String sellerOrderId, String marketPair, BigDecimal price, BigDecimal qty, BigDecimal fees, Side side, Liquidity liquidity, long createdAt) {
+        this(id, buyerOrderId, sellerOrderId, marketPair, price, qty, fees, side, liquidity, createdAt, 0, 0, 0, 0);
+    }
 
-    public Trade(String id, String buyerOrderId, String sellerOrderId, String marketPair, String price, String qty, String fees, String side, String liquidity, long createdAt) {
+    public Trade(String id, String buyerOrderId, String sellerOrderId, String marketPair, long priceFixed, long qtyFixed, int priceScale, int qtyScale, BigDecimal fees, Side side, Liquidity liquidity, long createdAt) {
+        this(id, buyerOrderId, sellerOrderId, marketPair, null, null, fees, side, liquidity, createdAt, priceFixed, qtyFixed, priceScale, qtyScale);
+    }
+
+    private Trade(String id, String buyerOrderId, String sellerOrderId, String marketPair, BigDecimal price, BigDecimal qty, BigDecimal fees, Side side, Liquidity liquidity, long createdAt, long priceFixed, long qtyFixed, int priceScale, int qtyScale) {
         this.id = id;
         this.buyerOrderId = buyerOrderId;
         this.sellerOrderId = sellerOrderId;
@@ -27,6 +42,14 @@ public class Trade {
         this.side = side;
         this.liquidity = liquidity;
         this.createdAt = createdAt;
+        this.priceFixed = priceFixed;
+        this.qtyFixed = qtyFixed;
+        this.priceScale = priceScale;
+        this.qtyScale = qtyScale;
+    }
+
+    public Trade(String id, String buyerOrderId, String sellerOrderId, String marketPair, String price, String qty, String fees, Side side, Liquidity liquidity, long createdAt) {
+        this(id, buyerOrderId, sellerOrderId, marketPair, new BigDecimal(price), new BigDecimal(qty), Decimals.parseNullable(fees), side, liquidity, createdAt);
     }
 
     @Override
@@ -39,9 +62,13 @@ public class Trade {
                 ", price=" + price +
                 ", qty=" + qty +
                 ", fees=" + fees +
-                ", side='" + side + '\'' +
-                ", liquidity='" + liquidity + '\'' +
+                ", side=" + side +
+                ", liquidity=" + liquidity +
                 ", createdAt=" + createdAt +
+                ", priceFixed=" + priceFixed +
+                ", qtyFixed=" + qtyFixed +
+                ", priceScale=" + priceScale +
+                ", qtyScale=" + qtyScale +
                 '}';
     }
 }
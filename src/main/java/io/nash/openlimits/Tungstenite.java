@@ -4,4 +4,8 @@ public class Tungstenite extends OpenLimitsException {
     public Tungstenite(String s) {
         super(s);
     }
+
+    public Tungstenite(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
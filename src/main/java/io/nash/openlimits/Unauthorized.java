@@ -4,4 +4,8 @@ public class Unauthorized extends OpenLimitsException {
     public Unauthorized(String s) {
         super(s);
     }
+
+    public Unauthorized(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
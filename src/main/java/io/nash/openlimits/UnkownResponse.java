@@ -4,4 +4,8 @@ public class UnkownResponse extends OpenLimitsException {
     public UnkownResponse(String s) {
         super(s);
     }
+
+    public UnkownResponse(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
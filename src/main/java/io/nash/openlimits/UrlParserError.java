@@ -4,4 +4,8 @@ public class UrlParserError extends OpenLimitsException {
     public UrlParserError(String s) {
         super(s);
     }
+
+    public UrlParserError(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
@@ -4,4 +4,8 @@ public class WebSocketMessageNotSupported extends OpenLimitsException {
     public WebSocketMessageNotSupported(String s) {
         super(s);
     }
+
+    public WebSocketMessageNotSupported(String message, String exchange, Long errorCode, int httpStatus, String exchangeMessage, String operation, String market, boolean retryable) {
+        super(message, exchange, errorCode, httpStatus, exchangeMessage, operation, market, retryable);
+    }
 }
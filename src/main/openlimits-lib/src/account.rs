@@ -0,0 +1,526 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use futures_util::FutureExt;
+use rust_decimal::Decimal;
+use tokio::sync::mpsc::UnboundedReceiver;
+use openlimits::OpenLimits;
+use openlimits::errors::OpenLimitsError;
+use openlimits::exchange::any::{AnyExchange, InitAnyExchange};
+use openlimits::exchange::traits::ExchangeAccount;
+use openlimits::model::{Balance, GetOrderHistoryRequest, GetOrderRequest, Order, Paginator, Trade, TradeHistoryRequest};
+use openlimits::model::websocket::Subscription;
+use crate::secrets::SecretParams;
+use crate::tracker::is_finished;
+
+// Records per page when reading the order and trade history
+const PAGE_SIZE: u64 = 500;
+// How far each read of the history reaches back before the previous one, for records the exchange published late
+const HISTORY_OVERLAP: Duration = Duration::from_secs(60);
+
+/// A private stream of the account behind the client's credentials.
+#[derive(Clone, Debug)]
+pub enum AccountStream {
+  Orders(String),
+  Fills(String),
+  Balances,
+}
+
+impl AccountStream {
+  pub fn label(&self) -> String {
+    match self {
+      AccountStream::Orders(market) => format!("orders:{}", market),
+      AccountStream::Fills(market) => format!("fills:{}", market),
+      AccountStream::Balances => String::from("balances"),
+    }
+  }
+}
+
+/// A change to the account, delivered to `onOrderUpdate`, `onFill` or `onBalance`.
+#[derive(Clone, Debug)]
+pub enum AccountUpdate {
+  Order(Order),
+  // An order of a market without order update subscription, only for the order tracker
+  TrackedOrder(Order),
+  Fill(Trade),
+  // A fill of a market without fill subscription, only for the position tracker
+  TrackedFill(Trade),
+  Balance(Balance),
+}
+
+impl AccountUpdate {
+  pub fn operation(&self) -> &'static str {
+    match self {
+      AccountUpdate::Order(_) => "onOrderUpdate",
+      AccountUpdate::TrackedOrder(_) => "onOrderStateChange",
+      AccountUpdate::Fill(_) => "onFill",
+      AccountUpdate::TrackedFill(_) => "trackPositions",
+      AccountUpdate::Balance(_) => "onBalance",
+    }
+  }
+
+  pub fn market(&self) -> Option<String> {
+    match self {
+      AccountUpdate::Order(order) | AccountUpdate::TrackedOrder(order) => Some(order.market_pair.clone()),
+      AccountUpdate::Fill(trade) | AccountUpdate::TrackedFill(trade) => Some(trade.market_pair.clone()),
+      AccountUpdate::Balance(_) => None,
+    }
+  }
+
+  pub fn label(&self) -> String {
+    match self {
+      AccountUpdate::Order(order) => AccountStream::Orders(order.market_pair.clone()).label(),
+      AccountUpdate::TrackedOrder(_) => String::from("orders:tracked"),
+      AccountUpdate::Fill(trade) => AccountStream::Fills(trade.market_pair.clone()).label(),
+      AccountUpdate::TrackedFill(_) => String::from("fills:tracked"),
+      AccountUpdate::Balance(_) => AccountStream::Balances.label(),
+    }
+  }
+}
+
+/// A failed read of the account, with the market it was for if any.
+pub type PollError = (OpenLimitsError, Option<String>);
+
+/// Answers `AccountCmd::Subscribe` with the websocket subscriptions that announce changes of the stream.
+pub type SubscribeReply = Box<dyn FnOnce(Result<Vec<Subscription>, OpenLimitsError>) + Send>;
+
+pub enum AccountCmd {
+  // Reads the current state of a stream, so only later changes are reported
+  Subscribe(AccountStream, SubscribeReply),
+  // A websocket subscription of the stream announced a change
+  Changed(AccountStream),
+}
+
+/// Whether openlimits subscribes the account streams of an exchange on its websocket. Only its Nash websocket maps
+/// `Subscription::AccountOrders`, `AccountTrades` and `AccountBalance`. The Binance user-data stream needs a listen
+/// key and the Coinbase user channel a signed subscription, openlimits does neither, so those accounts are polled.
+pub fn pushes_account_streams(params: &InitAnyExchange) -> bool {
+  matches!(params, InitAnyExchange::Nash(_))
+}
+
+/// Follows the account on a thread of its own with a REST client of its own, so reading it never holds up the
+/// websocket. openlimits hands account messages over in their exchange specific form, so a message only says that
+/// a stream changed. The change is read back and compared with what was reported before, which also finds changes
+/// the exchange didn't announce. Exchanges without account subscriptions are read every interval instead.
+pub struct AccountPoller {
+  params: SecretParams,
+  // Created on the account thread, a slow login mustn't hold up the websocket
+  client: Option<Arc<AnyExchange>>,
+  push: bool,
+  interval: Duration,
+  next_poll: Instant,
+  // Lowercase to the market as subscribed, exchanges don't always spell the market of an order the same way
+  order_markets: HashMap<String, String>,
+  // Every open order is read for the order tracker, whether its market is subscribed or not
+  track_orders: bool,
+  fill_markets: HashSet<String>,
+  // Read for the position tracker, whether subscribed or not
+  tracked_fill_markets: HashSet<String>,
+  balances: bool,
+  // What the previous read saw
+  open_orders: HashMap<String, Order>,
+  // Orders reported finished within the history overlap, with their creation time
+  finished_orders: HashMap<String, u64>,
+  orders_since: u64,
+  // Per market, the fills reported within the history overlap with their time, and where the next read starts
+  fills: HashMap<String, HashMap<String, u64>>,
+  fills_since: HashMap<String, u64>,
+  balance_values: HashMap<String, (Decimal, Decimal)>,
+  // Seen while subscribing, but still news to a tracker
+  pending: Vec<AccountUpdate>,
+}
+
+impl AccountPoller {
+  pub fn new(params: SecretParams, interval: Duration) -> Self {
+    AccountPoller {
+      push: pushes_account_streams(params.params()),
+      params,
+      client: None,
+      interval,
+      next_poll: Instant::now() + interval,
+      order_markets: HashMap::new(),
+      track_orders: false,
+      fill_markets: HashSet::new(),
+      tracked_fill_markets: HashSet::new(),
+      balances: false,
+      open_orders: HashMap::new(),
+      finished_orders: HashMap::new(),
+      orders_since: history_start(),
+      fills: HashMap::new(),
+      fills_since: HashMap::new(),
+      balance_values: HashMap::new(),
+      pending: Vec::new(),
+    }
+  }
+
+  /// Reads the orders of every market for the order tracker. No single subscription covers every market, so they
+  /// are read every interval even where account streams are pushed. Unlike subscriptions this reports the orders
+  /// open at the first read too, the tracker wants to know about them.
+  pub fn track_orders(&mut self) {
+    self.track_orders = true;
+  }
+
+  /// Reads the fills of markets for the position tracker. Like `track_orders`, the first read reports the recent
+  /// fills the trade history already has.
+  pub fn track_fills(&mut self, markets: Vec<String>) {
+    self.tracked_fill_markets.extend(markets);
+  }
+
+  /// The websocket subscriptions that announce the fills of the tracked markets.
+  pub fn tracker_subscriptions(&self) -> Vec<(AccountStream, Subscription)> {
+    match self.push {
+      true => self.tracked_fill_markets.iter()
+        .map(|market| (AccountStream::Fills(market.clone()), Subscription::AccountTrades(market.clone())))
+        .collect(),
+      false => Vec::new(),
+    }
+  }
+
+  /// Handles commands until every sender is gone or `report` returns false because nobody listens anymore.
+  pub async fn run<F>(mut self, mut commands: UnboundedReceiver<AccountCmd>, mut report: F)
+    where F: FnMut(Result<AccountUpdate, PollError>) -> bool
+  {
+    loop {
+      let command = match self.polls() {
+        true => match tokio::time::timeout(self.until_next_poll(), commands.recv()).await {
+          Ok(command) => command,
+          Err(_) => {
+            let (updates, errors) = self.poll().await;
+            if !deliver(&mut report, updates, errors) {
+              return;
+            }
+            continue;
+          },
+        },
+        false => commands.recv().await,
+      };
+      let mut command = match command {
+        Some(command) => command,
+        None => return,
+      };
+      // A burst of announcements is read back once
+      let mut changed: Vec<AccountStream> = Vec::new();
+      loop {
+        match command {
+          AccountCmd::Subscribe(stream, reply) => {
+            let result = self.subscribe(stream).await;
+            reply(result);
+          },
+          AccountCmd::Changed(stream) => {
+            if !changed.iter().any(|seen| seen.label() == stream.label()) {
+              changed.push(stream);
+            }
+          },
+        }
+        command = match commands.recv().now_or_never() {
+          Some(Some(command)) => command,
+          _ => break,
+        };
+      }
+      let mut updates = std::mem::take(&mut self.pending);
+      let mut errors = Vec::new();
+      for stream in changed {
+        self.refresh(&stream, &mut updates, &mut errors).await;
+      }
+      if !deliver(&mut report, updates, errors) {
+        return;
+      }
+    }
+  }
+
+  /// Adds a stream after reading its current state, so only later changes are reported. Fails when the account
+  /// cannot be read, e.g. without credentials.
+  async fn subscribe(&mut self, stream: AccountStream) -> Result<Vec<Subscription>, OpenLimitsError> {
+    let client = self.connect().await?;
+    let mut ignored = Vec::new();
+    let mut errors = Vec::new();
+    match &stream {
+      AccountStream::Orders(_) if !self.reads_orders() => self.read_orders(&client, &mut ignored, &mut errors).await,
+      AccountStream::Fills(market) if !self.reads_fills(market) => self.read_fills(&client, market, &mut ignored, &mut errors).await,
+      AccountStream::Balances if !self.balances => self.read_balances(&client, &mut ignored, &mut errors).await,
+      _ => {},
+    }
+    if let Some((err, _)) = errors.into_iter().next() {
+      return Err(err);
+    }
+    let subscriptions = match stream {
+      AccountStream::Orders(market) => {
+        let new = self.order_markets.insert(market.to_lowercase(), market.clone()).is_none();
+        match new {
+          true => vec![Subscription::AccountOrders(market)],
+          false => Vec::new(),
+        }
+      },
+      AccountStream::Fills(market) => {
+        let tracked = self.tracked_fill_markets.contains(&market);
+        if tracked {
+          self.pending.extend(ignored);
+        }
+        match self.fill_markets.insert(market.clone()) && !tracked {
+          true => vec![Subscription::AccountTrades(market)],
+          false => Vec::new(),
+        }
+      },
+      AccountStream::Balances => {
+        let new = !self.balances;
+        self.balances = true;
+        // openlimits announces balances per asset
+        match new {
+          true => self.balance_values.keys().map(|asset| Subscription::AccountBalance(asset.clone())).collect(),
+          false => Vec::new(),
+        }
+      },
+    };
+    Ok(match self.push {
+      true => subscriptions,
+      false => Vec::new(),
+    })
+  }
+
+  async fn connect(&mut self) -> Result<Arc<AnyExchange>, OpenLimitsError> {
+    if let Some(client) = &self.client {
+      return Ok(client.clone());
+    }
+    let client: AnyExchange = OpenLimits::instantiate(self.params.instantiate_params()).await?;
+    let client = Arc::new(client);
+    self.client = Some(client.clone());
+    Ok(client)
+  }
+
+  fn reads_orders(&self) -> bool {
+    self.track_orders || !self.order_markets.is_empty()
+  }
+
+  fn reads_fills(&self, market: &str) -> bool {
+    self.fill_markets.contains(market) || self.tracked_fill_markets.contains(market)
+  }
+
+  fn is_subscribed(&self) -> bool {
+    self.reads_orders() || !self.fill_markets.is_empty() || !self.tracked_fill_markets.is_empty() || self.balances
+  }
+
+  // Whether anything is read on a timer rather than when announced
+  fn polls(&self) -> bool {
+    self.track_orders || (!self.push && self.is_subscribed())
+  }
+
+  fn until_next_poll(&self) -> Duration {
+    self.next_poll.saturating_duration_since(Instant::now())
+  }
+
+  async fn poll(&mut self) -> (Vec<AccountUpdate>, Vec<PollError>) {
+    self.next_poll = Instant::now() + self.interval;
+    let mut updates = std::mem::take(&mut self.pending);
+    let mut errors = Vec::new();
+    let client = match self.connect().await {
+      Ok(client) => client,
+      Err(err) => {
+        errors.push((err, None));
+        return (updates, errors);
+      },
+    };
+    if self.reads_orders() {
+      self.read_orders(&client, &mut updates, &mut errors).await;
+    }
+    if !self.push {
+      for market in self.fill_markets.union(&self.tracked_fill_markets).cloned().collect::<Vec<_>>() {
+        self.read_fills(&client, &market, &mut updates, &mut errors).await;
+      }
+      if self.balances {
+        self.read_balances(&client, &mut updates, &mut errors).await;
+      }
+    }
+    (updates, errors)
+  }
+
+  async fn refresh(&mut self, stream: &AccountStream, updates: &mut Vec<AccountUpdate>, errors: &mut Vec<PollError>) {
+    let client = match self.connect().await {
+      Ok(client) => client,
+      Err(err) => {
+        errors.push((err, None));
+        return;
+      },
+    };
+    match stream {
+      AccountStream::Orders(_) => self.read_orders(&client, updates, errors).await,
+      AccountStream::Fills(market) => self.read_fills(&client, market, updates, errors).await,
+      AccountStream::Balances => self.read_balances(&client, updates, errors).await,
+    }
+  }
+
+  async fn read_orders(&mut self, client: &AnyExchange, updates: &mut Vec<AccountUpdate>, errors: &mut Vec<PollError>) {
+    let started = history_start();
+    let open = match client.get_all_open_orders().await {
+      Ok(open) => open,
+      Err(err) => {
+        errors.push((err, None));
+        return;
+      },
+    };
+    let mut previous = std::mem::take(&mut self.open_orders);
+    for order in open {
+      let changed = match previous.remove(&order.id) {
+        Some(before) => order_changed(&before, &order),
+        None => true,
+      };
+      if changed {
+        updates.extend(self.order_update(&order));
+      }
+      self.open_orders.insert(order.id.clone(), order);
+    }
+    // Whatever isn't open anymore was filled, canceled or expired, only the order itself tells which
+    for (id, order) in previous {
+      if self.order_update(&order).is_none() {
+        continue;
+      }
+      let req = GetOrderRequest { id: id.clone(), market_pair: Some(order.market_pair.clone()) };
+      match client.get_order(&req).await {
+        Ok(order) => self.finished(order, updates),
+        Err(err) => {
+          errors.push((err, Some(order.market_pair.clone())));
+          // Looked up again by the next read
+          self.open_orders.insert(id, order);
+        },
+      }
+    }
+    // An order placed and finished between two reads never was open to them, only the order history has it
+    let markets: Vec<String> = self.order_markets.values().cloned().collect();
+    for market in markets {
+      match order_history(client, &market, self.orders_since).await {
+        Ok(orders) => {
+          for order in orders {
+            let seen = self.open_orders.contains_key(&order.id) || self.finished_orders.contains_key(&order.id);
+            if !seen && is_finished(order.status) {
+              self.finished(order, updates);
+            }
+          }
+        },
+        Err(err) => errors.push((err, Some(market))),
+      }
+    }
+    self.orders_since = started;
+    self.finished_orders.retain(|_, created_at| *created_at >= started);
+  }
+
+  fn finished(&mut self, order: Order, updates: &mut Vec<AccountUpdate>) {
+    self.finished_orders.insert(order.id.clone(), order.created_at.unwrap_or_else(now_millis));
+    updates.extend(self.order_update(&order));
+  }
+
+  fn order_update(&self, order: &Order) -> Option<AccountUpdate> {
+    if self.order_markets.contains_key(&order.market_pair.to_lowercase()) {
+      Some(AccountUpdate::Order(order.clone()))
+    } else if self.track_orders {
+      Some(AccountUpdate::TrackedOrder(order.clone()))
+    } else {
+      None
+    }
+  }
+
+  async fn read_fills(&mut self, client: &AnyExchange, market: &str, updates: &mut Vec<AccountUpdate>, errors: &mut Vec<PollError>) {
+    let started = history_start();
+    let since = self.fills_since.get(market).copied().unwrap_or(started);
+    let trades = match trade_history(client, market, Some(since)).await {
+      Ok(trades) => trades,
+      Err(err) => {
+        errors.push((err, Some(String::from(market))));
+        return;
+      },
+    };
+    let seen = self.fills.entry(String::from(market)).or_default();
+    let mut fills: Vec<Trade> = trades.into_iter().filter(|trade| !seen.contains_key(&trade.id)).collect();
+    for fill in &fills {
+      seen.insert(fill.id.clone(), fill.created_at);
+    }
+    seen.retain(|_, created_at| *created_at >= started);
+    self.fills_since.insert(String::from(market), started);
+    fills.sort_by_key(|trade| trade.created_at);
+    let subscribed = self.fill_markets.contains(market);
+    updates.extend(fills.into_iter().map(|fill| match subscribed {
+      true => AccountUpdate::Fill(fill),
+      false => AccountUpdate::TrackedFill(fill),
+    }));
+  }
+
+  async fn read_balances(&mut self, client: &AnyExchange, updates: &mut Vec<AccountUpdate>, errors: &mut Vec<PollError>) {
+    let balances = match client.get_account_balances(None).await {
+      Ok(balances) => balances,
+      Err(err) => {
+        errors.push((err, None));
+        return;
+      },
+    };
+    for balance in balances {
+      let value = (balance.total, balance.free);
+      if self.balance_values.insert(balance.asset.clone(), value) != Some(value) {
+        updates.push(AccountUpdate::Balance(balance));
+      }
+    }
+  }
+}
+
+/// Every fill of a market from `since` on, in milliseconds, or the whole trade history.
+pub async fn trade_history(client: &AnyExchange, market: &str, since: Option<u64>) -> Result<Vec<Trade>, OpenLimitsError> {
+  paged(since, |paginator| {
+    let req = TradeHistoryRequest { market_pair: Some(String::from(market)), order_id: None, paginator: Some(paginator) };
+    async move { client.get_trade_history(&req).await }
+  }, |trade: &Trade| (trade.id.clone(), Some(trade.created_at))).await
+}
+
+async fn order_history(client: &AnyExchange, market: &str, since: u64) -> Result<Vec<Order>, OpenLimitsError> {
+  paged(Some(since), |paginator| {
+    let req = GetOrderHistoryRequest { market_pair: Some(String::from(market)), order_status: None, paginator: Some(paginator) };
+    async move { client.get_order_history(&req).await }
+  }, |order: &Order| (order.id.clone(), order.created_at)).await
+}
+
+/// Reads a history page by page, each starting at the latest record of the one before. Fails when a full page
+/// doesn't get past its start, rather than returning a history with a gap.
+async fn paged<T, F, R, K>(since: Option<u64>, mut fetch: F, key: K) -> Result<Vec<T>, OpenLimitsError>
+  where F: FnMut(Paginator) -> R, R: Future<Output = Result<Vec<T>, OpenLimitsError>>, K: Fn(&T) -> (String, Option<u64>)
+{
+  let mut records = Vec::new();
+  let mut ids = HashSet::new();
+  let mut start = since;
+  loop {
+    let page = fetch(Paginator { start_time: start, limit: Some(PAGE_SIZE), ..Default::default() }).await?;
+    let full = page.len() as u64 >= PAGE_SIZE;
+    let latest = page.iter().filter_map(|record| key(record).1).max();
+    for record in page {
+      // Pages overlap by the records at their boundary
+      if ids.insert(key(&record).0) {
+        records.push(record);
+      }
+    }
+    if !full {
+      return Ok(records);
+    }
+    match latest {
+      Some(latest) if Some(latest) > start => start = Some(latest),
+      _ => return Err(OpenLimitsError::NotParsableResponse(format!(
+        "Cannot page the history past {} records from {:?}, the exchange ignores the start time", PAGE_SIZE, start
+      ))),
+    }
+  }
+}
+
+/// Delivers updates, then errors, until `report` returns false.
+fn deliver<F>(report: &mut F, updates: Vec<AccountUpdate>, errors: Vec<PollError>) -> bool
+  where F: FnMut(Result<AccountUpdate, PollError>) -> bool
+{
+  updates.into_iter().map(Ok).chain(errors.into_iter().map(Err)).all(report)
+}
+
+fn now_millis() -> u64 {
+  SystemTime::now().duration_since(UNIX_EPOCH).map(|now| now.as_millis() as u64).unwrap_or_default()
+}
+
+// Where a read of the history starting now begins
+fn history_start() -> u64 {
+  now_millis().saturating_sub(HISTORY_OVERLAP.as_millis() as u64)
+}
+
+fn order_changed(before: &Order, after: &Order) -> bool {
+  before.status != after.status || before.remaining != after.remaining || before.trades.len() != after.trades.len()
+}
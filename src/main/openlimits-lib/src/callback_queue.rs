@@ -0,0 +1,195 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use crate::metrics::Metrics;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OverflowPolicy {
+  // Wait for the callback thread, which stalls the websocket stream
+  Block,
+  DropOldest,
+  DropNewest,
+}
+
+#[derive(Clone, Debug)]
+pub struct QueueOptions {
+  pub policy: OverflowPolicy,
+  pub capacity: usize,
+  // Keep only the latest order book snapshot of each market
+  pub conflate_orderbooks: bool,
+}
+
+impl Default for QueueOptions {
+  fn default() -> Self {
+    QueueOptions { policy: OverflowPolicy::Block, capacity: 100, conflate_orderbooks: false }
+  }
+}
+
+pub trait QueuedMessage {
+  /// Control messages are never dropped, conflated or counted against the capacity.
+  fn is_droppable(&self) -> bool;
+  /// Messages with the same key replace each other when conflation is enabled.
+  fn conflation_key(&self) -> Option<&str>;
+}
+
+/// Returned once the other side of the queue is gone.
+#[derive(Debug)]
+pub struct Disconnected;
+
+struct State<T> {
+  messages: VecDeque<T>,
+  droppable: usize,
+  dropped: u64,
+  senders: usize,
+  receiver_alive: bool,
+}
+
+struct Shared<T> {
+  state: Mutex<State<T>>,
+  not_empty: Condvar,
+  not_full: Condvar,
+  options: QueueOptions,
+  metrics: Arc<Metrics>,
+}
+
+impl<T> Shared<T> {
+  fn lock(&self) -> MutexGuard<'_, State<T>> {
+    // Nothing in here can panic while holding the lock, so the state stays consistent either way
+    self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
+
+/// The websocket side of the queue between the websocket threads and the Java callback thread.
+pub struct CallbackSender<T>(Arc<Shared<T>>);
+
+pub struct CallbackReceiver<T>(Arc<Shared<T>>);
+
+pub fn callback_queue<T: QueuedMessage>(options: QueueOptions, metrics: Arc<Metrics>) -> (CallbackSender<T>, CallbackReceiver<T>) {
+  let shared = Arc::new(Shared {
+    state: Mutex::new(State {
+      messages: VecDeque::new(),
+      droppable: 0,
+      dropped: 0,
+      senders: 1,
+      receiver_alive: true,
+    }),
+    not_empty: Condvar::new(),
+    not_full: Condvar::new(),
+    options,
+    metrics,
+  });
+  (CallbackSender(shared.clone()), CallbackReceiver(shared))
+}
+
+impl<T: QueuedMessage> CallbackSender<T> {
+  pub fn send(&self, msg: T) -> Result<(), Disconnected> {
+    let shared = &self.0;
+    let mut state = shared.lock();
+    if !state.receiver_alive {
+      return Err(Disconnected);
+    }
+    if !msg.is_droppable() {
+      state.messages.push_back(msg);
+      shared.not_empty.notify_one();
+      return Ok(());
+    }
+
+    if shared.options.conflate_orderbooks {
+      let queued = msg.conflation_key()
+        .and_then(|key| state.messages.iter().position(|queued| queued.conflation_key() == Some(key)));
+      if let Some(position) = queued {
+        // Move to the back so the latest snapshot is delivered in order with what came before it
+        state.messages.remove(position);
+        state.messages.push_back(msg);
+        state.dropped += 1;
+        shared.not_empty.notify_one();
+        return Ok(());
+      }
+    }
+
+    while state.droppable >= shared.options.capacity.max(1) {
+      match shared.options.policy {
+        OverflowPolicy::Block => {
+          state = shared.not_full.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+          if !state.receiver_alive {
+            return Err(Disconnected);
+          }
+        },
+        OverflowPolicy::DropNewest => {
+          state.dropped += 1;
+          return Ok(());
+        },
+        OverflowPolicy::DropOldest => {
+          if let Some(position) = state.messages.iter().position(|queued| queued.is_droppable()) {
+            state.messages.remove(position);
+            state.droppable -= 1;
+            state.dropped += 1;
+            shared.metrics.message_dequeued();
+          }
+        },
+      }
+    }
+
+    state.messages.push_back(msg);
+    state.droppable += 1;
+    shared.metrics.message_enqueued();
+    shared.not_empty.notify_one();
+    Ok(())
+  }
+}
+
+impl<T> Clone for CallbackSender<T> {
+  fn clone(&self) -> Self {
+    self.0.lock().senders += 1;
+    CallbackSender(self.0.clone())
+  }
+}
+
+impl<T> Drop for CallbackSender<T> {
+  fn drop(&mut self) {
+    let mut state = self.0.lock();
+    state.senders -= 1;
+    if state.senders == 0 {
+      self.0.not_empty.notify_all();
+    }
+  }
+}
+
+impl<T: QueuedMessage> CallbackReceiver<T> {
+  /// Blocks until a message arrives. Fails once the queue is empty and every sender is gone.
+  pub fn recv(&self) -> Result<T, Disconnected> {
+    let shared = &self.0;
+    let mut state = shared.lock();
+    loop {
+      if let Some(msg) = state.messages.pop_front() {
+        if msg.is_droppable() {
+          state.droppable -= 1;
+          shared.metrics.message_dequeued();
+          shared.not_full.notify_one();
+        }
+        return Ok(msg);
+      }
+      if state.senders == 0 {
+        return Err(Disconnected);
+      }
+      state = shared.not_empty.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+    }
+  }
+
+  /// Returns how many messages were dropped or conflated since the last call.
+  pub fn take_dropped(&self) -> u64 {
+    std::mem::take(&mut self.0.lock().dropped)
+  }
+}
+
+impl<T> Drop for CallbackReceiver<T> {
+  fn drop(&mut self) {
+    let mut state = self.0.lock();
+    state.receiver_alive = false;
+    for _ in 0..state.droppable {
+      self.0.metrics.message_dequeued();
+    }
+    state.droppable = 0;
+    state.messages.clear();
+    self.0.not_full.notify_all();
+  }
+}
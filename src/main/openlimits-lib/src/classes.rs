@@ -0,0 +1,170 @@
+use jni::JNIEnv;
+use jni::errors;
+use jni::objects::{GlobalRef, JClass, JMethodID, JObject, JStaticMethodID, JValue};
+use jni::signature::JavaType;
+use jni::sys::jmethodID;
+use once_cell::sync::OnceCell;
+
+static CLASSES: OnceCell<JavaClasses> = OnceCell::new();
+
+/// A class pinned by a global ref, with the constructor its instances are built with.
+pub struct JavaClass {
+  class: GlobalRef,
+  ctor: jmethodID,
+}
+
+// Method ids stay valid on every thread for as long as the class is loaded, which the global ref guarantees
+unsafe impl Send for JavaClass {}
+unsafe impl Sync for JavaClass {}
+
+impl JavaClass {
+  fn load(env: &JNIEnv, class_name: &str, ctor_sig: &str) -> errors::Result<Self> {
+    let cls = env.find_class(class_name)?;
+    let ctor = env.get_method_id(cls, "<init>", ctor_sig)?.into_inner();
+    Ok(JavaClass { class: env.new_global_ref(cls)?, ctor })
+  }
+
+  pub fn class<'a>(&self) -> JClass<'a> {
+    JClass::from(self.class.as_obj().into_inner())
+  }
+
+  /// Calls the cached constructor. `args` have to match the signature it was loaded with.
+  pub fn new_object<'a>(&self, env: &JNIEnv<'a>, args: &[JValue]) -> errors::Result<JObject<'a>> {
+    env.new_object_unchecked(self.class(), JMethodID::from(self.ctor), args)
+  }
+}
+
+/// `java.math.BigDecimal`, which is built far more often than anything else.
+pub struct BigDecimalClass {
+  class: GlobalRef,
+  value_of: jmethodID,
+  from_unscaled: jmethodID,
+  big_integer: JavaClass,
+}
+
+unsafe impl Send for BigDecimalClass {}
+unsafe impl Sync for BigDecimalClass {}
+
+impl BigDecimalClass {
+  fn load(env: &JNIEnv) -> errors::Result<Self> {
+    let cls = env.find_class("java/math/BigDecimal")?;
+    Ok(BigDecimalClass {
+      value_of: env.get_static_method_id(cls, "valueOf", "(JI)Ljava/math/BigDecimal;")?.into_inner(),
+      from_unscaled: env.get_method_id(cls, "<init>", "(Ljava/math/BigInteger;I)V")?.into_inner(),
+      class: env.new_global_ref(cls)?,
+      big_integer: JavaClass::load(env, "java/math/BigInteger", "([B)V")?,
+    })
+  }
+
+  pub fn value_of<'a>(&self, env: &JNIEnv<'a>, unscaled: i64, scale: i32) -> errors::Result<JObject<'a>> {
+    let class = JClass::from(self.class.as_obj().into_inner());
+    env.call_static_method_unchecked(
+      class,
+      JStaticMethodID::from(self.value_of),
+      JavaType::Object(String::from("java/math/BigDecimal")),
+      &[JValue::Long(unscaled), JValue::Int(scale)]
+    )?.l()
+  }
+
+  /// For values that don't fit in a long, `unscaled` holds the two's complement big endian bytes.
+  pub fn with_unscaled_bytes<'a>(&self, env: &JNIEnv<'a>, unscaled: &[u8], scale: i32) -> errors::Result<JObject<'a>> {
+    let bytes = env.byte_array_from_slice(unscaled)?;
+    let unscaled = self.big_integer.new_object(env, &[JObject::from(bytes).into()])?;
+    let class = JClass::from(self.class.as_obj().into_inner());
+    env.new_object_unchecked(class, JMethodID::from(self.from_unscaled), &[unscaled.into(), JValue::Int(scale)])
+  }
+}
+
+pub struct JavaClasses {
+  pub big_decimal: BigDecimalClass,
+  pub ask_bid: JavaClass,
+  pub ask_bid_fixed: JavaClass,
+  pub balance: JavaClass,
+  pub candle: JavaClass,
+  pub candle_fixed: JavaClass,
+  pub market_pair: JavaClass,
+  pub order: JavaClass,
+  pub order_fixed: JavaClass,
+  pub order_canceled: JavaClass,
+  pub orderbook_response: JavaClass,
+  pub ticker: JavaClass,
+  pub trade: JavaClass,
+  pub trade_fixed: JavaClass,
+  pub metrics_snapshot: JavaClass,
+  pub latency_histogram: JavaClass,
+  pub error_count: JavaClass,
+  pub subscription_metrics: JavaClass,
+  pub position: JavaClass,
+  pub asset_position: JavaClass,
+  pub audit_record: JavaClass,
+}
+
+/// Resolves every model class and constructor. Done from `JNI_OnLoad`, falling back to the first conversion
+/// for the rare JVM that loads the library from a thread without the application class loader.
+pub fn java_classes(env: &JNIEnv) -> errors::Result<&'static JavaClasses> {
+  CLASSES.get_or_try_init(|| {
+    Ok(JavaClasses {
+      big_decimal: BigDecimalClass::load(env)?,
+      ask_bid: JavaClass::load(env, "io/nash/openlimits/AskBid", "(Ljava/math/BigDecimal;Ljava/math/BigDecimal;)V")?,
+      ask_bid_fixed: JavaClass::load(env, "io/nash/openlimits/AskBid", "(JJII)V")?,
+      balance: JavaClass::load(env, "io/nash/openlimits/Balance", "(Ljava/lang/String;Ljava/math/BigDecimal;Ljava/math/BigDecimal;)V")?,
+      candle: JavaClass::load(
+        env,
+        "io/nash/openlimits/Candle",
+        "(JLjava/math/BigDecimal;Ljava/math/BigDecimal;Ljava/math/BigDecimal;Ljava/math/BigDecimal;Ljava/math/BigDecimal;)V"
+      )?,
+      candle_fixed: JavaClass::load(env, "io/nash/openlimits/Candle", "(JJJJJJII)V")?,
+      market_pair: JavaClass::load(
+        env,
+        "io/nash/openlimits/MarketPair",
+        "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/math/BigDecimal;Ljava/math/BigDecimal;Ljava/math/BigDecimal;Ljava/math/BigDecimal;)V"
+      )?,
+      order: JavaClass::load(
+        env,
+        "io/nash/openlimits/Order",
+        "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;JLio/nash/openlimits/OrderType;Lio/nash/openlimits/Side;Lio/nash/openlimits/OrderStatus;Ljava/math/BigDecimal;Ljava/math/BigDecimal;[Lio/nash/openlimits/Trade;)V"
+      )?,
+      order_fixed: JavaClass::load(
+        env,
+        "io/nash/openlimits/Order",
+        "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;JLio/nash/openlimits/OrderType;Lio/nash/openlimits/Side;Lio/nash/openlimits/OrderStatus;JJII[Lio/nash/openlimits/Trade;)V"
+      )?,
+      order_canceled: JavaClass::load(env, "io/nash/openlimits/OrderCanceled", "(Ljava/lang/String;)V")?,
+      orderbook_response: JavaClass::load(
+        env,
+        "io/nash/openlimits/OrderbookResponse",
+        "(Ljava/lang/String;[Lio/nash/openlimits/AskBid;[Lio/nash/openlimits/AskBid;JJ)V"
+      )?,
+      ticker: JavaClass::load(env, "io/nash/openlimits/Ticker", "(Ljava/math/BigDecimal;)V")?,
+      trade: JavaClass::load(
+        env,
+        "io/nash/openlimits/Trade",
+        "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/math/BigDecimal;Ljava/math/BigDecimal;Ljava/math/BigDecimal;Lio/nash/openlimits/Side;Lio/nash/openlimits/Liquidity;J)V"
+      )?,
+      trade_fixed: JavaClass::load(
+        env,
+        "io/nash/openlimits/Trade",
+        "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;JJIILjava/math/BigDecimal;Lio/nash/openlimits/Side;Lio/nash/openlimits/Liquidity;J)V"
+      )?,
+      metrics_snapshot: JavaClass::load(
+        env,
+        "io/nash/openlimits/MetricsSnapshot",
+        "([Lio/nash/openlimits/LatencyHistogram;[Lio/nash/openlimits/ErrorCount;[Lio/nash/openlimits/SubscriptionMetrics;JJ)V"
+      )?,
+      latency_histogram: JavaClass::load(env, "io/nash/openlimits/LatencyHistogram", "(Ljava/lang/String;JJJ[J[J)V")?,
+      error_count: JavaClass::load(env, "io/nash/openlimits/ErrorCount", "(Ljava/lang/String;J)V")?,
+      subscription_metrics: JavaClass::load(env, "io/nash/openlimits/SubscriptionMetrics", "(Ljava/lang/String;J)V")?,
+      position: JavaClass::load(
+        env,
+        "io/nash/openlimits/Position",
+        "(Ljava/lang/String;Ljava/math/BigDecimal;Ljava/math/BigDecimal;Ljava/math/BigDecimal;Ljava/math/BigDecimal;Ljava/math/BigDecimal;Ljava/math/BigDecimal;)V"
+      )?,
+      asset_position: JavaClass::load(env, "io/nash/openlimits/AssetPosition", "(Ljava/lang/String;Ljava/math/BigDecimal;Ljava/math/BigDecimal;)V")?,
+      audit_record: JavaClass::load(
+        env,
+        "io/nash/openlimits/AuditRecord",
+        "(JLjava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;JLjava/lang/String;)V"
+      )?,
+    })
+  })
+}
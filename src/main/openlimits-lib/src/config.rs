@@ -0,0 +1,394 @@
+use std::path::Path;
+use std::str::FromStr;
+use rust_decimal::Decimal;
+use toml::Value;
+use toml::value::Table;
+use zeroize::Zeroizing;
+use openlimits::prelude::Environment;
+use openlimits::exchange::any::InitAnyExchange;
+use openlimits::exchange::binance::{BinanceCredentials, BinanceParameters};
+use openlimits::exchange::coinbase::{CoinbaseCredentials, CoinbaseParameters};
+use openlimits::exchange::nash::{NashCredentials, NashParameters};
+use crate::risk::RiskLimits;
+use crate::secrets::{expose, SecretParams};
+
+// The tables of a configuration. Environment variables join them to their keys with `_`
+const SECTIONS: &[&str] = &["credentials", "options", "risk_limits", "dead_mans_switch", "audit_journal"];
+const JAVA_INT_MAX: u64 = i32::MAX as u64;
+const JAVA_LONG_MAX: u64 = i64::MAX as u64;
+
+/// A client configuration read from a file or the environment.
+pub struct ClientFile {
+  pub params: SecretParams,
+  pub options: ClientOptions,
+}
+
+/// The `ExchangeClientConfig` options of a configuration, `None` or empty when left to their defaults.
+#[derive(Default)]
+pub struct ClientOptions {
+  pub backpressure_policy: Option<String>,
+  pub callback_queue_capacity: Option<u64>,
+  pub conflate_orderbooks: bool,
+  pub streaming_mode: Option<String>,
+  pub direct_orderbooks: bool,
+  pub fixed_point: bool,
+  pub account_poll_millis: Option<u64>,
+  pub track_orders: bool,
+  pub position_markets: Vec<String>,
+  pub risk_limits: Option<RiskLimits>,
+  // Heartbeat timeout and markets
+  pub dead_mans_switch: Option<(u64, Vec<String>)>,
+  // Directory and file size limit
+  pub audit_journal: Option<(String, u64)>,
+}
+
+/// A TOML file, or JSON when its name ends in `.json`. Fails with every problem found.
+pub fn read_file(path: &Path) -> Result<ClientFile, Vec<String>> {
+  let content = Zeroizing::new(std::fs::read_to_string(path).map_err(|e| vec![format!("Cannot read {}: {}", path.display(), e)])?);
+  let is_json = matches!(path.extension().and_then(|ext| ext.to_str()), Some(ext) if ext.eq_ignore_ascii_case("json"));
+  let root = match is_json {
+    true => serde_json::from_str::<serde_json::Value>(&content).map_err(|e| e.to_string()).and_then(|json| match json_to_toml(json) {
+      Some(Value::Table(root)) => Ok(root),
+      _ => Err(String::from("the root must be an object")),
+    }),
+    false => toml::from_str::<Table>(&content).map_err(|e| e.to_string()),
+  };
+  parse(root.map_err(|e| vec![format!("Cannot parse {}: {}", path.display(), e)])?)
+}
+
+/// `<prefix>_EXCHANGE`, `<prefix>_CREDENTIALS_API_KEY`, `<prefix>_RISK_LIMITS_MAX_POSITION`, ..., the keys of a file
+/// in upper case joined to their table. Lists are comma separated. Fails with every problem found.
+pub fn read_environment(prefix: &str, vars: impl Iterator<Item = (String, String)>) -> Result<ClientFile, Vec<String>> {
+  let prefix = match prefix.ends_with('_') {
+    true => String::from(prefix),
+    false => format!("{}_", prefix),
+  };
+  let mut root = Table::new();
+  for (name, value) in vars {
+    let key = match name.strip_prefix(prefix.as_str()) {
+      Some(key) if !key.is_empty() => key.to_lowercase(),
+      _ => continue,
+    };
+    match SECTIONS.iter().find(|section| key.starts_with(&format!("{}_", section))) {
+      Some(section) => {
+        if !root.contains_key(*section) {
+          root.insert(String::from(*section), Value::Table(Table::new()));
+        }
+        if let Some(Value::Table(table)) = root.get_mut(*section) {
+          table.insert(String::from(&key[section.len() + 1..]), Value::String(value));
+        }
+      },
+      None => {
+        root.insert(key, Value::String(value));
+      },
+    }
+  }
+  if root.is_empty() {
+    return Err(vec![format!("No environment variable starts with {}", prefix)]);
+  }
+  parse(root)
+}
+
+fn json_to_toml(json: serde_json::Value) -> Option<Value> {
+  match json {
+    serde_json::Value::Null => None,
+    serde_json::Value::Bool(b) => Some(Value::Boolean(b)),
+    serde_json::Value::Number(n) => n.as_i64().map(Value::Integer).or_else(|| n.as_f64().map(Value::Float)),
+    serde_json::Value::String(s) => Some(Value::String(s)),
+    serde_json::Value::Array(values) => Some(Value::Array(values.into_iter().filter_map(json_to_toml).collect())),
+    serde_json::Value::Object(fields) => Some(Value::Table(fields.into_iter()
+      .filter_map(|(key, value)| json_to_toml(value).map(|value| (key, value)))
+      .collect())),
+  }
+}
+
+/// Reads the fields of one table, collecting what is wrong with them rather than stopping at the first.
+struct Fields<'a> {
+  // Empty for the root
+  section: &'static str,
+  table: Table,
+  errors: &'a mut Vec<String>,
+}
+
+impl<'a> Fields<'a> {
+  fn new(section: &'static str, table: Table, errors: &'a mut Vec<String>) -> Self {
+    Fields { section, table, errors }
+  }
+
+  // Never `key: problem`, redaction would take the problem for the value of a key like `api_secret`
+  fn error(&mut self, key: &str, problem: &str) {
+    let path = match self.section {
+      "" => String::from(key),
+      section => format!("{}.{}", section, key),
+    };
+    self.errors.push(format!("{} {}", path, problem));
+  }
+
+  fn require(&mut self, key: &str) {
+    if !self.table.contains_key(key) {
+      self.error(key, "is missing");
+    }
+  }
+
+  /// A table of its own, taken out to read with `Fields::new`.
+  fn table(&mut self, key: &'static str) -> Option<Table> {
+    match self.table.remove(key) {
+      None => None,
+      Some(Value::Table(table)) => Some(table),
+      Some(_) => {
+        self.error(key, "must be a table");
+        None
+      },
+    }
+  }
+
+  fn string(&mut self, key: &str) -> Option<String> {
+    match self.table.remove(key) {
+      None => None,
+      Some(Value::String(s)) => Some(s),
+      Some(_) => {
+        self.error(key, "must be a string");
+        None
+      },
+    }
+  }
+
+  fn secret(&mut self, key: &str) -> Option<Zeroizing<String>> {
+    self.string(key).map(Zeroizing::new)
+  }
+
+  /// One of `allowed`, in any case, spelled like in `allowed`.
+  fn one_of(&mut self, key: &str, allowed: &[&'static str]) -> Option<&'static str> {
+    let value = self.string(key)?;
+    match allowed.iter().find(|name| name.eq_ignore_ascii_case(&value)) {
+      Some(name) => Some(*name),
+      None => {
+        self.error(key, &format!("must be one of {}", allowed.join(", ")));
+        None
+      },
+    }
+  }
+
+  fn boolean(&mut self, key: &str) -> Option<bool> {
+    let parsed = match self.table.remove(key)? {
+      Value::Boolean(b) => Some(b),
+      Value::String(s) if s.eq_ignore_ascii_case("true") => Some(true),
+      Value::String(s) if s.eq_ignore_ascii_case("false") => Some(false),
+      _ => None,
+    };
+    if parsed.is_none() {
+      self.error(key, "must be true or false");
+    }
+    parsed
+  }
+
+  /// A whole number from `min` to `max`.
+  fn whole(&mut self, key: &str, min: u64, max: u64) -> Option<u64> {
+    let parsed = match self.table.remove(key)? {
+      Value::Integer(i) => Some(i),
+      Value::String(s) => s.trim().parse::<i64>().ok(),
+      _ => None,
+    };
+    match parsed {
+      Some(n) if n >= 0 && n as u64 >= min && n as u64 <= max => Some(n as u64),
+      Some(n) => {
+        self.error(key, &format!("must be from {} to {}, got {}", min, max, n));
+        None
+      },
+      None => {
+        self.error(key, "must be a whole number");
+        None
+      },
+    }
+  }
+
+  fn positive_decimal(&mut self, key: &str) -> Option<Decimal> {
+    let parsed = match self.table.remove(key)? {
+      Value::Integer(i) => Some(Decimal::from(i)),
+      Value::Float(f) => Decimal::from_str(&f.to_string()).ok(),
+      Value::String(s) => Decimal::from_str(s.trim()).ok(),
+      _ => None,
+    };
+    match parsed {
+      Some(value) if value > Decimal::default() => Some(value),
+      Some(value) => {
+        self.error(key, &format!("must be positive, got {}", value));
+        None
+      },
+      None => {
+        self.error(key, "must be a decimal number");
+        None
+      },
+    }
+  }
+
+  fn strings(&mut self, key: &str) -> Vec<String> {
+    let parsed = match self.table.remove(key) {
+      None => return Vec::new(),
+      Some(Value::Array(values)) => values.into_iter().map(|value| match value {
+        Value::String(s) => Some(s),
+        _ => None,
+      }).collect(),
+      Some(Value::String(s)) => Some(s.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect()),
+      Some(_) => None,
+    };
+    parsed.unwrap_or_else(|| {
+      self.error(key, "must be a list of strings");
+      Vec::new()
+    })
+  }
+
+  /// Reports whatever was not read.
+  fn finish(mut self) {
+    let keys: Vec<String> = self.table.keys().cloned().collect();
+    for key in keys {
+      self.error(&key, "is not a known field");
+    }
+  }
+}
+
+fn parse(root: Table) -> Result<ClientFile, Vec<String>> {
+  let mut errors = Vec::new();
+  let mut fields = Fields::new("", root, &mut errors);
+  fields.require("exchange");
+  let exchange = fields.one_of("exchange", &["nash", "binance", "coinbase"]);
+  let credentials = fields.table("credentials");
+  let options = fields.table("options");
+  let risk_limits = fields.table("risk_limits");
+  let dead_mans_switch = fields.table("dead_mans_switch");
+  let audit_journal = fields.table("audit_journal");
+  let params = match exchange {
+    Some(exchange) => {
+      let params = match exchange {
+        "nash" => parse_nash(&mut fields, credentials),
+        "binance" => parse_binance(&mut fields, credentials),
+        _ => parse_coinbase(&mut fields, credentials),
+      };
+      fields.finish();
+      params
+    },
+    // The rest of the root and the credentials can't be checked without knowing the exchange
+    None => None,
+  };
+
+  let mut client = ClientOptions::default();
+  if let Some(table) = options {
+    let mut fields = Fields::new("options", table, &mut errors);
+    client.backpressure_policy = fields.one_of("backpressure_policy", &["Block", "DropOldest", "DropNewest"]).map(String::from);
+    client.callback_queue_capacity = fields.whole("callback_queue_capacity", 1, JAVA_INT_MAX);
+    client.conflate_orderbooks = fields.boolean("conflate_orderbooks").unwrap_or_default();
+    client.streaming_mode = fields.one_of("streaming_mode", &["Eager", "Lazy", "Disabled"]).map(String::from);
+    client.direct_orderbooks = fields.boolean("direct_orderbooks").unwrap_or_default();
+    client.fixed_point = fields.boolean("fixed_point").unwrap_or_default();
+    client.account_poll_millis = fields.whole("account_poll_millis", 1, JAVA_LONG_MAX);
+    client.track_orders = fields.boolean("track_orders").unwrap_or_default();
+    client.position_markets = fields.strings("position_markets");
+    fields.finish();
+  }
+  if let Some(table) = risk_limits {
+    let mut fields = Fields::new("risk_limits", table, &mut errors);
+    let limits = RiskLimits {
+      max_order_notional: fields.positive_decimal("max_order_notional"),
+      max_position: fields.positive_decimal("max_position"),
+      max_open_orders: fields.whole("max_open_orders", 1, JAVA_INT_MAX).map(|max| max as usize),
+      price_band: fields.positive_decimal("price_band_percent"),
+    };
+    // Refused like withRiskLimits refuses them, rather than never enforced
+    if limits.max_position.is_some() && client.position_markets.is_empty() {
+      fields.error("max_position", "requires options.position_markets");
+    }
+    if limits.max_open_orders.is_some() && !client.track_orders {
+      fields.error("max_open_orders", "requires options.track_orders");
+    }
+    fields.finish();
+    client.risk_limits = Some(limits);
+  }
+  if let Some(table) = dead_mans_switch {
+    let mut fields = Fields::new("dead_mans_switch", table, &mut errors);
+    fields.require("timeout_millis");
+    let timeout = fields.whole("timeout_millis", 1, JAVA_LONG_MAX);
+    let markets = fields.strings("markets");
+    if markets.is_empty() {
+      fields.error("markets", "needs at least one market to cancel");
+    }
+    fields.finish();
+    client.dead_mans_switch = timeout.map(|timeout| (timeout, markets));
+  }
+  if let Some(table) = audit_journal {
+    let mut fields = Fields::new("audit_journal", table, &mut errors);
+    fields.require("directory");
+    fields.require("max_file_bytes");
+    let directory = fields.string("directory");
+    let max_bytes = fields.whole("max_file_bytes", 1, JAVA_LONG_MAX);
+    fields.finish();
+    client.audit_journal = directory.zip(max_bytes);
+  }
+
+  match params {
+    Some(params) if errors.is_empty() => Ok(ClientFile { params, options: client }),
+    _ => Err(errors),
+  }
+}
+
+/// The credentials of `keys`, in that order. `Err` when some are missing or invalid.
+fn parse_credentials(errors: &mut Vec<String>, credentials: Option<Table>, keys: &[&str]) -> Result<Option<Vec<Zeroizing<String>>>, ()> {
+  let table = match credentials {
+    Some(table) => table,
+    None => return Ok(None),
+  };
+  let mut fields = Fields::new("credentials", table, errors);
+  for key in keys {
+    fields.require(key);
+  }
+  let secrets: Vec<Option<Zeroizing<String>>> = keys.iter().map(|key| fields.secret(key)).collect();
+  fields.finish();
+  secrets.into_iter().collect::<Option<Vec<_>>>().map(Some).ok_or(())
+}
+
+// Credentials stay zeroizing until nothing else can fail
+fn parse_nash(fields: &mut Fields, credentials: Option<Table>) -> Option<SecretParams> {
+  fields.require("environment");
+  let environment = match fields.one_of("environment", &["production", "sandbox"]) {
+    Some("production") => Some(Environment::Production),
+    Some(_) => Some(Environment::Sandbox),
+    None => None,
+  };
+  let client_id = fields.whole("client_id", 0, JAVA_LONG_MAX).unwrap_or(0);
+  let timeout = fields.whole("timeout_millis", 1, JAVA_LONG_MAX).unwrap_or(1000);
+  let affiliate_code = fields.string("affiliate_code");
+  let credentials = parse_credentials(fields.errors, credentials, &["secret", "session"]).ok()?;
+  let environment = environment?;
+  let credentials = credentials.map(|mut secrets| NashCredentials {
+    session: expose(secrets.pop().unwrap_or_default()),
+    secret: expose(secrets.pop().unwrap_or_default()),
+  });
+  Some(SecretParams::new(InitAnyExchange::Nash(NashParameters {
+    credentials,
+    client_id,
+    environment,
+    timeout: std::time::Duration::from_millis(timeout),
+    affiliate_code,
+    sign_states_loop_interval: None,
+  })))
+}
+
+fn parse_binance(fields: &mut Fields, credentials: Option<Table>) -> Option<SecretParams> {
+  let sandbox = fields.boolean("sandbox").unwrap_or_default();
+  let credentials = parse_credentials(fields.errors, credentials, &["api_key", "api_secret"]).ok()?;
+  let credentials = credentials.map(|mut secrets| BinanceCredentials {
+    api_secret: expose(secrets.pop().unwrap_or_default()),
+    api_key: expose(secrets.pop().unwrap_or_default()),
+  });
+  Some(SecretParams::new(InitAnyExchange::Binance(BinanceParameters { credentials, sandbox })))
+}
+
+fn parse_coinbase(fields: &mut Fields, credentials: Option<Table>) -> Option<SecretParams> {
+  let sandbox = fields.boolean("sandbox").unwrap_or_default();
+  let credentials = parse_credentials(fields.errors, credentials, &["api_key", "api_secret", "passphrase"]).ok()?;
+  let credentials = credentials.map(|mut secrets| CoinbaseCredentials {
+    passphrase: expose(secrets.pop().unwrap_or_default()),
+    api_secret: expose(secrets.pop().unwrap_or_default()),
+    api_key: expose(secrets.pop().unwrap_or_default()),
+  });
+  Some(SecretParams::new(InitAnyExchange::Coinbase(CoinbaseParameters { credentials, sandbox })))
+}
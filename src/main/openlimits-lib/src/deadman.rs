@@ -0,0 +1,175 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use openlimits::OpenLimits;
+use openlimits::errors::OpenLimitsError;
+use openlimits::exchange::any::AnyExchange;
+use openlimits::exchange::traits::ExchangeAccount;
+use openlimits::model::{CancelAllOrdersRequest, OrderCanceled};
+use crate::journal::Journal;
+use crate::secrets::SecretParams;
+use crate::{audit_error, cancel_all_action, cancel_all_outcome, OpenlimitsJavaError};
+
+const CANCEL_ATTEMPTS: u32 = 5;
+const FIRST_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+enum Signal {
+  Heartbeat,
+  Disconnected(&'static str),
+  Credentials(SecretParams),
+  Stop,
+}
+
+/// What the websocket thread holds to report a lost connection to the switch.
+#[derive(Clone)]
+pub struct SwitchTrigger {
+  tx: UnboundedSender<Signal>,
+}
+
+impl SwitchTrigger {
+  pub fn disconnected(&self, reason: &'static str) {
+    // Nothing to do once the switch has stopped with its client
+    self.tx.send(Signal::Disconnected(reason)).ok();
+  }
+}
+
+/// Cancels every order of its markets when Java stops calling `heartbeat()` or the websocket is lost. Runs on a
+/// thread of its own with its own REST client, so neither stuck Java callbacks nor a REST call holding the client's
+/// lock can delay it. Stops when dropped with its client.
+pub struct DeadMansSwitch {
+  trigger: SwitchTrigger,
+  // Until started
+  idle: Option<(Switch, Duration, UnboundedReceiver<Signal>)>,
+}
+
+impl DeadMansSwitch {
+  /// A switch that only watches once started. Its trigger already works, what it reports before is handled then.
+  pub fn new(runtime: tokio::runtime::Runtime, client: AnyExchange, markets: Vec<String>, timeout: Duration, journal: Option<Arc<Journal>>) -> Self {
+    let (tx, rx) = unbounded_channel();
+    let switch = Switch { runtime, client, markets, journal };
+    DeadMansSwitch { trigger: SwitchTrigger { tx }, idle: Some((switch, timeout, rx)) }
+  }
+
+  /// Starts watching, the first heartbeat is due one timeout from now. Start it once nothing can fail anymore that
+  /// would leave it without a client to stop it.
+  pub fn start(&mut self) {
+    if let Some((switch, timeout, rx)) = self.idle.take() {
+      std::thread::spawn(move || watch(switch, timeout, rx));
+    }
+  }
+
+  pub fn heartbeat(&self) {
+    self.trigger.tx.send(Signal::Heartbeat).ok();
+  }
+
+  pub fn trigger(&self) -> SwitchTrigger {
+    self.trigger.clone()
+  }
+
+  /// Has the switch cancel with a client of these credentials from now on.
+  pub fn update_credentials(&self, params: SecretParams) {
+    self.trigger.tx.send(Signal::Credentials(params)).ok();
+  }
+}
+
+impl Drop for DeadMansSwitch {
+  fn drop(&mut self) {
+    self.trigger.tx.send(Signal::Stop).ok();
+  }
+}
+
+struct Switch {
+  runtime: tokio::runtime::Runtime,
+  client: AnyExchange,
+  markets: Vec<String>,
+  journal: Option<Arc<Journal>>,
+}
+
+fn watch(mut switch: Switch, timeout: Duration, mut rx: UnboundedReceiver<Signal>) {
+  let mut deadline = Instant::now() + timeout;
+  // Fires once, and is armed again by the next heartbeat
+  let mut armed = true;
+  loop {
+    let signal = match armed {
+      true => switch.runtime.block_on(tokio::time::timeout(deadline.saturating_duration_since(Instant::now()), rx.recv())),
+      false => Ok(switch.runtime.block_on(rx.recv())),
+    };
+    match signal {
+      Ok(Some(Signal::Heartbeat)) => {
+        deadline = Instant::now() + timeout;
+        if !armed {
+          log::info!("Dead man's switch armed again");
+          armed = true;
+        }
+      },
+      Ok(Some(Signal::Disconnected(reason))) => {
+        if armed {
+          armed = false;
+          switch.cancel_all(reason);
+        }
+      },
+      Ok(Some(Signal::Credentials(params))) => switch.update_client(params),
+      Ok(Some(Signal::Stop)) | Ok(None) => return,
+      Err(_) => {
+        armed = false;
+        switch.cancel_all("missed heartbeat");
+      },
+    }
+  }
+}
+
+impl Switch {
+  fn update_client(&mut self, params: SecretParams) {
+    match self.runtime.block_on(OpenLimits::instantiate(params.instantiate_params())) {
+      Ok(client) => self.client = client,
+      Err(err) => log::error!("Dead man's switch keeps its old credentials, failed to create a client with the new ones: {:?}", err),
+    }
+  }
+
+  fn cancel_all(&self, reason: &'static str) {
+    // Logging may call into a stuck JVM, so nothing is logged before the orders are canceled
+    let results: Vec<(&String, Result<Vec<OrderCanceled>, OpenlimitsJavaError>)> = self.markets.iter()
+      .map(|market| (market, self.cancel_market(market, reason)))
+      .collect();
+    log::warn!("Dead man's switch tripped by {}", reason);
+    for (market, result) in results {
+      match result {
+        Ok(canceled) => log::warn!("Dead man's switch canceled {} orders of {}", canceled.len(), market),
+        Err(err) => log::error!("Dead man's switch failed to cancel the orders of {}: {:?}", market, err),
+      }
+    }
+  }
+
+  fn cancel_market(&self, market: &str, reason: &'static str) -> Result<Vec<OrderCanceled>, OpenlimitsJavaError> {
+    let journal = self.journal.as_ref();
+    let pending = journal.map(|journal| journal.request(cancel_all_action(Some(String::from(market)), format!("dead man's switch: {}", reason))));
+    let result = self.runtime.block_on(cancel_with_retries(&self.client, market)).map_err(OpenlimitsJavaError::from);
+    if let (Some(journal), Some(pending)) = (journal, pending) {
+      match &result {
+        Ok(canceled) => {
+          let (order_id, detail) = cancel_all_outcome(canceled);
+          journal.result(pending, order_id, detail);
+        },
+        Err(err) => journal.error(pending, audit_error(err)),
+      }
+    }
+    result
+  }
+}
+
+async fn cancel_with_retries(client: &AnyExchange, market: &str) -> Result<Vec<OrderCanceled>, OpenLimitsError> {
+  let req = CancelAllOrdersRequest { market_pair: Some(String::from(market)) };
+  let mut delay = FIRST_RETRY_DELAY;
+  let mut attempt = 1;
+  loop {
+    match client.cancel_all_orders(&req).await {
+      Ok(canceled) => return Ok(canceled),
+      Err(err) if attempt == CANCEL_ATTEMPTS => return Err(err),
+      Err(_) => {
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+        attempt += 1;
+      },
+    }
+  }
+}
@@ -0,0 +1,101 @@
+use jni::JNIEnv;
+use jni::errors;
+use jni::objects::{GlobalRef, JObject};
+use once_cell::sync::OnceCell;
+
+static SIDE_CLS_NAME: &str = "io/nash/openlimits/Side";
+static ORDER_TYPE_CLS_NAME: &str = "io/nash/openlimits/OrderType";
+static ORDER_STATUS_CLS_NAME: &str = "io/nash/openlimits/OrderStatus";
+static LIQUIDITY_CLS_NAME: &str = "io/nash/openlimits/Liquidity";
+static INTERVAL_CLS_NAME: &str = "io/nash/openlimits/Interval";
+static TIME_IN_FORCE_CLS_NAME: &str = "io/nash/openlimits/TimeInForce";
+static BACKPRESSURE_POLICY_CLS_NAME: &str = "io/nash/openlimits/BackpressurePolicy";
+static STREAMING_MODE_CLS_NAME: &str = "io/nash/openlimits/StreamingMode";
+static FAULT_TYPE_CLS_NAME: &str = "io/nash/openlimits/FaultType";
+
+static SIDE_NAMES: &[&str] = &["Buy", "Sell"];
+static ORDER_TYPE_NAMES: &[&str] = &["Limit", "Market", "StopLimit", "StopMarket", "Unknown"];
+static ORDER_STATUS_NAMES: &[&str] = &[
+  "New", "PartiallyFilled", "Filled", "Canceled", "PendingCancel", "Rejected", "Expired", "Open", "Pending", "Active"
+];
+static LIQUIDITY_NAMES: &[&str] = &["Maker", "Taker"];
+static INTERVAL_NAMES: &[&str] = &[
+  "OneMinute", "ThreeMinutes", "FiveMinutes", "FifteenMinutes", "ThirtyMinutes", "OneHour", "TwoHours", "FourHours",
+  "SixHours", "EightHours", "TwelveHours", "OneDay", "ThreeDays", "OneWeek", "OneMonth"
+];
+static TIME_IN_FORCE_NAMES: &[&str] = &["GTC", "GTT", "IOC", "FOK"];
+static BACKPRESSURE_POLICY_NAMES: &[&str] = &["Block", "DropOldest", "DropNewest"];
+static STREAMING_MODE_NAMES: &[&str] = &["Eager", "Lazy", "Disabled"];
+static FAULT_TYPE_NAMES: &[&str] = &["Error", "Latency", "DropMessage", "DuplicateMessage", "Disconnect"];
+
+static ENUMS: OnceCell<JavaEnums> = OnceCell::new();
+
+/// The constants of a Java enum, pinned as global refs so conversions never go through `valueOf` or `name()`.
+pub struct JavaEnum {
+  class_name: &'static str,
+  constants: Vec<(&'static str, GlobalRef)>,
+}
+
+impl JavaEnum {
+  fn load(env: &JNIEnv, class_name: &'static str, names: &[&'static str]) -> errors::Result<Self> {
+    let cls = env.find_class(class_name)?;
+    let sig = format!("L{};", class_name);
+    let constants = names.iter().map(|name| {
+      let field_id = env.get_static_field_id(cls, *name, sig.as_str())?;
+      let constant = env.get_static_field_unchecked(cls, field_id, jni::signature::JavaType::Object(sig.clone()))?.l()?;
+      Ok((*name, env.new_global_ref(constant)?))
+    }).collect::<errors::Result<Vec<_>>>()?;
+
+    Ok(JavaEnum { class_name, constants })
+  }
+
+  pub fn to_jobject<'a>(&self, env: &JNIEnv<'a>, name: &str) -> errors::Result<JObject<'a>> {
+    match self.constants.iter().find(|(n, _)| *n == name) {
+      Some((_, constant)) => env.new_local_ref::<JObject>(JObject::from(constant.as_obj().into_inner())),
+      None => Err(errors::Error::FieldNotFound { name: String::from(name), sig: format!("L{};", self.class_name) })
+    }
+  }
+
+  /// Returns the constant name of `obj`, or `None` if it is null or not one of this enum's constants.
+  pub fn name_of(&self, env: &JNIEnv, obj: JObject) -> errors::Result<Option<&'static str>> {
+    if obj.is_null() {
+      return Ok(None);
+    }
+    for (name, constant) in &self.constants {
+      if env.is_same_object(obj, constant.as_obj())? {
+        return Ok(Some(name));
+      }
+    }
+    Ok(None)
+  }
+}
+
+pub struct JavaEnums {
+  pub side: JavaEnum,
+  pub order_type: JavaEnum,
+  pub order_status: JavaEnum,
+  pub liquidity: JavaEnum,
+  pub interval: JavaEnum,
+  pub time_in_force: JavaEnum,
+  pub backpressure_policy: JavaEnum,
+  pub streaming_mode: JavaEnum,
+  pub fault_type: JavaEnum,
+}
+
+/// Resolves every enum constant on first use. This has to happen on a thread that can see the application
+/// class loader, which every JNI entry point can.
+pub fn java_enums(env: &JNIEnv) -> errors::Result<&'static JavaEnums> {
+  ENUMS.get_or_try_init(|| {
+    Ok(JavaEnums {
+      side: JavaEnum::load(env, SIDE_CLS_NAME, SIDE_NAMES)?,
+      order_type: JavaEnum::load(env, ORDER_TYPE_CLS_NAME, ORDER_TYPE_NAMES)?,
+      order_status: JavaEnum::load(env, ORDER_STATUS_CLS_NAME, ORDER_STATUS_NAMES)?,
+      liquidity: JavaEnum::load(env, LIQUIDITY_CLS_NAME, LIQUIDITY_NAMES)?,
+      interval: JavaEnum::load(env, INTERVAL_CLS_NAME, INTERVAL_NAMES)?,
+      time_in_force: JavaEnum::load(env, TIME_IN_FORCE_CLS_NAME, TIME_IN_FORCE_NAMES)?,
+      backpressure_policy: JavaEnum::load(env, BACKPRESSURE_POLICY_CLS_NAME, BACKPRESSURE_POLICY_NAMES)?,
+      streaming_mode: JavaEnum::load(env, STREAMING_MODE_CLS_NAME, STREAMING_MODE_NAMES)?,
+      fault_type: JavaEnum::load(env, FAULT_TYPE_CLS_NAME, FAULT_TYPE_NAMES)?,
+    })
+  })
+}
@@ -0,0 +1,193 @@
+use std::sync::Mutex;
+use std::sync::mpsc::{channel, Sender};
+use std::time::{Duration, Instant};
+
+/// An exception a rule may raise, with the details a real error of that class would carry.
+#[derive(Debug)]
+pub struct InjectableError {
+  pub class_name: &'static str,
+  pub http_status: Option<u16>,
+  pub retryable: bool,
+}
+
+const fn injectable(class_name: &'static str, http_status: Option<u16>, retryable: bool) -> InjectableError {
+  InjectableError { class_name, http_status, retryable }
+}
+
+// Every class an OpenLimitsError is raised as
+static INJECTABLE_ERRORS: &[InjectableError] = &[
+  injectable("io/nash/openlimits/BinanceError", None, false),
+  injectable("io/nash/openlimits/CoinbaseError", None, false),
+  injectable("io/nash/openlimits/NashProtocolError", None, false),
+  injectable("io/nash/openlimits/MissingImplementation", None, false),
+  injectable("io/nash/openlimits/AssetNotFound", None, false),
+  injectable("io/nash/openlimits/NoApiKeySet", None, false),
+  injectable("io/nash/openlimits/InternalServerError", Some(500), true),
+  injectable("io/nash/openlimits/ServiceUnavailable", Some(503), true),
+  injectable("io/nash/openlimits/Unauthorized", Some(401), false),
+  injectable("io/nash/openlimits/SymbolNotFound", None, false),
+  injectable("io/nash/openlimits/SocketError", None, true),
+  injectable("io/nash/openlimits/GetTimestampFailed", None, true),
+  injectable("io/nash/openlimits/ReqError", None, true),
+  injectable("io/nash/openlimits/InvalidHeaderError", None, false),
+  injectable("io/nash/openlimits/InvalidPayloadSignature", None, false),
+  injectable("io/nash/openlimits/IoError", None, true),
+  injectable("io/nash/openlimits/PoisonError", None, false),
+  injectable("io/nash/openlimits/JsonError", None, false),
+  injectable("io/nash/openlimits/ParseFloatError", None, false),
+  injectable("io/nash/openlimits/UrlParserError", None, false),
+  injectable("io/nash/openlimits/Tungstenite", None, true),
+  injectable("io/nash/openlimits/TimestampError", None, false),
+  injectable("io/nash/openlimits/UnkownResponse", None, false),
+  injectable("io/nash/openlimits/NotParsableResponse", None, false),
+  injectable("io/nash/openlimits/MissingParameter", None, false),
+  injectable("io/nash/openlimits/WebSocketMessageNotSupported", None, false),
+  injectable("io/nash/openlimits/NoMarketPair", None, false),
+  injectable("io/nash/openlimits/InvalidParameter", None, false),
+];
+
+impl InjectableError {
+  /// The simple name of the class, e.g. `ServiceUnavailable`.
+  pub fn name(&self) -> &'static str {
+    self.class_name.rsplit('/').next().unwrap_or(self.class_name)
+  }
+}
+
+/// Looks up an exception class given by its Java name, e.g. `io.nash.openlimits.SocketError`.
+pub fn injectable_error(java_name: &str) -> Option<&'static InjectableError> {
+  let class_name = java_name.replace('.', "/");
+  INJECTABLE_ERRORS.iter().find(|error| error.class_name == class_name)
+}
+
+// The calls that send an order, whose rules fire after the risk check
+pub static ORDER_OPERATIONS: &[&str] = &["limitBuy", "limitSell", "marketBuy", "marketSell"];
+
+#[derive(Clone, Debug)]
+pub enum FaultAction {
+  Error(&'static InjectableError),
+  Latency(Duration),
+  // The remaining actions only apply to websocket messages
+  DropMessage,
+  DuplicateMessage,
+  Disconnect,
+}
+
+#[derive(Clone, Debug)]
+pub struct FaultRule {
+  // None matches every operation
+  pub operation: Option<String>,
+  // None matches every market, including calls without one
+  pub market: Option<String>,
+  pub action: FaultAction,
+  // None fires forever
+  pub remaining: Option<u64>,
+}
+
+impl FaultRule {
+  fn matches(&self, operation: &str, market: Option<&str>, stream: bool) -> bool {
+    let applies = match self.action {
+      FaultAction::Error(_) | FaultAction::Latency(_) => true,
+      _ => stream,
+    };
+    applies && filter_matches(&self.operation, Some(operation)) && filter_matches(&self.market, market)
+  }
+}
+
+fn filter_matches(filter: &Option<String>, value: Option<&str>) -> bool {
+  match filter {
+    Some(filter) => Some(filter.as_str()) == value,
+    None => true,
+  }
+}
+
+/// What the matching rules asked for. Latencies add up, the first error wins.
+#[derive(Default)]
+pub struct Faults {
+  pub latency: Duration,
+  pub error: Option<&'static InjectableError>,
+  pub drop_message: bool,
+  pub duplicate_message: bool,
+  pub disconnect: bool,
+}
+
+/// Test only fault rules of a client, consulted by every JNI call and websocket message.
+#[derive(Default)]
+pub struct FaultInjector {
+  rules: Mutex<Vec<FaultRule>>,
+}
+
+impl FaultInjector {
+  pub fn add(&self, rule: FaultRule) {
+    if let Ok(mut rules) = self.rules.lock() {
+      rules.push(rule);
+    }
+  }
+
+  pub fn clear(&self) {
+    if let Ok(mut rules) = self.rules.lock() {
+      rules.clear();
+    }
+  }
+
+  /// Cheap check so the market of a call is only looked up when rules exist.
+  pub fn is_empty(&self) -> bool {
+    self.rules.lock().map(|rules| rules.is_empty()).unwrap_or(true)
+  }
+
+  /// Fires every rule matching the call, using up one of their remaining counts.
+  pub fn fire(&self, operation: &str, market: Option<&str>, stream: bool) -> Faults {
+    let mut faults = Faults::default();
+    let mut rules = match self.rules.lock() {
+      Ok(rules) => rules,
+      Err(_) => return faults,
+    };
+    for rule in rules.iter_mut().filter(|rule| rule.matches(operation, market, stream)) {
+      match rule.action {
+        FaultAction::Error(error) => {
+          faults.error = faults.error.or(Some(error));
+        },
+        FaultAction::Latency(latency) => faults.latency += latency,
+        FaultAction::DropMessage => faults.drop_message = true,
+        FaultAction::DuplicateMessage => faults.duplicate_message = true,
+        FaultAction::Disconnect => faults.disconnect = true,
+      }
+      if let Some(remaining) = rule.remaining.as_mut() {
+        *remaining = remaining.saturating_sub(1);
+      }
+    }
+    rules.retain(|rule| rule.remaining != Some(0));
+    faults
+  }
+}
+
+/// Holds back the stream messages a latency rule delayed on a thread of its own, so the websocket thread keeps
+/// delivering the messages of other markets meanwhile. Messages leave in the order they were held.
+pub struct DelayLine<T> {
+  tx: Mutex<Sender<(Instant, T)>>,
+}
+
+impl<T: Send + 'static> DelayLine<T> {
+  /// `deliver` returns false once nobody takes messages anymore. The thread ends with the line.
+  pub fn new<F>(mut deliver: F) -> Self
+    where F: FnMut(T) -> bool + Send + 'static {
+    let (tx, rx) = channel::<(Instant, T)>();
+    std::thread::spawn(move || {
+      for (due, msg) in rx {
+        let now = Instant::now();
+        if due > now {
+          std::thread::sleep(due - now);
+        }
+        if !deliver(msg) {
+          return;
+        }
+      }
+    });
+    DelayLine { tx: Mutex::new(tx) }
+  }
+
+  pub fn hold(&self, latency: Duration, msg: T) {
+    if let Ok(tx) = self.tx.lock() {
+      tx.send((Instant::now() + latency, msg)).ok();
+    }
+  }
+}
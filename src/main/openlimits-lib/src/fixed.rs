@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use rust_decimal::Decimal;
+use openlimits::exchange::traits::info::MarketPair;
+use openlimits::model::{AskBid, Candle, Order, Trade};
+
+/// The unscaled value of `d` at `scale` decimal places, i.e. `d * 10^scale`. Errors unless that is exact and fits
+/// in a long.
+pub fn to_fixed(d: Decimal, scale: u32) -> Result<i64, String> {
+  // Trailing zeros don't make a value inexact
+  let d = if d.scale() > scale { d.normalize() } else { d };
+  if d.scale() > scale {
+    return Err(format!("{} is not exact at scale {}", d, scale));
+  }
+  10i128.checked_pow(scale - d.scale())
+    .and_then(|factor| d.mantissa().checked_mul(factor))
+    .and_then(|unscaled| i64::try_from(unscaled).ok())
+    .ok_or_else(|| format!("{} does not fit in a long at scale {}", d, scale))
+}
+
+/// Decimal places of the prices and sizes of a market.
+#[derive(Clone, Copy, Debug)]
+pub struct MarketScale {
+  pub price: u32,
+  pub size: u32,
+}
+
+impl MarketScale {
+  pub fn ask_bid(&self, level: &AskBid) -> Result<FixedAskBid, String> {
+    Ok(FixedAskBid { price: to_fixed(level.price, self.price)?, qty: to_fixed(level.qty, self.size)?, scale: *self })
+  }
+
+  pub fn trade(&self, trade: Trade) -> Result<FixedTrade, String> {
+    let price = to_fixed(trade.price, self.price)?;
+    let qty = to_fixed(trade.qty, self.size)?;
+    Ok(FixedTrade { trade, price, qty, scale: *self })
+  }
+
+  pub fn order(&self, mut order: Order) -> Result<FixedOrder, String> {
+    let trades = std::mem::take(&mut order.trades).into_iter().map(|trade| self.trade(trade)).collect::<Result<_, _>>()?;
+    let size = to_fixed(order.size, self.size)?;
+    let price = order.price.map(|price| to_fixed(price, self.price)).transpose()?;
+    Ok(FixedOrder { order, size, price: price.unwrap_or_default(), trades, scale: *self })
+  }
+
+  pub fn candle(&self, candle: Candle) -> Result<FixedCandle, String> {
+    let prices = [candle.low, candle.high, candle.open, candle.close];
+    let mut fixed = [0; 4];
+    for (fixed, price) in fixed.iter_mut().zip(prices.iter()) {
+      *fixed = to_fixed(*price, self.price)?;
+    }
+    let volume = to_fixed(candle.volume, self.size)?;
+    let [low, high, open, close] = fixed;
+    Ok(FixedCandle { time: candle.time, low, high, open, close, volume, scale: *self })
+  }
+}
+
+/// The scale of every market of an exchange, from the increments of its market pairs.
+pub struct MarketScales {
+  // Keyed by lowercase symbol, exchanges aren't consistent about the case of market names
+  scales: HashMap<String, MarketScale>,
+}
+
+impl MarketScales {
+  pub fn new(pairs: &[MarketPair]) -> Self {
+    let scales = pairs.iter().map(|pair| {
+      let scale = MarketScale {
+        price: pair.quote_increment.normalize().scale(),
+        size: pair.base_increment.normalize().scale(),
+      };
+      (pair.symbol.to_lowercase(), scale)
+    }).collect();
+    MarketScales { scales }
+  }
+
+  pub fn get(&self, market: &str) -> Result<MarketScale, String> {
+    self.scales.get(&market.to_lowercase()).copied()
+      .ok_or_else(|| format!("No market pair {} to take the scale from", market))
+  }
+
+  pub fn trades(&self, trades: Vec<Trade>) -> Result<Vec<FixedTrade>, String> {
+    trades.into_iter().map(|trade| self.get(&trade.market_pair)?.trade(trade)).collect()
+  }
+
+  pub fn orders(&self, orders: Vec<Order>) -> Result<Vec<FixedOrder>, String> {
+    orders.into_iter().map(|order| self.get(&order.market_pair)?.order(order)).collect()
+  }
+}
+
+pub struct FixedAskBid {
+  pub price: i64,
+  pub qty: i64,
+  pub scale: MarketScale,
+}
+
+pub struct FixedTrade {
+  // price and qty are only read through the fixed-point fields
+  pub trade: Trade,
+  pub price: i64,
+  pub qty: i64,
+  pub scale: MarketScale,
+}
+
+pub struct FixedOrder {
+  // size, price and trades are only read through the fixed-point fields
+  pub order: Order,
+  pub size: i64,
+  // Zero for orders without a price
+  pub price: i64,
+  pub trades: Vec<FixedTrade>,
+  pub scale: MarketScale,
+}
+
+pub struct FixedCandle {
+  pub time: u64,
+  pub low: i64,
+  pub high: i64,
+  pub open: i64,
+  pub close: i64,
+  pub volume: i64,
+  pub scale: MarketScale,
+}
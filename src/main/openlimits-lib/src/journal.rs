@@ -0,0 +1,381 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RecordKind {
+  Request,
+  Result,
+  Error,
+}
+
+impl RecordKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      RecordKind::Request => "request",
+      RecordKind::Result => "result",
+      RecordKind::Error => "error",
+    }
+  }
+
+  fn parse(kind: &str) -> Option<Self> {
+    match kind {
+      "request" => Some(RecordKind::Request),
+      "result" => Some(RecordKind::Result),
+      "error" => Some(RecordKind::Error),
+      _ => None,
+    }
+  }
+}
+
+/// One line of the journal. Only fields picked here are written, never whole requests or errors, so credentials and
+/// signed URLs cannot end up in it.
+pub struct JournalRecord {
+  pub timestamp: DateTime<Utc>,
+  pub kind: RecordKind,
+  pub operation: String,
+  pub market: Option<String>,
+  pub client_order_id: Option<String>,
+  pub order_id: Option<String>,
+  // From the request to its result or error
+  pub latency: Option<Duration>,
+  pub detail: String,
+}
+
+/// An order action about to be sent.
+pub struct JournalAction {
+  pub operation: &'static str,
+  pub market: Option<String>,
+  pub client_order_id: Option<String>,
+  pub order_id: Option<String>,
+  pub detail: String,
+}
+
+/// A journaled request waiting for its outcome.
+pub struct PendingAction {
+  action: JournalAction,
+  started: Instant,
+}
+
+struct Segment {
+  file: File,
+  date: NaiveDate,
+  seq: u32,
+  size: u64,
+}
+
+/// Appends order actions to `audit-<yyyymmdd>-<n>.log` files of a directory, tab separated, one record per line.
+/// A new file is started every UTC day and whenever the current one would grow beyond `max_bytes`.
+pub struct Journal {
+  dir: PathBuf,
+  max_bytes: u64,
+  segment: Mutex<Segment>,
+}
+
+impl Journal {
+  /// Continues the latest file of today, if there is one.
+  pub fn open(dir: &Path, max_bytes: u64) -> io::Result<Self> {
+    fs::create_dir_all(dir)?;
+    let segment = latest_segment(dir, Utc::now().naive_utc().date())?;
+    Ok(Journal { dir: dir.to_path_buf(), max_bytes, segment: Mutex::new(segment) })
+  }
+
+  pub fn request(&self, action: JournalAction) -> PendingAction {
+    self.append(JournalRecord {
+      timestamp: Utc::now(),
+      kind: RecordKind::Request,
+      operation: String::from(action.operation),
+      market: action.market.clone(),
+      client_order_id: action.client_order_id.clone(),
+      order_id: action.order_id.clone(),
+      latency: None,
+      detail: action.detail.clone(),
+    });
+    PendingAction { action, started: Instant::now() }
+  }
+
+  /// `order_id` is the id the exchange gave the order, when the request didn't name one.
+  pub fn result(&self, pending: PendingAction, order_id: Option<String>, detail: String) {
+    self.finish(pending, RecordKind::Result, order_id, detail);
+  }
+
+  pub fn error(&self, pending: PendingAction, detail: String) {
+    self.finish(pending, RecordKind::Error, None, detail);
+  }
+
+  fn finish(&self, pending: PendingAction, kind: RecordKind, order_id: Option<String>, detail: String) {
+    let PendingAction { action, started } = pending;
+    self.append(JournalRecord {
+      timestamp: Utc::now(),
+      kind,
+      operation: String::from(action.operation),
+      market: action.market,
+      client_order_id: action.client_order_id,
+      order_id: order_id.or(action.order_id),
+      latency: Some(started.elapsed()),
+      detail,
+    });
+  }
+
+  // Failing to journal must not fail the order that was already sent
+  fn append(&self, mut record: JournalRecord) {
+    if let Err(err) = self.write(&mut record) {
+      log::error!("Failed to write {} record of {} to the audit journal: {}", record.kind.as_str(), record.operation, err);
+    }
+  }
+
+  fn write(&self, record: &mut JournalRecord) -> io::Result<()> {
+    let mut segment = self.segment.lock().unwrap_or_else(|e| e.into_inner());
+    // Stamped under the lock, so records reach the files in time order and never land in a day already rolled over
+    record.timestamp = Utc::now();
+    let line = format_record(record);
+    let date = record.timestamp.naive_utc().date();
+    if date != segment.date {
+      *segment = latest_segment(&self.dir, date)?;
+    }
+    if segment.size > 0 && segment.size + line.len() as u64 > self.max_bytes {
+      let seq = segment.seq + 1;
+      *segment = open_segment(&self.dir, date, seq)?;
+    }
+    // One write per record, so concurrent readers never see half of one
+    segment.file.write_all(line.as_bytes())?;
+    segment.size += line.len() as u64;
+    Ok(())
+  }
+}
+
+fn segment_name(date: NaiveDate, seq: u32) -> String {
+  format!("audit-{}-{}.log", date.format("%Y%m%d"), seq)
+}
+
+/// The file with the highest sequence of a day, or its first one.
+fn latest_segment(dir: &Path, date: NaiveDate) -> io::Result<Segment> {
+  let seq = segments(dir)?.into_iter()
+    .filter(|(day, _, _)| *day == date)
+    .map(|(_, seq, _)| seq)
+    .max()
+    .unwrap_or(0);
+  open_segment(dir, date, seq)
+}
+
+fn open_segment(dir: &Path, date: NaiveDate, seq: u32) -> io::Result<Segment> {
+  let file = OpenOptions::new().create(true).append(true).open(dir.join(segment_name(date, seq)))?;
+  let size = file.metadata()?.len();
+  Ok(Segment { file, date, seq, size })
+}
+
+/// The journal files of a directory, oldest first.
+fn segments(dir: &Path) -> io::Result<Vec<(NaiveDate, u32, PathBuf)>> {
+  let mut segments = Vec::new();
+  for entry in fs::read_dir(dir)? {
+    let path = entry?.path();
+    let name = match path.file_name().and_then(|name| name.to_str()) {
+      Some(name) => name,
+      None => continue,
+    };
+    let parts: Vec<&str> = match name.strip_prefix("audit-").and_then(|name| name.strip_suffix(".log")) {
+      Some(stem) => stem.split('-').collect(),
+      None => continue,
+    };
+    if let [date, seq] = parts[..] {
+      if let (Ok(date), Ok(seq)) = (NaiveDate::parse_from_str(date, "%Y%m%d"), seq.parse::<u32>()) {
+        segments.push((date, seq, path));
+      }
+    }
+  }
+  segments.sort_by_key(|(date, seq, _)| (*date, *seq));
+  Ok(segments)
+}
+
+/// Every record of a directory between `from` and `to`, inclusive, oldest first. Lines that can't be parsed, such
+/// as one cut short by a crash, are skipped.
+pub fn read(dir: &Path, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> io::Result<Vec<JournalRecord>> {
+  let mut records = Vec::new();
+  for (date, _, path) in segments(dir)? {
+    // Files only hold records of their own day
+    if matches!(from, Some(from) if date < from.naive_utc().date()) || matches!(to, Some(to) if date > to.naive_utc().date()) {
+      continue;
+    }
+    let content = fs::read_to_string(&path)?;
+    for (n, line) in content.lines().enumerate() {
+      let record = match parse_record(line) {
+        Some(record) => record,
+        None => {
+          log::warn!("Skipping unreadable line {} of {}", n + 1, path.display());
+          continue;
+        }
+      };
+      if matches!(from, Some(from) if record.timestamp < from) || matches!(to, Some(to) if record.timestamp > to) {
+        continue;
+      }
+      records.push(record);
+    }
+  }
+  Ok(records)
+}
+
+fn escape(field: &str) -> String {
+  field.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+fn unescape(field: &str) -> String {
+  let mut out = String::with_capacity(field.len());
+  let mut chars = field.chars();
+  while let Some(c) = chars.next() {
+    if c != '\\' {
+      out.push(c);
+      continue;
+    }
+    match chars.next() {
+      Some('t') => out.push('\t'),
+      Some('n') => out.push('\n'),
+      Some('r') => out.push('\r'),
+      Some(other) => out.push(other),
+      None => {},
+    }
+  }
+  out
+}
+
+fn format_record(record: &JournalRecord) -> String {
+  let optional = |field: &Option<String>| field.as_deref().map(escape).unwrap_or_default();
+  format!(
+    "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+    record.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+    record.kind.as_str(),
+    escape(&record.operation),
+    optional(&record.market),
+    optional(&record.client_order_id),
+    optional(&record.order_id),
+    record.latency.map(|latency| latency.as_micros().to_string()).unwrap_or_default(),
+    escape(&record.detail),
+  )
+}
+
+fn parse_record(line: &str) -> Option<JournalRecord> {
+  let fields: Vec<&str> = line.split('\t').collect();
+  if fields.len() != 8 {
+    return None;
+  }
+  let optional = |field: &str| if field.is_empty() { None } else { Some(unescape(field)) };
+  Some(JournalRecord {
+    timestamp: DateTime::parse_from_rfc3339(fields[0]).ok()?.with_timezone(&Utc),
+    kind: RecordKind::parse(fields[1])?,
+    operation: unescape(fields[2]),
+    market: optional(fields[3]),
+    client_order_id: optional(fields[4]),
+    order_id: optional(fields[5]),
+    latency: match fields[6] {
+      "" => None,
+      micros => Some(Duration::from_micros(micros.parse().ok()?)),
+    },
+    detail: unescape(fields[7]),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // An empty directory of its own per test
+  fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("openlimits-journal-{}-{}", name, std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  fn action(detail: &str) -> JournalAction {
+    JournalAction {
+      operation: "limitBuy",
+      market: Some(String::from("BNBBTC")),
+      client_order_id: None,
+      order_id: None,
+      detail: String::from(detail),
+    }
+  }
+
+  fn names(dir: &Path) -> Vec<String> {
+    segments(dir).unwrap().into_iter().map(|(date, seq, _)| segment_name(date, seq)).collect()
+  }
+
+  fn today() -> NaiveDate {
+    Utc::now().naive_utc().date()
+  }
+
+  #[test]
+  fn rotates_by_size() {
+    let dir = temp_dir("size");
+    let journal = Journal::open(&dir, 100).unwrap();
+    for n in 0..3 {
+      journal.request(action(&format!("order {}", n)));
+    }
+    // Each record fills more than half a file
+    assert_eq!(names(&dir), vec![segment_name(today(), 0), segment_name(today(), 1), segment_name(today(), 2)]);
+    let details: Vec<String> = read(&dir, None, None).unwrap().into_iter().map(|record| record.detail).collect();
+    assert_eq!(details, vec!["order 0", "order 1", "order 2"]);
+    fs::remove_dir_all(&dir).ok();
+  }
+
+  #[test]
+  fn open_resumes_the_latest_segment() {
+    let dir = temp_dir("resume");
+    fs::write(dir.join(segment_name(today(), 0)), "").unwrap();
+    fs::write(dir.join(segment_name(today(), 3)), "").unwrap();
+    Journal::open(&dir, 1 << 20).unwrap().request(action("resumed"));
+    assert_eq!(fs::read_to_string(dir.join(segment_name(today(), 0))).unwrap(), "");
+    assert!(fs::read_to_string(dir.join(segment_name(today(), 3))).unwrap().contains("resumed"));
+    fs::remove_dir_all(&dir).ok();
+  }
+
+  #[test]
+  fn a_new_day_resumes_its_latest_segment() {
+    let dir = temp_dir("rollover");
+    let yesterday = today().pred_opt().unwrap();
+    fs::write(dir.join(segment_name(today(), 2)), "").unwrap();
+    // As if opened before midnight
+    let journal = Journal { dir: dir.clone(), max_bytes: 1 << 20, segment: Mutex::new(open_segment(&dir, yesterday, 0).unwrap()) };
+    journal.request(action("after midnight"));
+    assert_eq!(fs::read_to_string(dir.join(segment_name(yesterday, 0))).unwrap(), "");
+    assert!(fs::read_to_string(dir.join(segment_name(today(), 2))).unwrap().contains("after midnight"));
+    assert_eq!(names(&dir).len(), 2);
+    fs::remove_dir_all(&dir).ok();
+  }
+
+  #[test]
+  fn fields_round_trip() {
+    let record = JournalRecord {
+      timestamp: Utc::now(),
+      kind: RecordKind::Error,
+      operation: String::from("cancelOrder"),
+      market: Some(String::from("BNB\tBTC")),
+      client_order_id: None,
+      order_id: Some(String::from("back\\slash")),
+      latency: Some(Duration::from_micros(1500)),
+      detail: String::from("first line\nsecond\tline\r\\n"),
+    };
+    let line = format_record(&record);
+    // One record per line, whatever the fields hold
+    assert_eq!(line.matches('\n').count(), 1);
+    assert_eq!(line.matches('\t').count(), 7);
+    let parsed = parse_record(line.trim_end_matches('\n')).unwrap();
+    assert!(parsed.kind == RecordKind::Error);
+    assert_eq!(parsed.operation, record.operation);
+    assert_eq!(parsed.market, record.market);
+    assert_eq!(parsed.client_order_id, None);
+    assert_eq!(parsed.order_id, record.order_id);
+    assert_eq!(parsed.latency, record.latency);
+    assert_eq!(parsed.detail, record.detail);
+    assert_eq!(parsed.timestamp.timestamp_micros(), record.timestamp.timestamp_micros());
+  }
+
+  #[test]
+  fn unreadable_lines_are_skipped() {
+    assert!(parse_record("2021-01-01T00:00:00.000000Z\trequest\tlimitBuy").is_none());
+    assert!(parse_record("yesterday\trequest\tlimitBuy\t\t\t\t\t").is_none());
+    assert!(parse_record("2021-01-01T00:00:00.000000Z\tunknown\tlimitBuy\t\t\t\t\t").is_none());
+  }
+}
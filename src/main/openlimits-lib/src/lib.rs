@@ -1,10 +1,9 @@
 use jni;
-use jni::{errors, JNIEnv};
-use jni::objects::{JClass, JMethodID, JValue, JObject, JString};
-use jni::sys::{jsize, jobject};
+use jni::{errors, JNIEnv, JavaVM};
+use jni::objects::{GlobalRef, JByteBuffer, JClass, JMethodID, JValue, JObject, JString, JThrowable};
+use jni::sys::{jboolean, jint, jlong, jmethodID, jsize, jobject};
 use rust_decimal::{Decimal};
-use rust_decimal::prelude::*;
-use chrono::Duration;
+use chrono::{Duration, TimeZone, Utc};
 use openlimits::prelude::*;
 use openlimits::{
   OpenLimits,
@@ -59,8 +58,48 @@ use openlimits::{
       websocket::{Subscription, OpenLimitsWebSocketMessage, WebSocketResponse}
   }
 };
-use std::sync::MutexGuard;
+use std::sync::{Arc, MutexGuard};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::path::Path;
+use std::time::Instant;
 use thiserror::Error;
+use once_cell::sync::OnceCell;
+
+mod account;
+mod callback_queue;
+mod classes;
+mod config;
+mod deadman;
+mod enums;
+mod faults;
+mod fixed;
+mod journal;
+mod logging;
+mod metrics;
+mod natives;
+mod panics;
+mod positions;
+mod redact;
+mod risk;
+mod secrets;
+mod tracker;
+use account::{AccountCmd, AccountPoller, AccountStream, AccountUpdate, SubscribeReply};
+use callback_queue::{callback_queue, CallbackSender, OverflowPolicy, QueueOptions, QueuedMessage};
+use classes::{java_classes, JavaClass, JavaClasses};
+use config::{ClientFile, ClientOptions};
+use enums::{java_enums, JavaEnum, JavaEnums};
+use faults::{DelayLine, FaultAction, FaultInjector, FaultRule, Faults, InjectableError};
+use fixed::{FixedAskBid, FixedCandle, FixedOrder, FixedTrade, MarketScale, MarketScales};
+use metrics::{Metrics, LatencyHistogram, LATENCY_BUCKET_BOUNDS_MICROS};
+use panics::PoisonFlag;
+use deadman::{DeadMansSwitch, SwitchTrigger};
+use journal::{Journal, JournalAction, JournalRecord};
+use positions::{AssetPosition, Position, PositionTracker};
+use redact::{redact, RegisteredSecrets};
+use risk::{Admission, OrderIntent, RiskChecker, RiskLimits, RiskViolation};
+use secrets::{expose, SecretParams};
+use zeroize::{Zeroize, Zeroizing};
+use tracker::{OrderChange, OrderTracker, Reconciliation};
 
 #[derive(Error, Debug)]
 pub enum OpenlimitsJavaError {
@@ -74,10 +113,28 @@ pub enum OpenlimitsJavaError {
   OpenLimitsError(#[from] openlimits::errors::OpenLimitsError),
   #[error("{0}")]
   JNIError(#[from] jni::errors::Error),
+  #[error("Native code panicked: {0}")]
+  NativePanic(String),
+  // Raised by a fault rule instead of calling the exchange
+  #[error("Injected {} fault", .0.name())]
+  InjectedFault(&'static InjectableError),
+  // A price or size that cannot be a long at the scale of its market
+  #[error("{0}")]
+  FixedPoint(String),
+  // The order was stopped before it reached the exchange
+  #[error("Rejected by risk limit {0}")]
+  RiskRejected(RiskViolation),
 }
 
+static NEXT_STREAM_ID: AtomicU64 = AtomicU64::new(1);
+
+type StreamSender = tokio::sync::mpsc::UnboundedSender<SubthreadCmd>;
+
+/// Owns the websocket threads of a client. The REST client lives in `_client` and outlives it.
 pub struct ThreadManager {
-  msg_request_tx: std::sync::mpsc::SyncSender<JavaReportBackMsg>,
+  // Lets a finishing callback thread tell whether the client has opened a new stream since
+  stream_id: u64,
+  msg_request_tx: CallbackSender<JavaReportBackMsg>,
   sub_request_tx: tokio::sync::mpsc::UnboundedSender<SubthreadCmd>
 }
 
@@ -126,6 +183,10 @@ fn map_error_to_error_class(err: &OpenlimitsJavaError) -> &'static str {
     OpenlimitsJavaError::SubscribeException(_) => "io/nash/openlimits/SubscribeException",
     OpenlimitsJavaError::InitializeException(_) => "io/nash/openlimits/InitializeException",
     OpenlimitsJavaError::InvalidArgument(_) => "io/nash/openlimits/InvalidArgument",
+    OpenlimitsJavaError::NativePanic(_) => "io/nash/openlimits/NativePanic",
+    OpenlimitsJavaError::InjectedFault(error) => error.class_name,
+    OpenlimitsJavaError::FixedPoint(_) => "io/nash/openlimits/FixedPointError",
+    OpenlimitsJavaError::RiskRejected(_) => "io/nash/openlimits/RiskRejected",
     OpenlimitsJavaError::OpenLimitsError(e) => map_openlimits_error_class(e),
     OpenlimitsJavaError::JNIError(e) => {
       match e {
@@ -137,30 +198,250 @@ fn map_error_to_error_class(err: &OpenlimitsJavaError) -> &'static str {
   }
 }
 
+/// Structured fields carried by every `OpenLimitsException`, on top of the message.
+#[derive(Default)]
+struct ErrorDetails {
+  exchange: Option<&'static str>,
+  error_code: Option<i64>,
+  http_status: Option<u16>,
+  exchange_message: Option<String>,
+  retryable: bool,
+}
+
+fn http_status_is_retryable(status: u16) -> bool {
+  status == 429 || status >= 500
+}
+
+fn binance_code_is_retryable(code: i64) -> bool {
+  // UNKNOWN, DISCONNECTED, TOO_MANY_REQUESTS, TIMEOUT and TOO_MANY_ORDERS
+  matches!(code, -1000 | -1001 | -1003 | -1007 | -1015)
+}
+
+fn openlimits_error_details(err: &openlimits::errors::OpenLimitsError) -> ErrorDetails {
+  use openlimits::errors::OpenLimitsError;
+  match err {
+    OpenLimitsError::BinanceError(e) => ErrorDetails {
+      exchange: Some("binance"),
+      error_code: Some(e.code as i64),
+      exchange_message: Some(e.msg.clone()),
+      retryable: binance_code_is_retryable(e.code as i64),
+      ..Default::default()
+    },
+    OpenLimitsError::CoinbaseError(e) => ErrorDetails {
+      exchange: Some("coinbase"),
+      exchange_message: Some(e.message.clone()),
+      ..Default::default()
+    },
+    OpenLimitsError::NashProtocolError(e) => ErrorDetails {
+      exchange: Some("nash"),
+      exchange_message: Some(format!("{:?}", e)),
+      ..Default::default()
+    },
+    OpenLimitsError::ReqError(e) => {
+      let http_status = e.status().map(|status| status.as_u16());
+      ErrorDetails {
+        http_status,
+        retryable: e.is_timeout() || e.is_connect() || matches!(http_status, Some(status) if http_status_is_retryable(status)),
+        ..Default::default()
+      }
+    },
+    OpenLimitsError::InternalServerError() => ErrorDetails { http_status: Some(500), retryable: true, ..Default::default() },
+    OpenLimitsError::ServiceUnavailable() => ErrorDetails { http_status: Some(503), retryable: true, ..Default::default() },
+    OpenLimitsError::Unauthorized() => ErrorDetails { http_status: Some(401), ..Default::default() },
+    OpenLimitsError::SocketError() |
+    OpenLimitsError::Tungstenite(_) |
+    OpenLimitsError::IoError(_) |
+    OpenLimitsError::GetTimestampFailed() => ErrorDetails { retryable: true, ..Default::default() },
+    _ => ErrorDetails::default()
+  }
+}
+
+fn error_details(err: &OpenlimitsJavaError) -> ErrorDetails {
+  match err {
+    OpenlimitsJavaError::OpenLimitsError(e) => openlimits_error_details(e),
+    OpenlimitsJavaError::InjectedFault(error) => injected_fault_details(error),
+    _ => ErrorDetails::default()
+  }
+}
+
+fn injected_fault_details(error: &InjectableError) -> ErrorDetails {
+  ErrorDetails { http_status: error.http_status, retryable: error.retryable, ..Default::default() }
+}
+
+fn exchange_name(params: &InitAnyExchange) -> &'static str {
+  match params {
+    InitAnyExchange::Nash(_) => "nash",
+    InitAnyExchange::Binance(_) => "binance",
+    InitAnyExchange::Coinbase(_) => "coinbase",
+  }
+}
+
+/// Where to find the market of a failed call. Only read when an exception is actually raised.
+enum MarketSource<'a> {
+  Unknown,
+  // A request object with a `market` String field
+  Request(JObject<'a>),
+  Value(JString<'a>),
+}
+
+/// Identifies the JNI call an exception is raised from.
+struct ErrorContext<'a> {
+  operation: &'static str,
+  client: JObject<'a>,
+  market: MarketSource<'a>,
+}
+
+impl<'a> ErrorContext<'a> {
+  fn new(operation: &'static str, client: JObject<'a>) -> Self {
+    ErrorContext { operation, client, market: MarketSource::Unknown }
+  }
+
+  fn with_request(operation: &'static str, client: JObject<'a>, request: JObject<'a>) -> Self {
+    ErrorContext { operation, client, market: MarketSource::Request(request) }
+  }
+
+  fn with_market(operation: &'static str, client: JObject<'a>, market: JString<'a>) -> Self {
+    ErrorContext { operation, client, market: MarketSource::Value(market) }
+  }
+
+  fn market(&self, env: &JNIEnv) -> Option<String> {
+    let market = match &self.market {
+      MarketSource::Unknown => None,
+      MarketSource::Request(req) if req.is_null() => None,
+      MarketSource::Request(req) => get_string(env, req, "market").ok().flatten(),
+      MarketSource::Value(market) if market.is_null() => None,
+      MarketSource::Value(market) => env.get_string(*market).ok().map(|s| s.into()),
+    };
+    // A failed lookup must not replace the exception we are about to raise
+    if env.exception_check().unwrap_or(false) {
+      env.exception_clear().ok();
+    }
+    market
+  }
+
+  fn exchange(&self, env: &JNIEnv) -> Option<&'static str> {
+    if self.client.is_null() {
+      return None;
+    }
+    env.get_rust_field::<_,_,SecretParams>(self.client, "_config").ok().map(|config| exchange_name(config.params()))
+  }
+}
+
+fn exception_args<'a>(
+  env: &JNIEnv<'a>,
+  msg: String,
+  details: ErrorDetails,
+  operation: &str,
+  market: Option<String>
+) -> errors::Result<Vec<JValue<'a>>> {
+  let error_code = match details.error_code {
+    Some(code) => env.call_static_method("java/lang/Long", "valueOf", "(J)Ljava/lang/Long;", &[JValue::Long(code)])?.l()?,
+    None => JObject::null()
+  };
+  Ok(vec![
+    env.new_string(redact(&msg))?.into(),
+    optional_string_to_jstring(env, details.exchange.map(String::from))?,
+    error_code.into(),
+    JValue::Int(details.http_status.unwrap_or_default() as i32),
+    optional_string_to_jstring(env, details.exchange_message.as_deref().map(redact))?,
+    env.new_string(operation)?.into(),
+    optional_string_to_jstring(env, market)?,
+    JValue::Bool(details.retryable as u8),
+  ])
+}
+
+fn new_exception<'a>(
+  env: &JNIEnv<'a>,
+  class_name: &str,
+  msg: String,
+  details: ErrorDetails,
+  operation: &str,
+  market: Option<String>
+) -> errors::Result<JObject<'a>> {
+  let ctor_args = exception_args(env, msg, details, operation, market)?;
+  env.new_object(class_name, "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/Long;ILjava/lang/String;Ljava/lang/String;Ljava/lang/String;Z)V", &ctor_args)
+}
+
+/// A `RiskRejected`, which names the limit on top of what every `OpenLimitsException` carries.
+fn new_risk_rejected<'a>(
+  env: &JNIEnv<'a>,
+  violation: &RiskViolation,
+  msg: String,
+  details: ErrorDetails,
+  operation: &str,
+  market: Option<String>
+) -> errors::Result<JObject<'a>> {
+  let mut ctor_args = exception_args(env, msg, details, operation, market)?;
+  ctor_args.push(env.new_string(violation.limit)?.into());
+  env.new_object(
+    "io/nash/openlimits/RiskRejected",
+    "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/Long;ILjava/lang/String;Ljava/lang/String;Ljava/lang/String;ZLjava/lang/String;)V",
+    &ctor_args
+  )
+}
+
 type OpenLimitsJavaResult<T> = Result<T, OpenlimitsJavaError>;
 
 static EVENT_HANDLER_CLS_NAME: &str = "Lio/nash/openlimits/ExchangeClient;";
-static ASK_BID_CLS_NAME: &str = "Lio/nash/openlimits/AskBid;";
-static BALANCE_CLS_NAME: &str = "Lio/nash/openlimits/Balance;";
 static BINANCE_CONFIG_CLS_NAME: &str = "Lio/nash/openlimits/BinanceConfig;";
 static COINBASE_CONFIG_CLS_NAME: &str = "Lio/nash/openlimits/CoinbaseConfig;";
 static BINANCE_CREDENTIALS_CLS_NAME: &str = "Lio/nash/openlimits/BinanceCredentials;";
 static COINBASE_CREDENTIALS_CLS_NAME: &str = "Lio/nash/openlimits/CoinbaseCredentials;";
-static CANDLE_CLS_NAME: &str = "Lio/nash/openlimits/Candle;";
-static MARKET_PAIR_CLS_NAME: &str = "Lio/nash/openlimits/MarketPair;";
 static NASH_CONFIG_CLS_NAME: &str = "Lio/nash/openlimits/NashConfig;";
 static NASH_CREDENTIALS_CLS_NAME: &str = "Lio/nash/openlimits/NashCredentials;";
-static ORDER_CLS_NAME: &str = "Lio/nash/openlimits/Order;";
-static ORDERBOOK_RESPONSE_CLS_NAME: &str = "Lio/nash/openlimits/OrderbookResponse;";
-static ORDER_CANCELED_CLS_NAME: &str = "Lio/nash/openlimits/OrderCanceled;";
 static PAGINATOR_CLS_NAME: &str = "Lio/nash/openlimits/Paginator;";
-static TICKER_CLS_NAME: &str = "Lio/nash/openlimits/Ticker;";
-static TRADE_CLS_NAME: &str = "Lio/nash/openlimits/Trade;";
+static INTERVAL_CLS_NAME: &str = "Lio/nash/openlimits/Interval;";
+static TIME_IN_FORCE_CLS_NAME: &str = "Lio/nash/openlimits/TimeInForce;";
+static BACKPRESSURE_POLICY_CLS_NAME: &str = "Lio/nash/openlimits/BackpressurePolicy;";
+static STREAMING_MODE_CLS_NAME: &str = "Lio/nash/openlimits/StreamingMode;";
+static FAULT_TYPE_CLS_NAME: &str = "Lio/nash/openlimits/FaultType;";
+static EXCHANGE_CLIENT_CONFIG_CLS_NAME: &str = "Lio/nash/openlimits/ExchangeClientConfig;";
+static RISK_LIMITS_CLS_NAME: &str = "Lio/nash/openlimits/RiskLimits;";
 
 static STRING_CLS_NAME: &str = "Ljava/lang/String;";
+static BIG_DECIMAL_CLS_NAME: &str = "Ljava/math/BigDecimal;";
+
+/// Builds a `java.math.BigDecimal` from the mantissa and scale, avoiding a round trip through strings.
+fn decimal_to_jobject<'a>(env: &JNIEnv<'a>, d: Decimal) -> errors::Result<JObject<'a>> {
+  let mantissa = d.mantissa();
+  let scale = d.scale() as i32;
+  let big_decimal = &java_classes(env)?.big_decimal;
+  if mantissa >= i64::MIN as i128 && mantissa <= i64::MAX as i128 {
+    return big_decimal.value_of(env, mantissa as i64, scale);
+  }
+  big_decimal.with_unscaled_bytes(env, &mantissa.to_be_bytes(), scale)
+}
 
 fn decimal_to_jvalue<'a>(env: &JNIEnv<'a>, s: Decimal) -> errors::Result<JValue<'a>> {
-  env.new_string(s.to_string()).map(|e|e.into())
+  decimal_to_jobject(env, s).map(|e|e.into())
+}
+
+fn optional_decimal_to_jvalue<'a>(env: &JNIEnv<'a>, s: Option<Decimal>) -> errors::Result<JValue<'a>> {
+  match s {
+    Some(s) => decimal_to_jvalue(env, s),
+    None => Ok(JObject::null().into())
+  }
+}
+
+fn jobject_to_decimal(env: &JNIEnv, obj: JObject) -> Result<Decimal, String> {
+  let scale = env.call_method(obj, "scale", "()I", &[]).and_then(|v| v.i()).map_err(|e| e.to_string())?;
+  let unscaled = env.call_method(obj, "unscaledValue", "()Ljava/math/BigInteger;", &[]).and_then(|v| v.l()).map_err(|e| e.to_string())?;
+  let bytes = env.call_method(unscaled, "toByteArray", "()[B", &[]).and_then(|v| v.l()).map_err(|e| e.to_string())?;
+  let bytes = env.convert_byte_array(bytes.into_inner()).map_err(|e| e.to_string())?;
+  if bytes.is_empty() || bytes.len() > 16 {
+    return Err(String::from("BigDecimal does not fit in a 96 bit decimal"));
+  }
+
+  // toByteArray is big endian two's complement, sign extend it to 128 bits
+  let mut buf = if (bytes[0] as i8) < 0 { [0xffu8; 16] } else { [0u8; 16] };
+  buf[16 - bytes.len()..].copy_from_slice(&bytes);
+  let mut mantissa = i128::from_be_bytes(buf);
+  let mut scale = scale;
+  while scale < 0 {
+    mantissa = mantissa.checked_mul(10).ok_or("BigDecimal does not fit in a 96 bit decimal")?;
+    scale += 1;
+  }
+  Decimal::try_from_i128_with_scale(mantissa, scale as u32).map_err(|e| e.to_string())
 }
 
 fn get_field<'a>(
@@ -237,12 +518,99 @@ fn get_object<'a>(env: &'a JNIEnv, obj: &'a JObject, field: &str, t: &str) -> Re
   }
 }
 
+fn get_enum_name(
+  env: &JNIEnv,
+  obj: &JObject,
+  field: &str,
+  t: &str,
+  java_enum: fn(&JavaEnums) -> &JavaEnum
+) -> Result<&'static str, String> {
+  let value = get_object(env, obj, field, t)?.ok_or(format!("Could not find non-null field {}", field))?;
+  let enums = java_enums(env).map_err(|e| e.to_string())?;
+  java_enum(enums).name_of(env, value)
+    .map_err(|e| e.to_string())?
+    .ok_or(format!("Invalid enum constant for field {}", field))
+}
+
+fn get_string_array(env: &JNIEnv, obj: &JObject, field: &str) -> Result<Vec<String>, String> {
+  let array = match get_field(env, obj, field, "[Ljava/lang/String;")? {
+    Some(value) => value.l().map_err(|_| format!("field `{}` is not an Object", field))?,
+    None => return Ok(Vec::new()),
+  };
+  if array.is_null() {
+    return Ok(Vec::new());
+  }
+  let len = env.get_array_length(array.into_inner()).map_err(|e| e.to_string())?;
+  (0..len).map(|i| {
+    let element = env.get_object_array_element(array.into_inner(), i).map_err(|e| e.to_string())?;
+    if element.is_null() {
+      return Err(format!("Unexpected null in field {}", field));
+    }
+    env.get_string(JString::from(element)).map(|s| s.into()).map_err(|e| e.to_string())
+  }).collect()
+}
+
+fn get_decimal(env: &JNIEnv, obj: &JObject, field: &str) -> Result<Option<Decimal>, String> {
+  get_object(env, obj, field, BIG_DECIMAL_CLS_NAME)?
+    .map(|d| jobject_to_decimal(env, d).map_err(|e| format!("Invalid decimal in field {}: {}", field, e)))
+    .transpose()
+}
+
+fn get_decimal_non_null(env: &JNIEnv, obj: &JObject, field: &str) -> Result<Decimal, String> {
+  match get_decimal(env, obj, field)? {
+    Some(d) => Ok(d),
+    _ => Err(format!("Could not find non-null field {}", field))
+  }
+}
+
 fn get_string_non_null(env: &JNIEnv, obj: &JObject, field: &str) -> Result<String, String> {
   match get_string(env, obj, field)? {
     Some(s) => Ok(s),
     _ => Err(format!("Could not find non-null field {}", field))
   }
 }
+
+fn get_array<'a>(env: &'a JNIEnv, obj: &'a JObject, field: &str, type_: &str) -> Result<Option<JObject<'a>>, String> {
+  match get_field(env, obj, field, type_)? {
+    Some(value) => {
+      let array = value.l().map_err(|_| format!("field `{}` is not an array", field))?;
+      Ok(if array.is_null() { None } else { Some(array) })
+    },
+    None => Ok(None),
+  }
+}
+
+/// A credential from a String field, or from the `<field>Chars` or UTF-8 `<field>Bytes` array set instead. Java clears
+/// the arrays once the client was created or updated, a call that fails leaves them to retry with.
+fn get_secret(env: &JNIEnv, obj: &JObject, field: &str) -> Result<Zeroizing<String>, String> {
+  if let Some(secret) = get_string(env, obj, field)? {
+    return Ok(Zeroizing::new(secret));
+  }
+  let chars_field = format!("{}Chars", field);
+  if let Some(chars) = get_array(env, obj, &chars_field, "[C")? {
+    let array = chars.into_inner();
+    let len = env.get_array_length(array).map_err(|_| format!("Cannot read `{}`", chars_field))? as usize;
+    let mut chars = Zeroizing::new(vec![0u16; len]);
+    env.get_char_array_region(array, 0, &mut chars[..]).map_err(|_| format!("Cannot read `{}`", chars_field))?;
+    return String::from_utf16(&chars).map(Zeroizing::new).map_err(|_| format!("`{}` is not valid UTF-16", chars_field));
+  }
+  let bytes_field = format!("{}Bytes", field);
+  if let Some(bytes) = get_array(env, obj, &bytes_field, "[B")? {
+    let array = bytes.into_inner();
+    let len = env.get_array_length(array).map_err(|_| format!("Cannot read `{}`", bytes_field))? as usize;
+    let mut bytes = Zeroizing::new(vec![0i8; len]);
+    env.get_byte_array_region(array, 0, &mut bytes[..]).map_err(|_| format!("Cannot read `{}`", bytes_field))?;
+    let utf8: Vec<u8> = bytes.iter().map(|byte| *byte as u8).collect();
+    return match String::from_utf8(utf8) {
+      Ok(secret) => Ok(Zeroizing::new(secret)),
+      Err(e) => {
+        e.into_bytes().zeroize();
+        Err(format!("`{}` is not valid UTF-8", bytes_field))
+      }
+    };
+  }
+  Err(format!("Could not find non-null field {}", field))
+}
 fn get_long_default_with_default(
   env: &JNIEnv,
   obj: &JObject,
@@ -257,14 +625,13 @@ fn get_long_default_with_default(
 
 
 fn bidask_to_jobject<'a>(env: &JNIEnv<'a>, resp: AskBid) -> errors::Result<JObject<'a>> {
-  let cls_bidask = env.find_class(ASK_BID_CLS_NAME)?;
 
   let ctor_args = &[
     decimal_to_jvalue(env, resp.price)?,
     decimal_to_jvalue(env, resp.qty)?,
   ];
 
-  env.new_object(cls_bidask, "(Ljava/lang/String;Ljava/lang/String;)V", ctor_args)
+  java_classes(env)?.ask_bid.new_object(env, ctor_args)
 }
 
 fn vec_to_java_arr<'a>(env: &JNIEnv<'a>, cls: JClass, v: &Vec<JObject<'a>>) -> errors::Result<JValue<'a>> {
@@ -275,25 +642,51 @@ fn vec_to_java_arr<'a>(env: &JNIEnv<'a>, cls: JClass, v: &Vec<JObject<'a>>) -> e
   Ok(JValue::from(arr))
 }
 
+fn fixed_bidask_to_jobject<'a>(env: &JNIEnv<'a>, level: FixedAskBid) -> errors::Result<JObject<'a>> {
+  let ctor_args = &[
+    JValue::Long(level.price),
+    JValue::Long(level.qty),
+    JValue::Int(level.scale.price as jint),
+    JValue::Int(level.scale.size as jint),
+  ];
+  java_classes(env)?.ask_bid_fixed.new_object(env, ctor_args)
+}
+
 fn orderbook_resp_to_jobject<'a>(env: &JNIEnv<'a>, resp: OrderBookResponse, market_pair: JValue) -> errors::Result<JObject<'a>> {
-  let cls_resp = env.find_class(ORDERBOOK_RESPONSE_CLS_NAME)?;
+  let asks = vec_to_jobject(env, |c| &c.ask_bid, resp.asks, bidask_to_jobject)?;
+  let bids = vec_to_jobject(env, |c| &c.ask_bid, resp.bids, bidask_to_jobject)?;
+  orderbook_levels_to_jobject(env, market_pair, asks, bids, resp.last_update_id, resp.update_id)
+}
 
-  let asks = vec_to_jobject(env, ASK_BID_CLS_NAME, resp.asks, bidask_to_jobject)?;
-  let bids = vec_to_jobject(env, ASK_BID_CLS_NAME, resp.bids, bidask_to_jobject)?;
+fn fixed_orderbook_resp_to_jobject<'a>(env: &JNIEnv<'a>, resp: OrderBookResponse, scale: MarketScale, market_pair: JValue) -> OpenLimitsJavaResult<JObject<'a>> {
+  let fixed_levels = |levels: &[AskBid]| levels.iter().map(|level| scale.ask_bid(level)).collect::<Result<Vec<_>, _>>();
+  let asks = fixed_levels(&resp.asks).map_err(OpenlimitsJavaError::FixedPoint)?;
+  let bids = fixed_levels(&resp.bids).map_err(OpenlimitsJavaError::FixedPoint)?;
+  let asks = vec_to_jobject(env, |c| &c.ask_bid_fixed, asks, fixed_bidask_to_jobject)?;
+  let bids = vec_to_jobject(env, |c| &c.ask_bid_fixed, bids, fixed_bidask_to_jobject)?;
+  Ok(orderbook_levels_to_jobject(env, market_pair, asks, bids, resp.last_update_id, resp.update_id)?)
+}
 
+fn orderbook_levels_to_jobject<'a>(
+  env: &JNIEnv<'a>,
+  market_pair: JValue,
+  asks: JObject,
+  bids: JObject,
+  last_update_id: Option<u64>,
+  update_id: Option<u64>
+) -> errors::Result<JObject<'a>> {
   let ctor_args = &[
     market_pair,
     asks.into(),
     bids.into(),
-    JValue::Long(resp.last_update_id.unwrap_or_default() as i64),
-    JValue::Long(resp.update_id.unwrap_or_default() as i64),
+    JValue::Long(last_update_id.unwrap_or_default() as i64),
+    JValue::Long(update_id.unwrap_or_default() as i64),
   ];
-  env.new_object(cls_resp, "(Ljava/lang/String;[Lio/nash/openlimits/AskBid;[Lio/nash/openlimits/AskBid;JJ)V", ctor_args)
+  java_classes(env)?.orderbook_response.new_object(env, ctor_args)
 }
 
 
 fn candle_to_jobject<'a>(env: &JNIEnv<'a>, candle: Candle) -> errors::Result<JObject<'a>> {
-  let cls_candle = env.find_class(CANDLE_CLS_NAME)?;
   
   let ctor_args = &[
     JValue::Long(candle.time as i64),
@@ -304,11 +697,21 @@ fn candle_to_jobject<'a>(env: &JNIEnv<'a>, candle: Candle) -> errors::Result<JOb
     decimal_to_jvalue(env, candle.volume)?
   ];
 
-  env.new_object(cls_candle, "(JLjava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;)V", ctor_args)
+  java_classes(env)?.candle.new_object(env, ctor_args)
 }
 
-fn string_to_jstring<'a>(env: &JNIEnv<'a>, s: String) -> errors::Result<JString<'a>> {
-  env.new_string(s)
+fn fixed_candle_to_jobject<'a>(env: &JNIEnv<'a>, candle: FixedCandle) -> errors::Result<JObject<'a>> {
+  let ctor_args = &[
+    JValue::Long(candle.time as i64),
+    JValue::Long(candle.low),
+    JValue::Long(candle.high),
+    JValue::Long(candle.open),
+    JValue::Long(candle.close),
+    JValue::Long(candle.volume),
+    JValue::Int(candle.scale.price as jint),
+    JValue::Int(candle.scale.size as jint),
+  ];
+  java_classes(env)?.candle_fixed.new_object(env, ctor_args)
 }
 
 
@@ -319,19 +722,19 @@ fn optional_string_to_jstring<'a>(env: &JNIEnv<'a>, s: Option<String>) -> errors
   }
 }
 
-fn side_to_string<'a>(env: &JNIEnv<'a>, s: Side) -> errors::Result<JString<'a>> {
+fn side_to_jobject<'a>(env: &JNIEnv<'a>, s: Side) -> errors::Result<JObject<'a>> {
   let s = match s {
     Side::Buy => "Buy",
     Side::Sell => "Sell",
   };
-  string_to_jstring(env, String::from(s))
+  java_enums(env)?.side.to_jobject(env, s)
 }
-fn liquidity_to_string<'a>(env: &JNIEnv<'a>, s: Liquidity) -> errors::Result<JObject<'a>> {
+fn liquidity_to_jobject<'a>(env: &JNIEnv<'a>, s: Liquidity) -> errors::Result<JObject<'a>> {
   let s = match s {
     Liquidity::Maker => "Maker",
     Liquidity::Taker => "Taker",
   };
-  Ok(string_to_jstring(env, String::from(s))?.into())
+  java_enums(env)?.liquidity.to_jobject(env, s)
 }
 
 fn string_option_to_null(v: Option<JString>) -> JValue {
@@ -343,7 +746,6 @@ fn string_option_to_null(v: Option<JString>) -> JValue {
 
 
 fn trade_to_jobject<'a>(env: &JNIEnv<'a>, trade: Trade) -> errors::Result<JObject<'a>> {
-  let cls_trade = env.find_class(TRADE_CLS_NAME)?;
   let ctor_args = &[
     env.new_string(trade.id)?.into(),
     optional_string_to_jstring(env, trade.buyer_order_id)?,
@@ -351,21 +753,40 @@ fn trade_to_jobject<'a>(env: &JNIEnv<'a>, trade: Trade) -> errors::Result<JObjec
     env.new_string(trade.market_pair)?.into(),
     decimal_to_jvalue(env, trade.price)?,
     decimal_to_jvalue(env, trade.qty)?,
-    trade.fees.map_or(Ok(JObject::null().into()), |f| decimal_to_jvalue(env, f))?,
-    side_to_string(env, trade.side)?.into(),
-    trade.liquidity.map_or(Ok(JObject::null()), |l| liquidity_to_string(env, l))?.into(),
+    optional_decimal_to_jvalue(env, trade.fees)?,
+    side_to_jobject(env, trade.side)?.into(),
+    trade.liquidity.map_or(Ok(JObject::null()), |l| liquidity_to_jobject(env, l))?.into(),
+    JValue::Long(trade.created_at as i64)
+  ];
+
+  java_classes(env)?.trade.new_object(env, ctor_args)
+}
+
+fn fixed_trade_to_jobject<'a>(env: &JNIEnv<'a>, fixed: FixedTrade) -> errors::Result<JObject<'a>> {
+  let trade = fixed.trade;
+  let ctor_args = &[
+    env.new_string(trade.id)?.into(),
+    optional_string_to_jstring(env, trade.buyer_order_id)?,
+    optional_string_to_jstring(env, trade.seller_order_id)?,
+    env.new_string(trade.market_pair)?.into(),
+    JValue::Long(fixed.price),
+    JValue::Long(fixed.qty),
+    JValue::Int(fixed.scale.price as jint),
+    JValue::Int(fixed.scale.size as jint),
+    optional_decimal_to_jvalue(env, trade.fees)?,
+    side_to_jobject(env, trade.side)?.into(),
+    trade.liquidity.map_or(Ok(JObject::null()), |l| liquidity_to_jobject(env, l))?.into(),
     JValue::Long(trade.created_at as i64)
   ];
 
-  env.new_object(cls_trade, "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;J)V", ctor_args)
+  java_classes(env)?.trade_fixed.new_object(env, ctor_args)
 }
 
 fn ticker_to_jobject<'a>(env: &JNIEnv<'a>, resp: Ticker) -> errors::Result<JObject<'a>> {
-  let cls_resp = env.find_class(TICKER_CLS_NAME)?;
   let ctor_args = &[
     decimal_to_jvalue(env, resp.price.unwrap_or_default())?
   ];
-  env.new_object(cls_resp, "(Ljava/lang/String;)V", ctor_args)
+  java_classes(env)?.ticker.new_object(env, ctor_args)
 }
 
 fn order_type_to_string(typ: OrderType) -> &'static str {
@@ -393,300 +814,1023 @@ fn order_status_to_string(typ: OrderStatus) -> &'static str {
 }
 
 fn order_to_jobject<'a>(env: &JNIEnv<'a>, order: Order) -> errors::Result<JObject<'a>> {
-  let cls_resp = env.find_class(ORDER_CLS_NAME)?;
-  let trades = vec_to_jobject(&env, TRADE_CLS_NAME, order.trades, trade_to_jobject)?;
+  let trades = vec_to_jobject(env, |c| &c.trade, order.trades, trade_to_jobject)?;
+  let ctor_args = &[
+    env.new_string(order.id)?.into(),
+    env.new_string(order.market_pair)?.into(),
+    string_option_to_null(order.client_order_id.map(|s| env.new_string(s)).transpose()?),
+    JValue::Long(order.created_at.unwrap_or_default() as i64),
+    java_enums(env)?.order_type.to_jobject(env, order_type_to_string(order.order_type))?.into(),
+    side_to_jobject(env, order.side)?.into(),
+    java_enums(env)?.order_status.to_jobject(env, order_status_to_string(order.status))?.into(),
+    decimal_to_jvalue(env, order.size)?,
+    optional_decimal_to_jvalue(env, order.price)?,
+    trades.into()
+  ];
+  java_classes(env)?.order.new_object(env, ctor_args)
+}
+
+fn fixed_order_to_jobject<'a>(env: &JNIEnv<'a>, fixed: FixedOrder) -> errors::Result<JObject<'a>> {
+  let order = fixed.order;
+  let trades = vec_to_jobject(env, |c| &c.trade_fixed, fixed.trades, fixed_trade_to_jobject)?;
   let ctor_args = &[
     env.new_string(order.id)?.into(),
     env.new_string(order.market_pair)?.into(),
     string_option_to_null(order.client_order_id.map(|s| env.new_string(s)).transpose()?),
     JValue::Long(order.created_at.unwrap_or_default() as i64),
-    env.new_string(order_type_to_string(order.order_type))?.into(),
-    side_to_string(env, order.side)?.into(),
-    env.new_string(order_status_to_string(order.status))?.into(),
-    env.new_string(order.size.to_string())?.into(),
-    string_option_to_null(order.price.map(|p| env.new_string(p.to_string())).transpose()?),
+    java_enums(env)?.order_type.to_jobject(env, order_type_to_string(order.order_type))?.into(),
+    side_to_jobject(env, order.side)?.into(),
+    java_enums(env)?.order_status.to_jobject(env, order_status_to_string(order.status))?.into(),
+    JValue::Long(fixed.size),
+    JValue::Long(fixed.price),
+    JValue::Int(fixed.scale.price as jint),
+    JValue::Int(fixed.scale.size as jint),
     trades.into()
   ];
-  env.new_object(cls_resp, "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;JLjava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;[Lio/nash/openlimits/Trade;)V", ctor_args)
+  java_classes(env)?.order_fixed.new_object(env, ctor_args)
+}
+
+// Converters for the results a fixed-point client returns as longs. Without scales they build BigDecimals.
+
+fn trades_to_jobject<'a>(env: &JNIEnv<'a>, trades: Vec<Trade>, scales: Option<&MarketScales>) -> OpenLimitsJavaResult<JObject<'a>> {
+  match scales {
+    None => Ok(vec_to_jobject(env, |c| &c.trade, trades, trade_to_jobject)?),
+    Some(scales) => {
+      let trades = scales.trades(trades).map_err(OpenlimitsJavaError::FixedPoint)?;
+      Ok(vec_to_jobject(env, |c| &c.trade_fixed, trades, fixed_trade_to_jobject)?)
+    }
+  }
+}
+
+static ORDER_STATE_CHANGE_SIG: &str = "(Lio/nash/openlimits/Order;Lio/nash/openlimits/OrderStatus;Lio/nash/openlimits/OrderStatus;)V";
+
+fn order_change_args<'a>(env: &JNIEnv<'a>, change: OrderChange, scales: Option<&MarketScales>) -> OpenLimitsJavaResult<[JValue<'a>; 3]> {
+  let statuses = &java_enums(env)?.order_status;
+  let old = match change.old {
+    Some(old) => statuses.to_jobject(env, order_status_to_string(old))?,
+    None => JObject::null(),
+  };
+  let new = statuses.to_jobject(env, order_status_to_string(change.new))?;
+  Ok([any_order_to_jobject(env, change.order, scales)?.into(), old.into(), new.into()])
+}
+
+fn any_trade_to_jobject<'a>(env: &JNIEnv<'a>, trade: Trade, scales: Option<&MarketScales>) -> OpenLimitsJavaResult<JObject<'a>> {
+  match scales {
+    None => Ok(trade_to_jobject(env, trade)?),
+    Some(scales) => {
+      let trade = scales.get(&trade.market_pair).and_then(|scale| scale.trade(trade)).map_err(OpenlimitsJavaError::FixedPoint)?;
+      Ok(fixed_trade_to_jobject(env, trade)?)
+    }
+  }
+}
+
+fn any_order_to_jobject<'a>(env: &JNIEnv<'a>, order: Order, scales: Option<&MarketScales>) -> OpenLimitsJavaResult<JObject<'a>> {
+  match scales {
+    None => Ok(order_to_jobject(env, order)?),
+    Some(scales) => {
+      let order = scales.get(&order.market_pair).and_then(|scale| scale.order(order)).map_err(OpenlimitsJavaError::FixedPoint)?;
+      Ok(fixed_order_to_jobject(env, order)?)
+    }
+  }
+}
+
+fn orders_to_jobject<'a>(env: &JNIEnv<'a>, orders: Vec<Order>, scales: Option<&MarketScales>) -> OpenLimitsJavaResult<JObject<'a>> {
+  match scales {
+    None => Ok(vec_to_jobject(env, |c| &c.order, orders, order_to_jobject)?),
+    Some(scales) => {
+      let orders = scales.orders(orders).map_err(OpenlimitsJavaError::FixedPoint)?;
+      Ok(vec_to_jobject(env, |c| &c.order_fixed, orders, fixed_order_to_jobject)?)
+    }
+  }
+}
+
+fn candles_to_jobject<'a>(env: &JNIEnv<'a>, candles: Vec<Candle>, market: &str, scales: Option<&MarketScales>) -> OpenLimitsJavaResult<JObject<'a>> {
+  match scales {
+    None => Ok(vec_to_jobject(env, |c| &c.candle, candles, candle_to_jobject)?),
+    Some(scales) => {
+      let scale = scales.get(market).map_err(OpenlimitsJavaError::FixedPoint)?;
+      let candles = candles.into_iter().map(|candle| scale.candle(candle)).collect::<Result<Vec<_>, _>>()
+        .map_err(OpenlimitsJavaError::FixedPoint)?;
+      Ok(vec_to_jobject(env, |c| &c.candle_fixed, candles, fixed_candle_to_jobject)?)
+    }
+  }
+}
+
+fn any_orderbook_to_jobject<'a>(env: &JNIEnv<'a>, resp: OrderBookResponse, market: &str, market_pair: JValue, scales: Option<&MarketScales>) -> OpenLimitsJavaResult<JObject<'a>> {
+  match scales {
+    None => Ok(orderbook_resp_to_jobject(env, resp, market_pair)?),
+    Some(scales) => {
+      let scale = scales.get(market).map_err(OpenlimitsJavaError::FixedPoint)?;
+      fixed_orderbook_resp_to_jobject(env, resp, scale, market_pair)
+    }
+  }
 }
 
-fn vec_to_jobject<'a, T, F>(env: &JNIEnv<'a>, cls: &str, entries: Vec<T>, f: F) -> errors::Result<JObject<'a>>
+// Enough for the strings, decimals and enum constants of one converted entry
+static ENTRY_FRAME_CAPACITY: i32 = 16;
+// Enough for one websocket message, array entries are freed as they are added
+static DISPATCH_FRAME_CAPACITY: i32 = 64;
+
+fn vec_to_jobject<'a, T, F>(
+  env: &JNIEnv<'a>,
+  java_class: fn(&JavaClasses) -> &JavaClass,
+  entries: Vec<T>,
+  f: F
+) -> errors::Result<JObject<'a>>
   where F: Fn(&JNIEnv<'a>,T) -> errors::Result<JObject<'a>> {
-  if entries.len() == 0 {
-    let out = env.new_object_array(0, cls, JObject::null())?;
+  let cls = java_class(java_classes(env)?).class();
+  let out = env.new_object_array(entries.len() as jsize, cls, JObject::null())?;
+  for (i, entry) in entries.into_iter().enumerate() {
+    // Only the entry itself outlives its frame, so large order books don't pile up local references
+    let obj = env.with_local_frame(ENTRY_FRAME_CAPACITY, || f(env, entry))?;
+    env.set_object_array_element(out, i as jsize, obj)?;
+    env.delete_local_ref(obj)?;
+  }
+  Ok(JObject::from(out))
+}
+
+/// Pops a local reference frame when dropped, freeing every local reference created since it was pushed.
+struct LocalFrame<'a, 'b> {
+  env: &'b JNIEnv<'a>,
+}
+
+impl<'a, 'b> LocalFrame<'a, 'b> {
+  fn push(env: &'b JNIEnv<'a>, capacity: i32) -> errors::Result<Self> {
+    env.push_local_frame(capacity)?;
+    Ok(LocalFrame { env })
+  }
+}
+
+impl Drop for LocalFrame<'_, '_> {
+  fn drop(&mut self) {
+    if self.env.pop_local_frame(JObject::null()).is_err() {
+      log::error!("Failed to pop local reference frame");
+    }
+  }
+}
+
+/// Writes order books straight into the direct buffer of the client instead of building objects, see `OrderbookView`.
+struct DirectOrderbooks {
+  on_orderbook_view: jmethodID,
+  orderbook_buffer: jmethodID,
+  // The buffer Java handed out last, with its capacity in bytes
+  buffer: Option<(GlobalRef, usize)>,
+}
+
+// A price and a quantity, both longs
+const DIRECT_LEVEL_BYTES: usize = 16;
+
+impl DirectOrderbooks {
+  fn load(env: &JNIEnv, enabled: bool) -> OpenLimitsJavaResult<Option<Self>> {
+    if !enabled {
+      return Ok(None);
+    }
+    let event_handler_cls = env.find_class(EVENT_HANDLER_CLS_NAME)?;
+    Ok(Some(DirectOrderbooks {
+      on_orderbook_view: env.get_method_id(event_handler_cls, "onOrderbookView", "(Ljava/lang/String;JJIIII)V")?.into_inner(),
+      orderbook_buffer: env.get_method_id(event_handler_cls, "orderbookBuffer", "(I)Ljava/nio/ByteBuffer;")?.into_inner(),
+      buffer: None,
+    }))
+  }
+
+  /// The outer error is a failed JNI call, the inner one a book that cannot be written as fixed-point longs.
+  fn dispatch(&mut self, env: &JNIEnv, client: JObject, market: &str, resp: &OrderBookResponse) -> errors::Result<Result<(), String>> {
+    let levels = || resp.asks.iter().chain(resp.bids.iter());
+    // Every level is written at the largest scale of the book, so no value loses precision
+    let price_scale = levels().map(|level| level.price.scale()).max().unwrap_or_default();
+    let qty_scale = levels().map(|level| level.qty.scale()).max().unwrap_or_default();
+
+    let buffer = self.buffer(env, client, (resp.asks.len() + resp.bids.len()) * DIRECT_LEVEL_BYTES)?;
+    let bytes = env.get_direct_buffer_address(JByteBuffer::from(buffer.as_obj().into_inner()))?;
+    for (chunk, level) in bytes.chunks_exact_mut(DIRECT_LEVEL_BYTES).zip(levels()) {
+      let (price, qty) = match (fixed::to_fixed(level.price, price_scale), fixed::to_fixed(level.qty, qty_scale)) {
+        (Ok(price), Ok(qty)) => (price, qty),
+        (Err(err), _) | (_, Err(err)) => return Ok(Err(format!("Cannot write the {} order book: {}", market, err))),
+      };
+      chunk[..8].copy_from_slice(&price.to_ne_bytes());
+      chunk[8..].copy_from_slice(&qty.to_ne_bytes());
+    }
+
+    let market = env.new_string(market)?;
+    env.call_method_unchecked(
+      client,
+      JMethodID::from(self.on_orderbook_view),
+      jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
+      &[
+        market.into(),
+        JValue::Long(resp.update_id.unwrap_or_default() as i64),
+        JValue::Long(resp.last_update_id.unwrap_or_default() as i64),
+        JValue::Int(resp.asks.len() as jint),
+        JValue::Int(resp.bids.len() as jint),
+        JValue::Int(price_scale as jint),
+        JValue::Int(qty_scale as jint),
+      ]
+    )?;
+    Ok(Ok(()))
+  }
+
+  /// Asks Java for a larger buffer only when the book doesn't fit the current one.
+  fn buffer(&mut self, env: &JNIEnv, client: JObject, len: usize) -> errors::Result<GlobalRef> {
+    if let Some((buffer, capacity)) = &self.buffer {
+      if *capacity >= len {
+        return Ok(buffer.clone());
+      }
+    }
+    let buffer = env.call_method_unchecked(
+      client,
+      JMethodID::from(self.orderbook_buffer),
+      jni::signature::JavaType::Object(String::from("java/nio/ByteBuffer")),
+      &[JValue::Int(len as jint)]
+    )?.l()?;
+    let capacity = env.get_direct_buffer_capacity(JByteBuffer::from(buffer.into_inner()))?;
+    let buffer = env.new_global_ref(buffer)?;
+    self.buffer = Some((buffer.clone(), capacity as usize));
+    Ok(buffer)
+  }
+}
+
+/// The callbacks of the account streams, and the order and position trackers they feed.
+struct AccountCallbacks {
+  on_order_update: jmethodID,
+  on_fill: jmethodID,
+  on_balance: jmethodID,
+  on_order_state_change: jmethodID,
+  tracker: Option<Arc<OrderTracker>>,
+  positions: Option<Arc<PositionTracker>>,
+}
 
-    return Ok(JObject::from(out));
+impl AccountCallbacks {
+  fn load(env: &JNIEnv, tracker: Option<Arc<OrderTracker>>, positions: Option<Arc<PositionTracker>>) -> OpenLimitsJavaResult<Self> {
+    let event_handler_cls = env.find_class(EVENT_HANDLER_CLS_NAME)?;
+    Ok(AccountCallbacks {
+      on_order_update: env.get_method_id(event_handler_cls, "onOrderUpdate", "(Lio/nash/openlimits/Order;)V")?.into_inner(),
+      on_fill: env.get_method_id(event_handler_cls, "onFill", "(Lio/nash/openlimits/Trade;)V")?.into_inner(),
+      on_balance: env.get_method_id(event_handler_cls, "onBalance", "(Lio/nash/openlimits/Balance;)V")?.into_inner(),
+      on_order_state_change: env.get_method_id(event_handler_cls, "onOrderStateChange", ORDER_STATE_CHANGE_SIG)?.into_inner(),
+      tracker,
+      positions,
+    })
   }
-  let pairs_maybe: errors::Result<Vec<_>> = entries.into_iter().map(|v| f(env, v)).collect();
-  let pairs = pairs_maybe?;
-  let pairs_cls = env.find_class(cls)?;
 
-  let out = vec_to_java_arr(&env, pairs_cls, &pairs)?;
-  out.l()
+  fn dispatch(&self, env: &JNIEnv, client: JObject, update: AccountUpdate, scales: Option<&MarketScales>) -> OpenLimitsJavaResult<()> {
+    if let (Some(tracker), AccountUpdate::Order(order) | AccountUpdate::TrackedOrder(order)) = (&self.tracker, &update) {
+      if let Some(change) = tracker.record(order.clone()) {
+        env.call_method_unchecked(
+          client,
+          JMethodID::from(self.on_order_state_change),
+          jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
+          &order_change_args(env, change, scales)?
+        )?;
+      }
+    }
+    if let (Some(positions), AccountUpdate::Fill(fill) | AccountUpdate::TrackedFill(fill)) = (&self.positions, &update) {
+      positions.apply(vec![fill.clone()]);
+    }
+    let (method, arg) = match update {
+      AccountUpdate::TrackedOrder(_) | AccountUpdate::TrackedFill(_) => return Ok(()),
+      AccountUpdate::Order(order) => (self.on_order_update, any_order_to_jobject(env, order, scales)?),
+      AccountUpdate::Fill(trade) => (self.on_fill, any_trade_to_jobject(env, trade, scales)?),
+      AccountUpdate::Balance(balance) => (self.on_balance, balance_to_jobject(env, balance)?),
+    };
+    env.call_method_unchecked(
+      client,
+      JMethodID::from(method),
+      jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
+      &[arg.into()]
+    )?;
+    Ok(())
+  }
 }
 
 
 fn order_cancelled_to_jobject<'a>(env: &JNIEnv<'a>, order: OrderCanceled) -> errors::Result<JObject<'a>> {
-  let cls_resp = env.find_class(ORDER_CANCELED_CLS_NAME)?;
   let ctor_args = &[
     env.new_string(order.id)?.into(),
   ];
-  env.new_object(cls_resp, "(Ljava/lang/String;)V", ctor_args)
+  java_classes(env)?.order_canceled.new_object(env, ctor_args)
+}
+
+fn position_to_jobject<'a>(env: &JNIEnv<'a>, position: Position) -> errors::Result<JObject<'a>> {
+  let ctor_args = &[
+    env.new_string(&position.market)?.into(),
+    decimal_to_jvalue(env, position.net)?,
+    decimal_to_jvalue(env, position.average_entry)?,
+    decimal_to_jvalue(env, position.realised)?,
+    optional_decimal_to_jvalue(env, position.unrealised())?,
+    decimal_to_jvalue(env, position.fees)?,
+    optional_decimal_to_jvalue(env, position.mid)?,
+  ];
+  java_classes(env)?.position.new_object(env, ctor_args)
+}
+
+fn asset_position_to_jobject<'a>(env: &JNIEnv<'a>, asset: AssetPosition) -> errors::Result<JObject<'a>> {
+  let ctor_args = &[
+    env.new_string(asset.asset)?.into(),
+    decimal_to_jvalue(env, asset.net)?,
+    decimal_to_jvalue(env, asset.fees)?,
+  ];
+  java_classes(env)?.asset_position.new_object(env, ctor_args)
 }
 
 fn balance_to_jobject<'a>(env: &JNIEnv<'a>, balance: Balance) -> errors::Result<JObject<'a>> {
-  let cls_resp = env.find_class(BALANCE_CLS_NAME)?;
   let ctor_args = &[
     env.new_string(balance.asset)?.into(),
-    env.new_string(balance.total.to_string())?.into(),
-    env.new_string(balance.free.to_string())?.into(),
+    decimal_to_jvalue(env, balance.total)?,
+    decimal_to_jvalue(env, balance.free)?,
   ];
 
-  env.new_object(cls_resp, "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;)V", ctor_args)
+  java_classes(env)?.balance.new_object(env, ctor_args)
 }
 
 fn market_pair_to_jobject<'a>(env: &JNIEnv<'a>, pair: MarketPair) -> errors::Result<JObject<'a>> {
-  let cls_resp = env.find_class(MARKET_PAIR_CLS_NAME)?;
-  
-  let min_base_trade_size = pair.min_base_trade_size;
-  let min_base_trade_size = min_base_trade_size.map(|f|f.to_string());
-  let min_base_trade_size = match min_base_trade_size {
-    None => JObject::null().into(),
-    Some(s) => env.new_string(s)?
-  };
-  
-  let min_quote_trade_size = pair.min_quote_trade_size;
-  let min_quote_trade_size = min_quote_trade_size.map(|f|f.to_string());
-  let min_quote_trade_size = match min_quote_trade_size {
-    None => JObject::null().into(),
-    Some(s) => env.new_string(s)?
-  };
-  
 
   let ctor_args = &[
     env.new_string(pair.base)?.into(),
     env.new_string(pair.quote)?.into(),
     env.new_string(pair.symbol)?.into(),
-    env.new_string(pair.base_increment.to_string())?.into(),
-    env.new_string(pair.quote_increment.to_string())?.into(),
-    min_base_trade_size.into(),
-    min_quote_trade_size.into()
+    decimal_to_jvalue(env, pair.base_increment)?,
+    decimal_to_jvalue(env, pair.quote_increment)?,
+    optional_decimal_to_jvalue(env, pair.min_base_trade_size)?,
+    optional_decimal_to_jvalue(env, pair.min_quote_trade_size)?,
+  ];
+
+  java_classes(env)?.market_pair.new_object(env, ctor_args)
+}
+
+fn u64_slice_to_jlong_array<'a>(env: &JNIEnv<'a>, values: &[u64]) -> errors::Result<JValue<'a>> {
+  let values: Vec<i64> = values.iter().map(|v| *v as i64).collect();
+  let arr = env.new_long_array(values.len() as jsize)?;
+  env.set_long_array_region(arr, 0, &values)?;
+  Ok(JObject::from(arr).into())
+}
+
+fn latency_histogram_to_jobject<'a>(env: &JNIEnv<'a>, entry: (&'static str, LatencyHistogram)) -> errors::Result<JObject<'a>> {
+  let (operation, histogram) = entry;
+  let ctor_args = &[
+    env.new_string(operation)?.into(),
+    JValue::Long(histogram.count as i64),
+    JValue::Long(histogram.sum_micros as i64),
+    JValue::Long(histogram.max_micros as i64),
+    u64_slice_to_jlong_array(env, &LATENCY_BUCKET_BOUNDS_MICROS)?,
+    u64_slice_to_jlong_array(env, &histogram.buckets)?,
+  ];
+  java_classes(env)?.latency_histogram.new_object(env, ctor_args)
+}
+
+fn error_count_to_jobject<'a>(env: &JNIEnv<'a>, entry: (&'static str, u64)) -> errors::Result<JObject<'a>> {
+  let (error_class, count) = entry;
+  let ctor_args = &[
+    env.new_string(error_class.replace('/', "."))?.into(),
+    JValue::Long(count as i64),
+  ];
+  java_classes(env)?.error_count.new_object(env, ctor_args)
+}
+
+fn subscription_metrics_to_jobject<'a>(env: &JNIEnv<'a>, entry: (String, u64)) -> errors::Result<JObject<'a>> {
+  let (subscription, messages) = entry;
+  let ctor_args = &[
+    env.new_string(subscription)?.into(),
+    JValue::Long(messages as i64),
+  ];
+  java_classes(env)?.subscription_metrics.new_object(env, ctor_args)
+}
+
+fn metrics_to_jobject<'a>(env: &JNIEnv<'a>, metrics: &Metrics) -> errors::Result<JObject<'a>> {
+  let snapshot = metrics.snapshot();
+  let latencies = vec_to_jobject(env, |c| &c.latency_histogram, snapshot.latencies, latency_histogram_to_jobject)?;
+  let errors = vec_to_jobject(env, |c| &c.error_count, snapshot.errors, error_count_to_jobject)?;
+  let subscriptions = vec_to_jobject(env, |c| &c.subscription_metrics, snapshot.subscription_messages, subscription_metrics_to_jobject)?;
+  let ctor_args = &[
+    latencies.into(),
+    errors.into(),
+    subscriptions.into(),
+    JValue::Long(snapshot.queue_depth),
+    JValue::Long(snapshot.max_queue_depth),
   ];
+  java_classes(env)?.metrics_snapshot.new_object(env, ctor_args)
+}
+
+fn is_connection_error(err: &openlimits::errors::OpenLimitsError) -> bool {
+  use openlimits::errors::OpenLimitsError;
+  matches!(err, OpenLimitsError::SocketError() | OpenLimitsError::Tungstenite(_) | OpenLimitsError::IoError(_))
+}
+
+fn stream_market(stream: &AccountStream) -> Option<String> {
+  match stream {
+    AccountStream::Orders(market) | AccountStream::Fills(market) => Some(market.clone()),
+    AccountStream::Balances => None,
+  }
+}
+
+fn subscription_label(sub: &Subscription) -> String {
+  match sub {
+    Subscription::OrderBookUpdates(market) => format!("orderbook:{}", market),
+    Subscription::Trades(market) => format!("trades:{}", market),
+    Subscription::Ticker(market) => format!("ticker:{}", market),
+    Subscription::AccountOrders(market) => format!("orders:{}", market),
+    Subscription::AccountTrades(market) => format!("fills:{}", market),
+    Subscription::AccountBalance(asset) => format!("balances:{}", asset),
+  }
+}
+
+/// Hands the error of a websocket subscription to `onError`, and tells the dead man's switch when the websocket
+/// was lost.
+fn report_stream_error(
+  err: &openlimits::errors::OpenLimitsError,
+  label: &str,
+  market: Option<String>,
+  switch: Option<&SwitchTrigger>,
+  reporter_tx: &CallbackSender<JavaReportBackMsg>
+) {
+  log::warn!("Subscription {} failed: {:?}", label, err);
+  if let (Some(switch), true) = (switch, is_connection_error(err)) {
+    switch.disconnected("websocket error");
+  }
+  let err = match err {
+      openlimits::errors::OpenLimitsError::UnkownResponse(e) => openlimits::errors::OpenLimitsError::UnkownResponse(e.clone()),
+      openlimits::errors::OpenLimitsError::NotParsableResponse(e) => openlimits::errors::OpenLimitsError::NotParsableResponse(e.clone()),
+      openlimits::errors::OpenLimitsError::MissingParameter(e) => openlimits::errors::OpenLimitsError::MissingParameter(e.clone()),
+      openlimits::errors::OpenLimitsError::AssetNotFound() => openlimits::errors::OpenLimitsError::AssetNotFound(),
+      openlimits::errors::OpenLimitsError::NoApiKeySet() => openlimits::errors::OpenLimitsError::NoApiKeySet(),
+      openlimits::errors::OpenLimitsError::InternalServerError() => openlimits::errors::OpenLimitsError::InternalServerError(),
+      openlimits::errors::OpenLimitsError::ServiceUnavailable() => openlimits::errors::OpenLimitsError::ServiceUnavailable(),
+      openlimits::errors::OpenLimitsError::Unauthorized() => openlimits::errors::OpenLimitsError::Unauthorized(),
+      openlimits::errors::OpenLimitsError::SymbolNotFound() => openlimits::errors::OpenLimitsError::SymbolNotFound(),
+      openlimits::errors::OpenLimitsError::SocketError() => openlimits::errors::OpenLimitsError::SocketError(),
+      openlimits::errors::OpenLimitsError::WebSocketMessageNotSupported() => openlimits::errors::OpenLimitsError::WebSocketMessageNotSupported(),
+      openlimits::errors::OpenLimitsError::GetTimestampFailed() => openlimits::errors::OpenLimitsError::GetTimestampFailed(),
+      openlimits::errors::OpenLimitsError::PoisonError() => openlimits::errors::OpenLimitsError::PoisonError(),
+      _ => openlimits::errors::OpenLimitsError::SocketError(),
+  };
+
+  // The callback thread is gone once the client is closed, nothing is listening anymore
+  if reporter_tx.send(JavaReportBackMsg::Error(err, market)).is_err() {
+    log::debug!("Dropping error of {}, the callback thread has stopped", label);
+  }
+}
 
-  env.new_object(cls_resp, "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;)V", ctor_args)
+/// Starts the thread reading the account of a stream. It ends with the websocket thread, which holds the only
+/// sender of its commands besides the websocket subscriptions announcing changes.
+fn start_account_thread(
+  poller: AccountPoller,
+  reporter_tx: CallbackSender<JavaReportBackMsg>,
+  metrics: Arc<Metrics>,
+  poison: PoisonFlag
+) -> tokio::sync::mpsc::UnboundedSender<AccountCmd> {
+  let (account_tx, account_rx) = tokio::sync::mpsc::unbounded_channel::<AccountCmd>();
+  std::thread::spawn(move || {
+    let panic_tx = reporter_tx.clone();
+    let result = panics::catch(move || {
+      let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(rt) => rt,
+        Err(err) => {
+          reporter_tx.send(JavaReportBackMsg::Error(openlimits::errors::OpenLimitsError::IoError(err), None)).ok();
+          return;
+        }
+      };
+      rt.block_on(poller.run(account_rx, |event| {
+        let msg = match event {
+          Ok(update) => {
+            metrics.record_subscription_message(&update.label());
+            JavaReportBackMsg::Account(update, Instant::now())
+          },
+          Err((err, market)) => JavaReportBackMsg::Error(err, market),
+        };
+        // Nobody listens anymore once the callback thread has stopped
+        reporter_tx.send(msg).is_ok()
+      }));
+    });
+    if let Err(msg) = result {
+      poison.poison();
+      log::error!("Account thread panicked: {}", msg);
+      panic_tx.send(JavaReportBackMsg::Panic(msg)).ok();
+    }
+  });
+  account_tx
 }
 
 type SubResult = std::result::Result<openlimits::exchange::traits::stream::CallbackHandle, openlimits::errors::OpenLimitsError>;
 type SubChannel = tokio::sync::oneshot::Sender<SubResult>;
+type AccountSubChannel = tokio::sync::oneshot::Sender<Result<(), openlimits::errors::OpenLimitsError>>;
 enum SubthreadCmd {
   Sub(Subscription, SubChannel),
+  // Read by the account thread, which answers with the websocket subscriptions announcing changes
+  SubAccount(AccountStream, AccountSubChannel),
+  PushAccount(AccountStream, Vec<Subscription>, AccountSubChannel),
   SimulateError,
+  // Feeds generated messages of a market through the callback thread, for soak tests
+  SimulateMessages(String, u64),
   Disconnect
 }
 
 enum JavaReportBackMsg {
   Disconnect,
-  Message(OpenLimitsWebSocketMessage, String),
-  Error(openlimits::errors::OpenLimitsError)
+  Message(OpenLimitsWebSocketMessage, String, Instant),
+  // Never dropped, a missed order update or fill would not be repeated
+  Account(AccountUpdate, Instant),
+  // The market of the failed subscription, if known
+  Error(openlimits::errors::OpenLimitsError, Option<String>),
+  // A panic caught on the websocket thread or inside a subscription handler
+  Panic(String),
+  // An error raised by a fault rule instead of delivering a message of the market
+  Fault(&'static InjectableError, String)
 }
 
-fn init_ws(env: JNIEnv, _class: JClass, cli: JObject, init_params: InitAnyExchange) -> OpenLimitsJavaResult<()> {
-  let client = env.new_global_ref(cli)?;
-  
-  let (sub_request_tx, mut sub_rx) = tokio::sync::mpsc::unbounded_channel::<SubthreadCmd>();
-  env.set_rust_field(cli, "_sub_tx", sub_request_tx.clone())?;
-  let (msg_request_tx, msg_rx) = std::sync::mpsc::sync_channel::<JavaReportBackMsg>(100);
-  let main_thread_message_request_tx = msg_request_tx.clone();
+impl QueuedMessage for JavaReportBackMsg {
+  fn is_droppable(&self) -> bool {
+    matches!(self, JavaReportBackMsg::Message(..))
+  }
 
-  let thread_manager = ThreadManager {
-    msg_request_tx: msg_request_tx.clone(),
-    sub_request_tx
-  };
-  env.set_rust_field(cli, "_thread_manager", thread_manager)?;
+  fn conflation_key(&self) -> Option<&str> {
+    match self {
+      JavaReportBackMsg::Message(OpenLimitsWebSocketMessage::OrderBook(_), market, _) => Some(market),
+      _ => None
+    }
+  }
+}
 
-  let jvm = env.get_java_vm()?;
-  let runtime: MutexGuard<tokio::runtime::Runtime> = env.get_rust_field(cli, "_runtime")?;
-  
-  // Signal used to sync initializating of the callback thread
-  let (finish_tx, finish_rx) = tokio::sync::oneshot::channel::<OpenLimitsJavaResult<()>>();
-  std::thread::spawn(move|| {
-    let env = match jvm.attach_current_thread(){
-      Err(error) => {
-        finish_tx.send(Err(OpenlimitsJavaError::JNIError(error))).expect("Failed to signal back client initialization status");
-        return
-      },
-      Ok(e) => e
-    };
+/// Tells Java about a panic that took down the callback thread. No more events will be delivered, so this is
+/// followed by `onDisconnect`.
+fn report_callback_thread_panic(jvm: &JavaVM, client: &GlobalRef, exchange: &'static str, msg: String) {
+  let result = jvm.attach_current_thread().and_then(|env| {
+    // Whatever was pending when the thread unwound would make every call below fail
+    if env.exception_check()? {
+      env.exception_describe()?;
+      env.exception_clear()?;
+    }
+    let details = ErrorDetails { exchange: Some(exchange), ..Default::default() };
+    let exception = new_exception(&env, "io/nash/openlimits/NativePanic", msg, details, "subscribe", None)?;
+    env.call_method(client.as_obj(), "onError", "(Lio/nash/openlimits/OpenLimitsException;)V", &[exception.into()])?;
+    env.call_method(client.as_obj(), "onDisconnect", "()V", &[])?;
+    Ok(())
+  });
+  if let Err(err) = result {
+    log::error!("Failed to report panic of the callback thread: {}", err);
+  }
+}
 
-    let call = move || -> OpenLimitsJavaResult<(jni::AttachGuard, JMethodID, JMethodID, JMethodID, JMethodID, JMethodID)> {
-      let event_handler_cls = env.find_class(EVENT_HANDLER_CLS_NAME)?;
-      let on_trades = env.get_method_id(event_handler_cls, "onTrades", "(Ljava/lang/String;[Lio/nash/openlimits/Trade;)V")?;
-      let on_orderbook = env.get_method_id(event_handler_cls, "onOrderbook", "(Lio/nash/openlimits/OrderbookResponse;)V")?;
-      let on_error = env.get_method_id(event_handler_cls, "onError", "(Lio/nash/openlimits/OpenLimitsException;)V")?;
-      let on_disconnect = env.get_method_id(event_handler_cls, "onDisconnect", "()V")?;
-      let on_ping = env.get_method_id(event_handler_cls, "onPing", "()V")?;
+/// Forgets the websocket threads of a client while leaving the REST client untouched. Does nothing if the client
+/// has been closed or opened a new stream since.
+fn clear_stream(env: &JNIEnv, cli: JObject, stream_id: u64) -> OpenLimitsJavaResult<()> {
+  // take_rust_field holds the monitor too, so closeClient cannot free the fields while they are locked here
+  let _monitor = env.lock_obj(cli)?;
+  let mut thread_manager = env.get_rust_field::<_,_,Option<ThreadManager>>(cli, "_thread_manager")?;
+  if thread_manager.as_ref().map(|t| t.stream_id) != Some(stream_id) {
+    return Ok(());
+  }
+  *thread_manager = None;
+  *env.get_rust_field::<_,_,Option<StreamSender>>(cli, "_sub_tx")? = None;
+  Ok(())
+}
 
-      Ok((env, on_trades, on_orderbook, on_error, on_disconnect, on_ping))
-    };
+fn client_options<'a>(env: &'a JNIEnv, cli: &'a JObject) -> OpenLimitsJavaResult<JObject<'a>> {
+  get_object(env, cli, "config", EXCHANGE_CLIENT_CONFIG_CLS_NAME)
+    .map_err(OpenlimitsJavaError::InvalidArgument)?
+    .ok_or_else(|| OpenlimitsJavaError::InvalidArgument(String::from("Could not find non-null field config")))
+}
 
-    let (env, on_trades, on_orderbook, on_error, on_disconnect, on_ping) = match call() {
-      Ok(res) => res,
-      Err(err) => {
-        finish_tx.send(Err(err)).expect("Failed to signal back client initialization status");
-        return;
-      }
-    };
+/// Opens the websocket of a client unless it is open already. Returns whether a new one was opened.
+fn ensure_stream(env: JNIEnv, _class: JClass, cli: JObject) -> OpenLimitsJavaResult<bool> {
+  // Held until the stream is open, concurrent first subscriptions would open a websocket each and drop all but one.
+  // Not the client's monitor, that would stall every other call of the client meanwhile.
+  let _opening = env.get_rust_field::<_,_,()>(cli, "_stream_lock")?;
+  if env.get_rust_field::<_,_,Option<ThreadManager>>(cli, "_thread_manager")?.is_some() {
+    return Ok(false);
+  }
+  let init_params = env.get_rust_field::<_,_,SecretParams>(cli, "_config")?.clone();
+  open_stream(env, _class, cli, stream_params(&env, cli, init_params)?)?;
+  Ok(true)
+}
 
-    let raise_exception = || {
-      // These two excepts would usually signify the JVM is in some really weird state
-      if env.exception_check().expect("Cannot get exception state of JVM") {
-        return;
-      }
-      env.throw("Aborting execution").expect("Failed to raise exception");
-    };
+fn stream_params(env: &JNIEnv, cli: JObject, init_params: SecretParams) -> OpenLimitsJavaResult<StreamParams> {
+  let conf = client_options(env, &cli)?;
+  if get_streaming_mode(env, &conf).map_err(OpenlimitsJavaError::InvalidArgument)? == StreamingMode::Disabled {
+    return Err(OpenlimitsJavaError::SubscribeException(String::from("Streaming is disabled for this REST only client")));
+  }
+  Ok(StreamParams {
+    init_params,
+    metrics: env.get_rust_field::<_,_,Arc<Metrics>>(cli, "_metrics")?.clone(),
+    poison: env.get_rust_field::<_,_,PoisonFlag>(cli, "_poisoned")?.clone(),
+    faults: env.get_rust_field::<_,_,Arc<FaultInjector>>(cli, "_faults")?.clone(),
+    queue_options: get_queue_options(env, &conf).map_err(OpenlimitsJavaError::InvalidArgument)?,
+    direct_orderbooks: get_boolean_field(env, &conf, "directOrderbooks").map_err(OpenlimitsJavaError::InvalidArgument)?,
+    scales: client_scales(env, cli)?,
+    account_poll_interval: get_account_poll_interval(env, &conf).map_err(OpenlimitsJavaError::InvalidArgument)?,
+    tracker: client_tracker(env, cli)?,
+    positions: client_positions(env, cli)?,
+    risk: client_risk(env, cli)?,
+    dead_mans_switch: client_switch_trigger(env, cli)?,
+  })
+}
 
-    if finish_tx.send(Ok(())).is_err() {
-      raise_exception();
-      return;
-    };
-    
-    loop {
-      let msg = msg_rx.recv();
-      let (msg, market_str) = match msg {
-        Ok(JavaReportBackMsg::Message(msg, market)) => (msg, market),
-        Ok(JavaReportBackMsg::Disconnect) => {
-          break;
-        },
-        Ok(JavaReportBackMsg::Error(err)) => {
-          let s = map_openlimits_error_class(&err);
-          let msg = format!("{:?}", err);
-          let client_inst = client.as_obj();
-          let call = || -> jni::errors::Result<JValue> {
-            let msg = env.new_string(msg)?;
-            let cls = env.find_class(s)?;
-            let inst = env.new_object(cls, "(Ljava/lang/String;)V", &[msg.into()])?;
-            env.call_method_unchecked(
-              client_inst,
-              on_error,
-              jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
-              &[inst.into()]
-            )
-          };
-          if call().is_err() {
-            raise_exception();
-            continue;
+fn stream_sender(env: &JNIEnv, cli: JObject) -> OpenLimitsJavaResult<Option<StreamSender>> {
+  // Cloned so the lock is not held while waiting for the websocket thread
+  Ok(env.get_rust_field::<_,_,Option<StreamSender>>(cli, "_sub_tx")?.clone())
+}
+
+/// Everything the websocket threads share with the rest of the client.
+struct StreamParams {
+  init_params: SecretParams,
+  metrics: Arc<Metrics>,
+  poison: PoisonFlag,
+  faults: Arc<FaultInjector>,
+  queue_options: QueueOptions,
+  direct_orderbooks: bool,
+  scales: Option<Arc<MarketScales>>,
+  account_poll_interval: std::time::Duration,
+  tracker: Option<Arc<OrderTracker>>,
+  positions: Option<Arc<PositionTracker>>,
+  // Fed the reference prices of the price band and of market order notionals
+  risk: Option<Arc<RiskChecker>>,
+  // Told when the websocket is lost
+  dead_mans_switch: Option<SwitchTrigger>,
+}
+
+fn open_stream(env: JNIEnv, _class: JClass, cli: JObject, params: StreamParams) -> OpenLimitsJavaResult<()> {
+  let result = init_ws(env, _class, cli, params);
+  if result.is_err() {
+    // Dropping the thread manager stops whichever thread did start
+    let _monitor = env.lock_obj(cli)?;
+    *env.get_rust_field::<_,_,Option<ThreadManager>>(cli, "_thread_manager")? = None;
+    *env.get_rust_field::<_,_,Option<StreamSender>>(cli, "_sub_tx")? = None;
+  }
+  result
+}
+
+fn init_ws(env: JNIEnv, _class: JClass, cli: JObject, params: StreamParams) -> OpenLimitsJavaResult<()> {
+  let StreamParams { init_params, metrics, poison, faults, queue_options, direct_orderbooks, scales, account_poll_interval, tracker, positions, risk, dead_mans_switch } = params;
+  let client = env.new_global_ref(cli)?;
+  
+  let (sub_request_tx, mut sub_rx) = tokio::sync::mpsc::unbounded_channel::<SubthreadCmd>();
+  *env.get_rust_field::<_,_,Option<StreamSender>>(cli, "_sub_tx")? = Some(sub_request_tx.clone());
+  // Hands the websocket subscriptions of account streams back to the websocket thread
+  let account_reply_tx = sub_request_tx.clone();
+  let (msg_request_tx, msg_rx) = callback_queue::<JavaReportBackMsg>(queue_options, metrics.clone());
+  let main_thread_message_request_tx = msg_request_tx.clone();
+
+  let stream_id = NEXT_STREAM_ID.fetch_add(1, Ordering::Relaxed);
+  let thread_manager = ThreadManager {
+    stream_id,
+    msg_request_tx: msg_request_tx.clone(),
+    sub_request_tx
+  };
+  *env.get_rust_field::<_,_,Option<ThreadManager>>(cli, "_thread_manager")? = Some(thread_manager);
+
+  let jvm = env.get_java_vm()?;
+  let runtime: MutexGuard<tokio::runtime::Runtime> = env.get_rust_field(cli, "_runtime")?;
+  let callback_metrics = metrics.clone();
+  let exchange = exchange_name(init_params.params());
+  
+  // Signal used to sync initializating of the callback thread
+  let (finish_tx, finish_rx) = tokio::sync::oneshot::channel::<OpenLimitsJavaResult<()>>();
+  let panic_jvm = env.get_java_vm()?;
+  let panic_client = client.clone();
+  let callback_poison = poison.clone();
+  let callback_tracker = tracker.clone();
+  let callback_positions = positions.clone();
+  std::thread::spawn(move|| {
+    let result = panics::catch(move || {
+      let metrics = callback_metrics;
+      let env = match jvm.attach_current_thread(){
+        Err(error) => {
+          if finish_tx.send(Err(OpenlimitsJavaError::JNIError(error))).is_err() {
+            log::error!("Failed to signal back client initialization status");
           }
-          continue;
+          return
         },
-        Err(_) => {
-          let s = "io/nash/openlimits/SocketError";
-          let msg = format!("Failed to receive message");
-          let client_inst = client.as_obj();
-          let call = || -> jni::errors::Result<JValue> {
-            let msg = env.new_string(msg)?;
-            let cls = env.find_class(s)?;
-            let inst = env.new_object(cls, "(Ljava/lang/String;)V", &[msg.into()])?;
-            env.call_method_unchecked(
-              client_inst,
-              on_error,
-              jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
-              &[inst.into()]
-            )
-          };
-          if call().is_err() {
-            raise_exception();
-            continue;
+        Ok(e) => e
+      };
+
+      let call = move || -> OpenLimitsJavaResult<(jni::AttachGuard, JMethodID, JMethodID, JMethodID, JMethodID, JMethodID, JMethodID)> {
+        let event_handler_cls = env.find_class(EVENT_HANDLER_CLS_NAME)?;
+        let on_trades = env.get_method_id(event_handler_cls, "onTrades", "(Ljava/lang/String;[Lio/nash/openlimits/Trade;)V")?;
+        let on_orderbook = env.get_method_id(event_handler_cls, "onOrderbook", "(Lio/nash/openlimits/OrderbookResponse;)V")?;
+        let on_error = env.get_method_id(event_handler_cls, "onError", "(Lio/nash/openlimits/OpenLimitsException;)V")?;
+        let on_disconnect = env.get_method_id(event_handler_cls, "onDisconnect", "()V")?;
+        let on_ping = env.get_method_id(event_handler_cls, "onPing", "()V")?;
+        let on_backpressure = env.get_method_id(event_handler_cls, "onBackpressure", "(J)V")?;
+
+        Ok((env, on_trades, on_orderbook, on_error, on_disconnect, on_ping, on_backpressure))
+      };
+
+      let (env, on_trades, on_orderbook, on_error, on_disconnect, on_ping, on_backpressure) = match call() {
+        Ok(res) => res,
+        Err(err) => {
+          if finish_tx.send(Err(err)).is_err() {
+            log::error!("Failed to signal back client initialization status");
           }
-          continue;
-        },
+          return;
+        }
+      };
+      let mut direct = match DirectOrderbooks::load(&env, direct_orderbooks) {
+        Ok(direct) => direct,
+        Err(err) => {
+          if finish_tx.send(Err(err)).is_err() {
+            log::error!("Failed to signal back client initialization status");
+          }
+          return;
+        }
+      };
+      let account_callbacks = match AccountCallbacks::load(&env, callback_tracker, callback_positions.clone()) {
+        Ok(callbacks) => callbacks,
+        Err(err) => {
+          if finish_tx.send(Err(err)).is_err() {
+            log::error!("Failed to signal back client initialization status");
+          }
+          return;
+        }
       };
 
-      match msg {
-        OpenLimitsWebSocketMessage::Trades(trades) => {
-          match vec_to_jobject(&env, TRADE_CLS_NAME, trades.clone(), trade_to_jobject) {
-            Ok(trades) => {
-
-              let call = || -> jni::errors::Result<JValue> {
-                let s = env.new_string(market_str)?;
-                env.call_method_unchecked(
-                  client.as_obj(),
-                  on_trades,
-                  jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
-                  &[s.into(), trades.into()]
-                )
-              };
-
-              if call().is_err() {
-                raise_exception();
-                continue;
-              }
-            },
-            Err(_) => {
-              raise_exception();
-              continue;
-            }
-          };
-          continue
-        },
-        OpenLimitsWebSocketMessage::OrderBook(orderbook) => {
-          let call = || -> jni::errors::Result<JValue> {
-            let s = env.new_string(market_str)?;
-            let order_book = orderbook_resp_to_jobject(&env, orderbook.clone(),s.into())?;
-            env.call_method_unchecked(
-              client.as_obj(),
-              on_orderbook,
-              jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
-              &[order_book.into()]
-            )
-          };
+      let raise_exception = || {
+        // Failing here means the JVM is in some really weird state, nothing left to tell it
+        match env.exception_check() {
+          Ok(true) => {},
+          Ok(false) => if let Err(err) = env.throw("Aborting execution") {
+            log::error!("Failed to raise exception on the callback thread: {}", err);
+          },
+          Err(err) => log::error!("Cannot get exception state of JVM: {}", err),
+        }
+      };
 
-          if call().is_err() {
-            raise_exception();
-            continue; 
+      // Hands an error that only affects one message to the onError callbacks
+      let report_error = |class_name: &'static str, msg: String, market: Option<String>| {
+        metrics.record_error(class_name);
+        let details = ErrorDetails { exchange: Some(exchange), ..Default::default() };
+        let call = || -> jni::errors::Result<JValue> {
+          let inst = new_exception(&env, class_name, msg, details, "subscribe", market)?;
+          env.call_method_unchecked(
+            client.as_obj(),
+            on_error,
+            jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
+            &[inst.into()]
+          )
+        };
+        if call().is_err() {
+          raise_exception();
+        }
+      };
+
+      if finish_tx.send(Ok(())).is_err() {
+        raise_exception();
+        return;
+      };
+    
+      loop {
+        let msg = msg_rx.recv();
+        // This thread never returns to Java, so the references a message creates are only freed with its frame
+        let _frame = match LocalFrame::push(&env, DISPATCH_FRAME_CAPACITY) {
+          Ok(frame) => Some(frame),
+          Err(err) => {
+            log::warn!("Failed to push local reference frame: {}", err);
+            env.exception_clear().ok();
+            None
           }
-          continue; 
-        },
-        OpenLimitsWebSocketMessage::Ping => {
+        };
+        let dropped = msg_rx.take_dropped();
+        if dropped > 0 {
           let res = env.call_method_unchecked(
             client.as_obj(),
-            on_ping,
+            on_backpressure,
             jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
-            &[]
+            &[JValue::Long(dropped as i64)]
           );
-
           if res.is_err() {
             raise_exception();
-            continue;
           }
-          continue; 
-        },
-        OpenLimitsWebSocketMessage::OrderBookDiff(orderbook) => {
-          let call = || -> jni::errors::Result<JValue> {
-            let s = env.new_string(market_str)?;
-            let orderbook = orderbook_resp_to_jobject(&env, orderbook.clone(),s.into())?;
-            env.call_method_unchecked(
+        }
+        let (msg, market_str, enqueued_at) = match msg {
+          Ok(JavaReportBackMsg::Message(msg, market, enqueued_at)) => {
+            (msg, market, enqueued_at)
+          },
+          Ok(JavaReportBackMsg::Disconnect) => {
+            break;
+          },
+          Ok(JavaReportBackMsg::Account(update, enqueued_at)) => {
+            let operation = update.operation();
+            let market = update.market();
+            let res = account_callbacks.dispatch(&env, client.as_obj(), update, scales.as_deref());
+            metrics.record_latency(operation, enqueued_at.elapsed());
+            match res {
+              Ok(()) => {},
+              Err(OpenlimitsJavaError::FixedPoint(msg)) => report_error("io/nash/openlimits/FixedPointError", msg, market),
+              Err(_) => raise_exception(),
+            }
+            continue;
+          },
+          Ok(JavaReportBackMsg::Error(err, market)) => {
+            let s = map_openlimits_error_class(&err);
+            metrics.record_error(s);
+            let msg = format!("{:?}", err);
+            let mut details = openlimits_error_details(&err);
+            details.exchange = Some(exchange);
+            let client_inst = client.as_obj();
+            let call = || -> jni::errors::Result<JValue> {
+              let inst = new_exception(&env, s, msg, details, "subscribe", market)?;
+              env.call_method_unchecked(
+                client_inst,
+                on_error,
+                jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
+                &[inst.into()]
+              )
+            };
+            if call().is_err() {
+              raise_exception();
+              continue;
+            }
+            continue;
+          },
+          Ok(JavaReportBackMsg::Fault(error, market)) => {
+            let s = error.class_name;
+            metrics.record_error(s);
+            let mut details = injected_fault_details(error);
+            details.exchange = Some(exchange);
+            let client_inst = client.as_obj();
+            let call = || -> jni::errors::Result<JValue> {
+              let inst = new_exception(&env, s, OpenlimitsJavaError::InjectedFault(error).to_string(), details, "subscribe", Some(market))?;
+              env.call_method_unchecked(
+                client_inst,
+                on_error,
+                jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
+                &[inst.into()]
+              )
+            };
+            if call().is_err() {
+              raise_exception();
+            }
+            continue;
+          },
+          Ok(JavaReportBackMsg::Panic(msg)) => {
+            let s = "io/nash/openlimits/NativePanic";
+            metrics.record_error(s);
+            let details = ErrorDetails { exchange: Some(exchange), ..Default::default() };
+            let client_inst = client.as_obj();
+            let call = || -> jni::errors::Result<JValue> {
+              let inst = new_exception(&env, s, msg, details, "subscribe", None)?;
+              env.call_method_unchecked(
+                client_inst,
+                on_error,
+                jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
+                &[inst.into()]
+              )
+            };
+            if call().is_err() {
+              raise_exception();
+            }
+            continue;
+          },
+          Err(_) => {
+            let s = "io/nash/openlimits/SocketError";
+            metrics.record_error(s);
+            let msg = format!("Failed to receive message");
+            let details = ErrorDetails { exchange: Some(exchange), retryable: true, ..Default::default() };
+            let client_inst = client.as_obj();
+            let call = || -> jni::errors::Result<JValue> {
+              let inst = new_exception(&env, s, msg, details, "subscribe", None)?;
+              env.call_method_unchecked(
+                client_inst,
+                on_error,
+                jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
+                &[inst.into()]
+              )
+            };
+            if call().is_err() {
+              raise_exception();
+              continue;
+            }
+            continue;
+          },
+        };
+
+        if let (Some(positions), OpenLimitsWebSocketMessage::OrderBook(orderbook)) = (&callback_positions, &msg) {
+          positions.mark(&market_str, orderbook);
+        }
+        match (&risk, &msg) {
+          (Some(risk), OpenLimitsWebSocketMessage::OrderBook(orderbook)) => risk.mark(&market_str, orderbook),
+          (Some(risk), OpenLimitsWebSocketMessage::Trades(trades)) => risk.last_trades(&market_str, trades),
+          _ => {}
+        }
+        match msg {
+          OpenLimitsWebSocketMessage::Trades(trades) => {
+            match trades_to_jobject(&env, trades.clone(), scales.as_deref()) {
+              Ok(trades) => {
+
+                let call = || -> jni::errors::Result<JValue> {
+                  let s = env.new_string(market_str)?;
+                  env.call_method_unchecked(
+                    client.as_obj(),
+                    on_trades,
+                    jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
+                    &[s.into(), trades.into()]
+                  )
+                };
+
+                let res = call();
+                metrics.record_latency("onTrades", enqueued_at.elapsed());
+                if res.is_err() {
+                  raise_exception();
+                  continue;
+                }
+              },
+              Err(OpenlimitsJavaError::FixedPoint(msg)) => {
+                report_error("io/nash/openlimits/FixedPointError", msg, Some(market_str));
+                continue;
+              },
+              Err(_) => {
+                raise_exception();
+                continue;
+              }
+            };
+            continue
+          },
+          OpenLimitsWebSocketMessage::OrderBook(orderbook) | OpenLimitsWebSocketMessage::OrderBookDiff(orderbook) if direct.is_some() => {
+            let direct = match direct.as_mut() {
+              Some(direct) => direct,
+              None => continue,
+            };
+            let res = direct.dispatch(&env, client.as_obj(), &market_str, &orderbook);
+            metrics.record_latency("onOrderbookView", enqueued_at.elapsed());
+            match res {
+              Ok(Ok(())) => {},
+              Ok(Err(msg)) => report_error("io/nash/openlimits/NotParsableResponse", msg, Some(market_str)),
+              Err(_) => raise_exception(),
+            }
+            continue
+          },
+          OpenLimitsWebSocketMessage::OrderBook(orderbook) => {
+            let call = || -> OpenLimitsJavaResult<JValue> {
+              let s = env.new_string(&market_str)?;
+              let order_book = any_orderbook_to_jobject(&env, orderbook.clone(), &market_str, s.into(), scales.as_deref())?;
+              Ok(env.call_method_unchecked(
+                client.as_obj(),
+                on_orderbook,
+                jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
+                &[order_book.into()]
+              )?)
+            };
+
+            let res = call();
+            metrics.record_latency("onOrderbook", enqueued_at.elapsed());
+            match res {
+              Ok(_) => {},
+              Err(OpenlimitsJavaError::FixedPoint(msg)) => report_error("io/nash/openlimits/FixedPointError", msg, Some(market_str)),
+              Err(_) => raise_exception(),
+            }
+            continue; 
+          },
+          OpenLimitsWebSocketMessage::Ping => {
+            let res = env.call_method_unchecked(
               client.as_obj(),
-              on_orderbook,
+              on_ping,
               jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
-              &[orderbook.into()]
-            )
-          };
-          if call().is_err() {
-            raise_exception();
-            continue;
-          }
-          continue
-        },
-      };
-    }
-    let res = env.call_method_unchecked(
-      client.as_obj(),
-      on_disconnect,
-      jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
-      &[]
-    );
-    if res.is_err() {
-      raise_exception();
+              &[]
+            );
+            metrics.record_latency("onPing", enqueued_at.elapsed());
+
+            if res.is_err() {
+              raise_exception();
+              continue;
+            }
+            continue; 
+          },
+          OpenLimitsWebSocketMessage::OrderBookDiff(orderbook) => {
+            let call = || -> OpenLimitsJavaResult<JValue> {
+              let s = env.new_string(&market_str)?;
+              let orderbook = any_orderbook_to_jobject(&env, orderbook.clone(), &market_str, s.into(), scales.as_deref())?;
+              Ok(env.call_method_unchecked(
+                client.as_obj(),
+                on_orderbook,
+                jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
+                &[orderbook.into()]
+              )?)
+            };
+            let res = call();
+            metrics.record_latency("onOrderbook", enqueued_at.elapsed());
+            match res {
+              Ok(_) => {},
+              Err(OpenlimitsJavaError::FixedPoint(msg)) => report_error("io/nash/openlimits/FixedPointError", msg, Some(market_str)),
+              Err(_) => raise_exception(),
+            }
+            continue
+          },
+        };
+      }
+      if let Err(err) = clear_stream(&env, client.as_obj(), stream_id) {
+        log::warn!("Failed to release the closed stream: {}", err);
+      }
+      let res = env.call_method_unchecked(
+        client.as_obj(),
+        on_disconnect,
+        jni::signature::JavaType::Primitive(jni::signature::Primitive::Void),
+        &[]
+      );
+      if res.is_err() {
+        raise_exception();
+      }
+    });
+    if let Err(msg) = result {
+      callback_poison.poison();
+      log::error!("Callback thread panicked: {}", msg);
+      report_callback_thread_panic(&panic_jvm, &panic_client, exchange, msg);
     }
   });
 
@@ -695,125 +1839,259 @@ fn init_ws(env: JNIEnv, _class: JClass, cli: JObject, init_params: InitAnyExchan
 
   let jvm = env.get_java_vm()?;
   let (finish_tx, finish_rx) = tokio::sync::oneshot::channel::<OpenLimitsJavaResult<()>>();
+  let panic_report_tx = msg_request_tx.clone();
+  let sub_thread_poison = poison.clone();
+  let panic_switch = dead_mans_switch.clone();
+  let track_orders = tracker.is_some();
+  let position_markets = positions.as_ref().map(|positions| positions.markets()).unwrap_or_default();
   std::thread::spawn(move || {
-    let env = match jvm.attach_current_thread(){
-      Err(error) => {
-        if finish_tx.send(Err(OpenlimitsJavaError::JNIError(error))).is_err() {
-          msg_request_tx.clone().send(JavaReportBackMsg::Disconnect).expect("Failed to send message to callback thread");
-        };
-        return
-      },
-      Ok(e) => e
-    };
-    let raise_exception = || {
-      if env.exception_check().expect("Cannot get exception state of JVM") {
-        return;
-      }
-      env.throw_new("java/lang/RuntimeException", "Aborting execution").expect("Failed to raise exception");
-    };
+    let result = panics::catch(move || {
+      let env = match jvm.attach_current_thread(){
+        Err(error) => {
+          if finish_tx.send(Err(OpenlimitsJavaError::JNIError(error))).is_err() {
+            msg_request_tx.clone().send(JavaReportBackMsg::Disconnect).ok();
+          };
+          return
+        },
+        Ok(e) => e
+      };
+      let raise_exception = || {
+        match env.exception_check() {
+          Ok(true) => {},
+          Ok(false) => if let Err(err) = env.throw_new("java/lang/RuntimeException", "Aborting execution") {
+            log::error!("Failed to raise exception on the websocket thread: {}", err);
+          },
+          Err(err) => log::error!("Cannot get exception state of JVM: {}", err),
+        }
+      };
 
-    let call = move || -> OpenLimitsJavaResult<(tokio::runtime::Runtime, OpenLimitsWs<AnyWsExchange>)> {
-        let rt = tokio::runtime::Builder::new_current_thread()
-          .enable_all()
-          .build()
-          .map_err(|e| OpenlimitsJavaError::OpenLimitsError(openlimits::errors::OpenLimitsError::IoError(e)))
-          ?;
-        let client: OpenLimitsWs<AnyWsExchange> = rt.block_on(OpenLimitsWs::instantiate(init_params.clone()))?;
+      let account_params = init_params.clone();
+      let call = move || -> OpenLimitsJavaResult<(tokio::runtime::Runtime, OpenLimitsWs<AnyWsExchange>)> {
+          let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| OpenlimitsJavaError::OpenLimitsError(openlimits::errors::OpenLimitsError::IoError(e)))
+            ?;
+          let client: OpenLimitsWs<AnyWsExchange> = rt.block_on(OpenLimitsWs::instantiate(init_params.instantiate_params()))?;
 
-        Ok((rt, client))
-    };
+          Ok((rt, client))
+      };
 
-    let (rt, client) = match call() {
-      Ok(res) => res,
-      Err(err) => {
-        if finish_tx.send(Err(err)).is_err() {
-          // We failed to report to main thread that initialization has failed. Abort and attempt to shutdown callback thread
-          if msg_request_tx.clone().send(JavaReportBackMsg::Disconnect).is_err() {
-            raise_exception();
-          }
-        };
+      let (rt, client) = match call() {
+        Ok(res) => res,
+        Err(err) => {
+          if finish_tx.send(Err(err)).is_err() {
+            // We failed to report to main thread that initialization has failed. Abort and attempt to shutdown callback thread
+            if msg_request_tx.clone().send(JavaReportBackMsg::Disconnect).is_err() {
+              raise_exception();
+            }
+          };
+          return;
+        }
+      };
+      if finish_tx.send(Ok(())).is_err() {
+        // We failed to report to main thread that initialization has failed. Abort and attempt to shutdown callback thread
+        if msg_request_tx.clone().send(JavaReportBackMsg::Disconnect).is_err() {
+          raise_exception();
+        }
         return;
       }
-    };
-    if finish_tx.send(Ok(())).is_err() {
-      // We failed to report to main thread that initialization has failed. Abort and attempt to shutdown callback thread
-      if msg_request_tx.clone().send(JavaReportBackMsg::Disconnect).is_err() {
-        raise_exception();
-      }
-      return;
-    }
 
-    loop {
-      let subcmd = sub_rx.recv();
-      let next_msg = rt.block_on(subcmd);
-
-      match next_msg {
-        Some(thread_cmd) => {
-          match thread_cmd {
-            SubthreadCmd::SimulateError => {
-              if msg_request_tx.clone().send(JavaReportBackMsg::Error(openlimits::errors::OpenLimitsError::SocketError())).is_err() {
-                raise_exception();
-              }
+      // Announces changes of an account stream to the account thread
+      let subscribe_account = |stream: &AccountStream, sub: Subscription, account_tx: &tokio::sync::mpsc::UnboundedSender<AccountCmd>| -> SubResult {
+        let sub_reporter_tx = msg_request_tx.clone();
+        let handler_poison = poison.clone();
+        let handler_switch = dead_mans_switch.clone();
+        let account_tx = account_tx.clone();
+        let stream = stream.clone();
+        let label = subscription_label(&sub);
+        rt.block_on(client.subscribe(sub, move |resp| {
+          let result = panics::catch(|| match resp {
+            Ok(_) => {
+              account_tx.send(AccountCmd::Changed(stream.clone())).ok();
             },
-            SubthreadCmd::Disconnect => {
-              if msg_request_tx.clone().send(JavaReportBackMsg::Disconnect).is_err() {
-                raise_exception();
-              }
-              return;
-            },
-            SubthreadCmd::Sub(sub, writer) => {
-              let sub_reporter_tx = msg_request_tx.clone();
+            Err(err) => report_stream_error(err, &label, stream_market(&stream), handler_switch.as_ref(), &sub_reporter_tx),
+          });
+          if let Err(msg) = result {
+            handler_poison.poison();
+            log::error!("Handler of {} panicked: {}", label, msg);
+            sub_reporter_tx.send(JavaReportBackMsg::Panic(msg)).ok();
+          }
+        }))
+      };
 
-              let result = rt.block_on(client.subscribe(sub.clone(), move |resp| {
-                let resp = match resp {
-                  Ok(e) => e,
-                  Err(err) => {
-                    let err = match err {
-                        openlimits::errors::OpenLimitsError::UnkownResponse(e) => openlimits::errors::OpenLimitsError::UnkownResponse(e.clone()),
-                        openlimits::errors::OpenLimitsError::NotParsableResponse(e) => openlimits::errors::OpenLimitsError::NotParsableResponse(e.clone()),
-                        openlimits::errors::OpenLimitsError::MissingParameter(e) => openlimits::errors::OpenLimitsError::MissingParameter(e.clone()),
-                        openlimits::errors::OpenLimitsError::AssetNotFound() => openlimits::errors::OpenLimitsError::AssetNotFound(),
-                        openlimits::errors::OpenLimitsError::NoApiKeySet() => openlimits::errors::OpenLimitsError::NoApiKeySet(),
-                        openlimits::errors::OpenLimitsError::InternalServerError() => openlimits::errors::OpenLimitsError::InternalServerError(),
-                        openlimits::errors::OpenLimitsError::ServiceUnavailable() => openlimits::errors::OpenLimitsError::ServiceUnavailable(),
-                        openlimits::errors::OpenLimitsError::Unauthorized() => openlimits::errors::OpenLimitsError::Unauthorized(),
-                        openlimits::errors::OpenLimitsError::SymbolNotFound() => openlimits::errors::OpenLimitsError::SymbolNotFound(),
-                        openlimits::errors::OpenLimitsError::SocketError() => openlimits::errors::OpenLimitsError::SocketError(),
-                        openlimits::errors::OpenLimitsError::WebSocketMessageNotSupported() => openlimits::errors::OpenLimitsError::WebSocketMessageNotSupported(),
-                        openlimits::errors::OpenLimitsError::GetTimestampFailed() => openlimits::errors::OpenLimitsError::GetTimestampFailed(),
-                        openlimits::errors::OpenLimitsError::PoisonError() => openlimits::errors::OpenLimitsError::PoisonError(),
-                        _ => openlimits::errors::OpenLimitsError::SocketError(),
-                    };
+      // The order and position trackers follow the account for as long as the stream is open, subscribed or not
+      let mut account = match track_orders || !position_markets.is_empty() {
+        true => {
+          let mut poller = AccountPoller::new(account_params.clone(), account_poll_interval);
+          if track_orders {
+            poller.track_orders();
+          }
+          poller.track_fills(position_markets);
+          let subscriptions = poller.tracker_subscriptions();
+          let account_tx = start_account_thread(poller, msg_request_tx.clone(), metrics.clone(), poison.clone());
+          for (stream, sub) in subscriptions {
+            if let Err(err) = subscribe_account(&stream, sub, &account_tx) {
+              msg_request_tx.send(JavaReportBackMsg::Error(err, stream_market(&stream))).ok();
+            }
+          }
+          Some(account_tx)
+        },
+        false => None,
+      };
 
-                    // Not sure how to raise an JVM exception here. The subscription handlers have some odd traits
-                    sub_reporter_tx.send(JavaReportBackMsg::Error(err)).expect("Failed to send message to callback thread");
-                    return;
-                  }
-                };
-                let resp = match resp {
-                  WebSocketResponse::Generic(msg) => msg,
-                  WebSocketResponse::Raw(_) => {
-                    return;
+      loop {
+        let next_msg = rt.block_on(sub_rx.recv());
+
+        match next_msg {
+          Some(thread_cmd) => {
+            match thread_cmd {
+              SubthreadCmd::SimulateError => {
+                if let Some(switch) = &dead_mans_switch {
+                  switch.disconnected("simulated socket error");
+                }
+                if msg_request_tx.clone().send(JavaReportBackMsg::Error(openlimits::errors::OpenLimitsError::SocketError(), None)).is_err() {
+                  raise_exception();
+                }
+              },
+              SubthreadCmd::SimulateMessages(market, count) => {
+                for n in 0..count {
+                  let msg = JavaReportBackMsg::Message(synthetic_message(&market, n), market.clone(), Instant::now());
+                  if msg_request_tx.send(msg).is_err() {
+                    break;
                   }
+                }
+              },
+              SubthreadCmd::Disconnect => {
+                if msg_request_tx.clone().send(JavaReportBackMsg::Disconnect).is_err() {
+                  raise_exception();
+                }
+                return;
+              },
+              SubthreadCmd::SubAccount(stream, writer) => {
+                let account_tx = account.get_or_insert_with(|| {
+                  let poller = AccountPoller::new(account_params.clone(), account_poll_interval);
+                  start_account_thread(poller, msg_request_tx.clone(), metrics.clone(), poison.clone())
+                });
+                // Subscribed on this thread once the account thread has read the stream, without waiting for it here
+                let reply_tx = account_reply_tx.clone();
+                let reply_stream = stream.clone();
+                let reply: SubscribeReply = Box::new(move |result| match result {
+                  Ok(subscriptions) => {
+                    reply_tx.send(SubthreadCmd::PushAccount(reply_stream, subscriptions, writer)).ok();
+                  },
+                  Err(err) => {
+                    writer.send(Err(err)).ok();
+                  },
+                });
+                // A dropped writer fails the subscribe call, the next one starts a new account thread
+                if account_tx.send(AccountCmd::Subscribe(stream, reply)).is_err() {
+                  account = None;
+                }
+              },
+              SubthreadCmd::PushAccount(stream, subscriptions, writer) => {
+                let result = match &account {
+                  Some(account_tx) => subscriptions.into_iter()
+                    .try_for_each(|sub| subscribe_account(&stream, sub, account_tx).map(drop)),
+                  None => Ok(()),
                 };
-                let market = match sub.clone() {
-                  Subscription::Ticker(e) => e.clone(),
-                  Subscription::OrderBookUpdates(e) => e.clone(),
-                  Subscription::Trades(e) => e.clone(),
-                  _ => String::from("Unknown")
+                if writer.send(result).is_err() {
+                  raise_exception();
+                }
+              },
+              SubthreadCmd::Sub(sub, writer) => {
+                let sub_reporter_tx = msg_request_tx.clone();
+                let sub_metrics = metrics.clone();
+                let handler_poison = poison.clone();
+                let handler_faults = faults.clone();
+                let handler_switch = dead_mans_switch.clone();
+                // Started by the first latency rule that fires for the subscription
+                let delayed: OnceCell<DelayLine<JavaReportBackMsg>> = OnceCell::new();
+                let label = subscription_label(&sub);
+                let sub_market = match &sub {
+                  Subscription::Ticker(market) |
+                  Subscription::OrderBookUpdates(market) |
+                  Subscription::Trades(market) => Some(market.clone()),
+                  _ => None
                 };
 
-                // Not sure how to raise an JVM exception here. The subscription handlers have some odd traits
-                sub_reporter_tx.send(JavaReportBackMsg::Message(resp.clone(), market)).expect("Failed to send message to callback thread");
-              }));
-              if writer.send(result).is_err() {
-                raise_exception();
-              }
-            },
-          }
-        },
-        None => {}
+                let result = rt.block_on(client.subscribe(sub.clone(), move |resp| {
+                  let result = panics::catch(|| {
+                    let resp = match resp {
+                      Ok(e) => e,
+                      Err(err) => {
+                        report_stream_error(err, &label, sub_market.clone(), handler_switch.as_ref(), &sub_reporter_tx);
+                        return;
+                      }
+                    };
+                    let resp = match resp {
+                      WebSocketResponse::Generic(msg) => msg,
+                      WebSocketResponse::Raw(_) => {
+                        return;
+                      }
+                    };
+                    let market = match sub.clone() {
+                      Subscription::Ticker(e) => e.clone(),
+                      Subscription::OrderBookUpdates(e) => e.clone(),
+                      Subscription::Trades(e) => e.clone(),
+                      _ => String::from("Unknown")
+                    };
+
+                    sub_metrics.record_subscription_message(&label);
+                    let fired = if handler_faults.is_empty() {
+                      Faults::default()
+                    } else {
+                      handler_faults.fire(stream_operation(resp), Some(market.as_str()), true)
+                    };
+                    let msgs = if fired.disconnect {
+                      if let Some(switch) = &handler_switch {
+                        switch.disconnected("injected disconnect");
+                      }
+                      vec![JavaReportBackMsg::Disconnect]
+                    } else if let Some(error) = fired.error {
+                      vec![JavaReportBackMsg::Fault(error, market)]
+                    } else if fired.drop_message {
+                      return;
+                    } else if fired.duplicate_message {
+                      vec![
+                        JavaReportBackMsg::Message(resp.clone(), market.clone(), Instant::now()),
+                        JavaReportBackMsg::Message(resp.clone(), market, Instant::now()),
+                      ]
+                    } else {
+                      vec![JavaReportBackMsg::Message(resp.clone(), market, Instant::now())]
+                    };
+                    for msg in msgs {
+                      if fired.latency > std::time::Duration::from_secs(0) {
+                        let reporter_tx = sub_reporter_tx.clone();
+                        delayed.get_or_init(|| DelayLine::new(move |msg| reporter_tx.send(msg).is_ok())).hold(fired.latency, msg);
+                      } else if sub_reporter_tx.send(msg).is_err() {
+                        log::debug!("Dropping message of {}, the callback thread has stopped", label);
+                      }
+                    }
+                  });
+                  if let Err(msg) = result {
+                    handler_poison.poison();
+                    log::error!("Handler of {} panicked: {}", label, msg);
+                    sub_reporter_tx.send(JavaReportBackMsg::Panic(msg)).ok();
+                  }
+                }));
+                if writer.send(result).is_err() {
+                  raise_exception();
+                }
+              },
+            }
+          },
+          None => {}
+        }
+      }
+    });
+    if let Err(msg) = result {
+      sub_thread_poison.poison();
+      if let Some(switch) = panic_switch {
+        switch.disconnected("websocket thread panic");
       }
+      log::error!("Websocket thread panicked: {}", msg);
+      panic_report_tx.send(JavaReportBackMsg::Panic(msg)).ok();
     }
   });
 
@@ -821,7 +2099,7 @@ fn init_ws(env: JNIEnv, _class: JClass, cli: JObject, init_params: InitAnyExchan
   if wait_for_result.is_err() {
     // Send a disconnect signal to callback thread to exit cleanly
     if main_thread_message_request_tx.clone().send(JavaReportBackMsg::Disconnect).is_err() {
-      println!("Cannot shutdown listener threads");
+      log::error!("Cannot shutdown listener threads");
     };
   }
   return wait_for_result.map_err(|e| OpenlimitsJavaError::InitializeException(e.to_string()))?;
@@ -829,32 +2107,168 @@ fn init_ws(env: JNIEnv, _class: JClass, cli: JObject, init_params: InitAnyExchan
 
 #[no_mangle]
 pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_init(env: JNIEnv, _class: JClass, cli: JObject, conf: JObject) {
-  let call = move || -> OpenLimitsJavaResult<()> {
-    let init_params = get_options(&env, &conf).map_err(OpenlimitsJavaError::InvalidArgument)?;
-    let ws_params = init_params.clone();
-    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()
-      .map_err(|e| OpenlimitsJavaError::OpenLimitsError(openlimits::errors::OpenLimitsError::IoError(e)))?;
-    
-    let client_future = OpenLimits::instantiate(init_params.clone());
-    let client: AnyExchange = runtime.block_on(client_future)?;
+  // Handed to the client once it is complete. Until then it outlives a failure, whose exception may hold the
+  // credentials.
+  let mut secrets = None;
+  let call = || init_client(env, _class, cli, conf, &mut secrets);
+  let context = ErrorContext::new("init", cli);
+  let result = guarded(&env, &context, call);
+  if result.is_err() {
+    discard_client(&env, cli);
+  }
+  handle_void_result(env, &context, result);
+}
 
-    env.set_rust_field(cli, "_config", init_params)?;
-    env.set_rust_field(cli, "_client", client)?;
-    env.set_rust_field(cli, "_runtime", runtime)?;
-    init_ws(env, _class, cli, ws_params)?;
-    Ok(())
+fn init_client(env: JNIEnv, _class: JClass, cli: JObject, conf: JObject, secrets: &mut Option<RegisteredSecrets>) -> OpenLimitsJavaResult<()> {
+  let metrics = Arc::new(Metrics::default());
+  env.set_rust_field(cli, "_metrics", metrics.clone())?;
+  let poison = PoisonFlag::default();
+  env.set_rust_field(cli, "_poisoned", poison.clone())?;
+  let faults = Arc::new(FaultInjector::default());
+  env.set_rust_field(cli, "_faults", faults.clone())?;
+  // Resolve enum constants here, the callback thread cannot see the application class loader
+  java_enums(&env)?;
+  let init_params = SecretParams::new(get_options(&env, &conf).map_err(OpenlimitsJavaError::InvalidArgument)?);
+  // Before anything can fail with a message that may hold them
+  *secrets = Some(redact::register(init_params.secrets()));
+  let queue_options = get_queue_options(&env, &conf).map_err(OpenlimitsJavaError::InvalidArgument)?;
+  let streaming_mode = get_streaming_mode(&env, &conf).map_err(OpenlimitsJavaError::InvalidArgument)?;
+  let direct_orderbooks = get_boolean_field(&env, &conf, "directOrderbooks").map_err(OpenlimitsJavaError::InvalidArgument)?;
+  let fixed_point = get_boolean_field(&env, &conf, "fixedPoint").map_err(OpenlimitsJavaError::InvalidArgument)?;
+  let account_poll_interval = get_account_poll_interval(&env, &conf).map_err(OpenlimitsJavaError::InvalidArgument)?;
+  let track_orders = get_boolean_field(&env, &conf, "trackOrders").map_err(OpenlimitsJavaError::InvalidArgument)?;
+  let position_markets = get_string_array(&env, &conf, "positionMarkets").map_err(OpenlimitsJavaError::InvalidArgument)?;
+  let risk_limits = get_risk_limits(&env, &conf, track_orders, !position_markets.is_empty()).map_err(OpenlimitsJavaError::InvalidArgument)?;
+  let dead_mans_switch_options = get_dead_mans_switch_options(&env, &conf).map_err(OpenlimitsJavaError::InvalidArgument)?;
+  let journal = match get_audit_journal_options(&env, &conf).map_err(OpenlimitsJavaError::InvalidArgument)? {
+    Some((dir, max_bytes)) => Some(Arc::new(Journal::open(Path::new(&dir), max_bytes)
+      .map_err(|e| OpenlimitsJavaError::InitializeException(format!("Cannot open the audit journal in {}: {}", dir, e)))?)),
+    None => None,
+  };
+  let ws_params = init_params.clone();
+  let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()
+    .map_err(|e| OpenlimitsJavaError::OpenLimitsError(openlimits::errors::OpenLimitsError::IoError(e)))?;
+  
+  let client_future = OpenLimits::instantiate(init_params.instantiate_params());
+  let client: AnyExchange = runtime.block_on(client_future)?;
+  let pairs = if fixed_point || !position_markets.is_empty() {
+    runtime.block_on(client.retrieve_pairs())?
+  } else {
+    Vec::new()
+  };
+  let scales = if fixed_point { Some(Arc::new(MarketScales::new(&pairs))) } else { None };
+  let positions = if position_markets.is_empty() {
+    None
+  } else {
+    let positions = PositionTracker::new(&position_markets, &pairs).map_err(OpenlimitsJavaError::InvalidArgument)?;
+    // Every page of the history from the start, a position built from the latest page only would be wrong
+    for market in &position_markets {
+      positions.apply(runtime.block_on(account::trade_history(&client, market, Some(0)))?);
+    }
+    Some(Arc::new(positions))
+  };
+
+  env.set_rust_field(cli, "_config", init_params)?;
+  env.set_rust_field(cli, "_client", client)?;
+  env.set_rust_field(cli, "_runtime", runtime)?;
+  env.set_rust_field(cli, "_scales", scales.clone())?;
+  let tracker = if track_orders { Some(Arc::new(OrderTracker::default())) } else { None };
+  env.set_rust_field(cli, "_tracker", tracker.clone())?;
+  env.set_rust_field(cli, "_positions", positions.clone())?;
+  let risk = risk_limits.map(|limits| Arc::new(RiskChecker::new(limits)));
+  env.set_rust_field(cli, "_risk", risk.clone())?;
+  env.set_rust_field(cli, "_journal", journal.clone())?;
+  let dead_mans_switch = match dead_mans_switch_options {
+    Some((timeout, markets)) => {
+      // A client of its own, the switch must not wait for the lock of a REST call in progress
+      let switch_runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()
+        .map_err(|e| OpenlimitsJavaError::OpenLimitsError(openlimits::errors::OpenLimitsError::IoError(e)))?;
+      let switch_client: AnyExchange = switch_runtime.block_on(OpenLimits::instantiate(ws_params.instantiate_params()))?;
+      Some(DeadMansSwitch::new(switch_runtime, switch_client, markets, timeout, journal))
+    },
+    None => None,
   };
+  env.set_rust_field(cli, "_thread_manager", None::<ThreadManager>)?;
+  env.set_rust_field(cli, "_sub_tx", None::<StreamSender>)?;
+  env.set_rust_field(cli, "_stream_lock", ())?;
+  let switch_trigger = dead_mans_switch.as_ref().map(DeadMansSwitch::trigger);
+  if streaming_mode == StreamingMode::Eager {
+    let params = StreamParams {
+      init_params: ws_params, metrics, poison, faults, queue_options, direct_orderbooks, scales, account_poll_interval, tracker, positions, risk,
+      dead_mans_switch: switch_trigger
+    };
+    open_stream(env, _class, cli, params)?;
+  }
+  if let Some(registered) = secrets.take() {
+    env.set_rust_field(cli, "_secrets", registered)?;
+  }
+  // Last, a switch left running by a failed init would cancel the orders of the next client with the same
+  // credentials
+  let dead_mans_switch = dead_mans_switch.map(|mut switch| {
+    switch.start();
+    switch
+  });
+  env.set_rust_field(cli, "_dead_mans_switch", dead_mans_switch)?;
+  Ok(())
+}
 
-  handle_void_result(env, call());
+// A constructor that throws is never closed, so whatever init set on the client is freed right away. Fields that
+// weren't set yet are skipped.
+fn discard_client(env: &JNIEnv, cli: JObject) {
+  // Taking a poisoned rust field frees it while Java still points at it, see closeClient
+  if client_is_poisoned(env, cli) {
+    if let Ok(mut config) = env.get_rust_field::<_,_,SecretParams>(cli, "_config") {
+      config.scrub();
+    }
+    log::warn!("Leaking the native state of an ExchangeClient that panicked in init");
+    return;
+  }
+  env.take_rust_field::<_,_,Option<DeadMansSwitch>>(cli, "_dead_mans_switch").ok();
+  env.take_rust_field::<_,_,Option<ThreadManager>>(cli, "_thread_manager").ok();
+  env.take_rust_field::<_,_,Option<StreamSender>>(cli, "_sub_tx").ok();
+  env.take_rust_field::<_,_,()>(cli, "_stream_lock").ok();
+  env.take_rust_field::<_,_,AnyExchange>(cli, "_client").ok();
+  env.take_rust_field::<_,_,SecretParams>(cli, "_config").ok();
+  env.take_rust_field::<_,_,tokio::runtime::Runtime>(cli, "_runtime").ok();
+  env.take_rust_field::<_,_,Arc<Metrics>>(cli, "_metrics").ok();
+  env.take_rust_field::<_,_,PoisonFlag>(cli, "_poisoned").ok();
+  env.take_rust_field::<_,_,Arc<FaultInjector>>(cli, "_faults").ok();
+  env.take_rust_field::<_,_,Option<Arc<MarketScales>>>(cli, "_scales").ok();
+  env.take_rust_field::<_,_,Option<Arc<OrderTracker>>>(cli, "_tracker").ok();
+  env.take_rust_field::<_,_,Option<Arc<PositionTracker>>>(cli, "_positions").ok();
+  env.take_rust_field::<_,_,Option<Arc<RiskChecker>>>(cli, "_risk").ok();
+  env.take_rust_field::<_,_,Option<Arc<Journal>>>(cli, "_journal").ok();
+  env.take_rust_field::<_,_,RegisteredSecrets>(cli, "_secrets").ok();
 }
 
+
 #[no_mangle]
 pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_subscribe(env: JNIEnv, _class: JClass,  cli: JObject, sub: JObject) {
   let call = move || -> OpenLimitsJavaResult<()> {
-    let sub_request_tx: MutexGuard<tokio::sync::mpsc::UnboundedSender<SubthreadCmd>> = env.get_rust_field(cli, "_sub_tx")?;
+    match get_streaming_mode(&env, &client_options(&env, &cli)?).map_err(OpenlimitsJavaError::InvalidArgument)? {
+      StreamingMode::Lazy => {
+        ensure_stream(env, _class, cli)?;
+      },
+      StreamingMode::Disabled => {
+        return Err(OpenlimitsJavaError::SubscribeException(String::from("Streaming is disabled for this REST only client")));
+      },
+      StreamingMode::Eager => {}
+    }
+    let sub_request_tx = stream_sender(&env, cli)?
+      .ok_or_else(|| OpenlimitsJavaError::SubscribeException(String::from("Websocket is disconnected, call reconnect() first")))?;
     let runtime: MutexGuard<tokio::runtime::Runtime> = env.get_rust_field(cli, "_runtime")?;
     let sub = get_subscription(&env, &sub).map_err(OpenlimitsJavaError::InvalidArgument)?;
 
+    let sub = match sub {
+      StreamSubscription::Public(sub) => sub,
+      StreamSubscription::Account(stream) => {
+        let (finish_tx, finish_rx) = tokio::sync::oneshot::channel();
+        sub_request_tx.send(SubthreadCmd::SubAccount(stream, finish_tx))
+          .map_err(|e| OpenlimitsJavaError::SubscribeException(e.to_string()))?;
+        runtime.block_on(finish_rx).map_err(|e| OpenlimitsJavaError::SubscribeException(e.to_string()))??;
+        return Ok(());
+      }
+    };
 
     let (finish_tx, finish_rx) = tokio::sync::oneshot::channel::<SubResult>();
     match sub_request_tx.send(SubthreadCmd::Sub(sub, finish_tx)) {
@@ -873,73 +2287,473 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_subscribe(env: JNI
 
     Ok(())
   };
-
-  handle_void_result(env, call());
+
+  let context = ErrorContext::with_request("subscribe", cli, sub);
+  handle_void_result(env, &context, guarded(&env, &context, call));
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_disconnect(env: JNIEnv, _class: JClass,  cli: JObject) {
+  let call = move || -> OpenLimitsJavaResult<()> {
+    // Released right away so a reconnect() that follows cannot race the callback thread. Dropping the thread
+    // manager stops both threads, after which onDisconnect is called.
+    let _monitor = env.lock_obj(cli)?;
+    env.get_rust_field::<_,_,Option<ThreadManager>>(cli, "_thread_manager")?.take();
+    env.get_rust_field::<_,_,Option<StreamSender>>(cli, "_sub_tx")?.take();
+    Ok(())
+  };
+  let context = ErrorContext::new("disconnect", cli);
+  handle_void_result(env, &context, guarded(&env, &context, call));
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_connect(env: JNIEnv, _class: JClass,  cli: JObject) -> jboolean {
+  let call = move || -> OpenLimitsJavaResult<bool> {
+    ensure_stream(env, _class, cli)
+  };
+  let context = ErrorContext::new("connect", cli);
+  match guarded(&env, &context, call) {
+    Ok(opened) => opened as jboolean,
+    Err(err) => {
+      handle_void_result(env, &context, Err(err));
+      false as jboolean
+    }
+  }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_closeClient(env: JNIEnv, _class: JClass,  cli: JObject) {
+  let call = move || -> OpenLimitsJavaResult<()> {
+    // Its mutexes may be poisoned, and taking a poisoned rust field frees it while Java still points at it
+    if client_is_poisoned(&env, cli) {
+      // Wiped in place, the credentials leak no further than the rest
+      if let Ok(mut config) = env.get_rust_field::<_,_,SecretParams>(cli, "_config") {
+        config.scrub();
+      }
+      log::warn!("Leaking the native state of a poisoned ExchangeClient");
+      return Ok(());
+    }
+    // Stopped first, closing the client must not look like a lost connection to it
+    env.take_rust_field::<_,_,Option<DeadMansSwitch>>(cli, "_dead_mans_switch")?;
+    env.take_rust_field::<_,_,Option<ThreadManager>>(cli, "_thread_manager")?;
+    env.take_rust_field::<_,_,Option<StreamSender>>(cli, "_sub_tx" )?;
+    env.take_rust_field::<_,_,()>(cli, "_stream_lock")?;
+    env.take_rust_field::<_,_,AnyExchange>(cli, "_client")?;
+    // Dropping them wipes the credentials
+    env.take_rust_field::<_,_,SecretParams>(cli, "_config" )?;
+    env.take_rust_field::<_,_,tokio::runtime::Runtime>(cli, "_runtime")?;
+    env.take_rust_field::<_,_,Arc<Metrics>>(cli, "_metrics")?;
+    env.take_rust_field::<_,_,PoisonFlag>(cli, "_poisoned")?;
+    env.take_rust_field::<_,_,Arc<FaultInjector>>(cli, "_faults")?;
+    env.take_rust_field::<_,_,Option<Arc<MarketScales>>>(cli, "_scales")?;
+    env.take_rust_field::<_,_,Option<Arc<OrderTracker>>>(cli, "_tracker")?;
+    env.take_rust_field::<_,_,Option<Arc<PositionTracker>>>(cli, "_positions")?;
+    env.take_rust_field::<_,_,Option<Arc<RiskChecker>>>(cli, "_risk")?;
+    env.take_rust_field::<_,_,Option<Arc<Journal>>>(cli, "_journal")?;
+    // Last, closing may still log errors that hold them
+    env.take_rust_field::<_,_,RegisteredSecrets>(cli, "_secrets")?;
+    Ok(())
+  };
+  let context = ErrorContext::new("closeClient", cli);
+  handle_void_result(env, &context, guarded(&env, &context, call));
+}
+
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_updateCredentials(env: JNIEnv, _class: JClass,  cli: JObject, credentials: JObject) -> jboolean {
+  let call = move || -> OpenLimitsJavaResult<bool> {
+    let (params, previous) = {
+      let config = env.get_rust_field::<_,_,SecretParams>(cli, "_config")?;
+      (get_updated_credentials(&env, &config, &credentials).map_err(OpenlimitsJavaError::InvalidArgument)?, config.clone())
+    };
+    // Both stay redacted until the switch is complete, errors of either may still be raised
+    let secrets = env.get_rust_field::<_,_,RegisteredSecrets>(cli, "_secrets")?;
+    secrets.add(params.secrets());
+    drop(secrets);
+    let client = {
+      let runtime: MutexGuard<tokio::runtime::Runtime> = env.get_rust_field(cli, "_runtime")?;
+      runtime.block_on(OpenLimits::instantiate(params.instantiate_params()))?
+    };
+    // Nothing is switched before the websocket opened with the new credentials, so a failure leaves the client
+    // on the previous ones throughout
+    let reopened = match replace_stream(env, _class, cli, &params) {
+      Ok(reopened) => reopened,
+      Err(err) => {
+        restore_stream(env, _class, cli, previous);
+        return Err(err);
+      }
+    };
+    *env.get_rust_field::<_,_,AnyExchange>(cli, "_client")? = client;
+    *env.get_rust_field::<_,_,SecretParams>(cli, "_config")? = params.clone();
+    if let Some(switch) = env.get_rust_field::<_,_,Option<DeadMansSwitch>>(cli, "_dead_mans_switch")?.as_ref() {
+      switch.update_credentials(params.clone());
+    }
+    env.get_rust_field::<_,_,RegisteredSecrets>(cli, "_secrets")?.replace(params.secrets());
+    Ok(reopened)
+  };
+  let context = ErrorContext::new("updateCredentials", cli);
+  match guarded(&env, &context, call) {
+    Ok(reopened) => reopened as jboolean,
+    Err(err) => {
+      handle_void_result(env, &context, Err(err));
+      false as jboolean
+    }
+  }
+}
+
+// The private streams authenticated with the old credentials, so an open websocket is replaced by one opened with
+// `params`. False when none was open.
+fn replace_stream(env: JNIEnv, _class: JClass, cli: JObject, params: &SecretParams) -> OpenLimitsJavaResult<bool> {
+  let _opening = env.get_rust_field::<_,_,()>(cli, "_stream_lock")?;
+  {
+    let _monitor = env.lock_obj(cli)?;
+    if env.get_rust_field::<_,_,Option<ThreadManager>>(cli, "_thread_manager")?.take().is_none() {
+      return Ok(false);
+    }
+    env.get_rust_field::<_,_,Option<StreamSender>>(cli, "_sub_tx")?.take();
+  }
+  open_stream(env, _class, cli, stream_params(&env, cli, params.clone())?)?;
+  Ok(true)
+}
+
+// Streams with the previous credentials again rather than leave the callbacks of the client without a websocket.
+// Best effort, the caller raises why the new credentials couldn't stream either way.
+fn restore_stream(env: JNIEnv, _class: JClass, cli: JObject, previous: SecretParams) {
+  let reopen = || -> OpenLimitsJavaResult<()> {
+    let _opening = env.get_rust_field::<_,_,()>(cli, "_stream_lock")?;
+    if env.get_rust_field::<_,_,Option<ThreadManager>>(cli, "_thread_manager")?.is_some() {
+      return Ok(());
+    }
+    open_stream(env, _class, cli, stream_params(&env, cli, previous)?)
+  };
+  let restored = reopen().and_then(|_| Ok(env.call_method(cli, "resubscribe", "()V", &[])?));
+  if let Err(err) = restored {
+    log::error!("Failed to stream with the previous credentials: {}", redact(&format!("{:?}", err)));
+    if env.exception_check().unwrap_or(false) {
+      env.exception_clear().ok();
+    }
+  }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_heartbeat(env: JNIEnv, _class: JClass,  cli: JObject) {
+  let call = move || -> OpenLimitsJavaResult<()> {
+    env.get_rust_field::<_,_,Option<DeadMansSwitch>>(cli, "_dead_mans_switch")?
+      .as_ref()
+      .ok_or_else(|| OpenlimitsJavaError::InvalidArgument(String::from("Heartbeats require ExchangeClientConfig.withDeadMansSwitch()")))?
+      .heartbeat();
+    Ok(())
+  };
+  let context = ErrorContext::new("heartbeat", cli);
+  handle_void_result(env, &context, guarded(&env, &context, call));
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_simulateSocketError(env: JNIEnv, _class: JClass,  cli: JObject) {
+  let call = move || -> OpenLimitsJavaResult<()> {
+    let sub_request_tx = stream_sender(&env, cli)?
+      .ok_or_else(|| OpenlimitsJavaError::SubscribeException(String::from("Websocket is disconnected")))?;
+    match sub_request_tx.send(SubthreadCmd::SimulateError) {
+      Err(e) => Err(
+        OpenlimitsJavaError::SubscribeException(e.to_string())
+      ),
+      _ => Ok(())
+    }?;
+    Ok(())
+  };
+  let context = ErrorContext::new("simulateSocketError", cli);
+  handle_void_result(env, &context, guarded(&env, &context, call));
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_simulateMessages(env: JNIEnv, _class: JClass,  cli: JObject, market: JString, count: jlong) {
+  let call = move || -> OpenLimitsJavaResult<()> {
+    let market: String = env.get_string(market)?.into();
+    if count < 0 {
+      return Err(OpenlimitsJavaError::InvalidArgument(format!("count must not be negative, got {}", count)));
+    }
+    // So a soak test gets a callback thread that only sees the simulated messages
+    if get_streaming_mode(&env, &client_options(&env, &cli)?).map_err(OpenlimitsJavaError::InvalidArgument)? == StreamingMode::Lazy {
+      ensure_stream(env, _class, cli)?;
+    }
+    let sub_request_tx = stream_sender(&env, cli)?
+      .ok_or_else(|| OpenlimitsJavaError::SubscribeException(String::from("Websocket is disconnected")))?;
+    sub_request_tx.send(SubthreadCmd::SimulateMessages(market, count as u64))
+      .map_err(|e| OpenlimitsJavaError::SubscribeException(e.to_string()))?;
+    Ok(())
+  };
+  let context = ErrorContext::with_market("simulateMessages", cli, market);
+  handle_void_result(env, &context, guarded(&env, &context, call));
+}
+
+/// Alternates order book snapshots of 10 levels per side with single trades.
+fn synthetic_message(market: &str, n: u64) -> OpenLimitsWebSocketMessage {
+  let level = |i: i64| AskBid { price: Decimal::new(10_000 + i, 2), qty: Decimal::new(n as i64 % 1000 + 1, 3) };
+  if n % 2 == 0 {
+    OpenLimitsWebSocketMessage::OrderBook(OrderBookResponse {
+      update_id: Some(n),
+      last_update_id: Some(n),
+      bids: (1..=10).map(|i| level(-i)).collect(),
+      asks: (1..=10).map(level).collect(),
+    })
+  } else {
+    OpenLimitsWebSocketMessage::Trades(vec![Trade {
+      id: n.to_string(),
+      buyer_order_id: None,
+      seller_order_id: None,
+      market_pair: String::from(market),
+      price: Decimal::new(10_000, 2),
+      qty: Decimal::new(n as i64 % 1000 + 1, 3),
+      fees: None,
+      side: if n % 4 == 1 { Side::Buy } else { Side::Sell },
+      liquidity: Some(Liquidity::Taker),
+      created_at: n,
+    }])
+  }
+}
+
+fn client_metrics(env: &JNIEnv, cli: JObject) -> Option<Arc<Metrics>> {
+  env.get_rust_field::<_,_,Arc<Metrics>>(cli, "_metrics").ok().map(|metrics| metrics.clone())
+}
+
+fn client_poison_flag(env: &JNIEnv, cli: JObject) -> Option<PoisonFlag> {
+  if cli.is_null() {
+    return None;
+  }
+  env.get_rust_field::<_,_,PoisonFlag>(cli, "_poisoned").ok().map(|poison| poison.clone())
+}
+
+/// The market scales of a fixed-point client, None when it returns BigDecimals.
+fn client_scales(env: &JNIEnv, cli: JObject) -> OpenLimitsJavaResult<Option<Arc<MarketScales>>> {
+  Ok(env.get_rust_field::<_,_,Option<Arc<MarketScales>>>(cli, "_scales")?.clone())
+}
+
+fn client_positions(env: &JNIEnv, cli: JObject) -> OpenLimitsJavaResult<Option<Arc<PositionTracker>>> {
+  Ok(env.get_rust_field::<_,_,Option<Arc<PositionTracker>>>(cli, "_positions")?.clone())
+}
+
+fn client_risk(env: &JNIEnv, cli: JObject) -> OpenLimitsJavaResult<Option<Arc<RiskChecker>>> {
+  Ok(env.get_rust_field::<_,_,Option<Arc<RiskChecker>>>(cli, "_risk")?.clone())
+}
+
+fn client_switch_trigger(env: &JNIEnv, cli: JObject) -> OpenLimitsJavaResult<Option<SwitchTrigger>> {
+  Ok(env.get_rust_field::<_,_,Option<DeadMansSwitch>>(cli, "_dead_mans_switch")?.as_ref().map(DeadMansSwitch::trigger))
+}
+
+fn client_journal(env: &JNIEnv, cli: JObject) -> OpenLimitsJavaResult<Option<Arc<Journal>>> {
+  Ok(env.get_rust_field::<_,_,Option<Arc<Journal>>>(cli, "_journal")?.clone())
+}
+
+fn client_tracker(env: &JNIEnv, cli: JObject) -> OpenLimitsJavaResult<Option<Arc<OrderTracker>>> {
+  Ok(env.get_rust_field::<_,_,Option<Arc<OrderTracker>>>(cli, "_tracker")?.clone())
+}
+
+/// Hands the changes of tracked orders to `onOrderStateChange` on the calling thread. Must run after `_client` is
+/// unlocked, the callbacks are free to use the client.
+fn report_order_changes<F: FnOnce(&OrderTracker) -> Vec<OrderChange>>(env: &JNIEnv, cli: JObject, track: F) -> OpenLimitsJavaResult<()> {
+  let changes = match client_tracker(env, cli)? {
+    Some(tracker) => track(&tracker),
+    None => return Ok(()),
+  };
+  let scales = client_scales(env, cli)?;
+  for change in changes {
+    env.call_method(cli, "onOrderStateChange", ORDER_STATE_CHANGE_SIG, &order_change_args(env, change, scales.as_deref())?)?;
+  }
+  Ok(())
+}
+
+fn client_faults(env: &JNIEnv, cli: JObject) -> Option<Arc<FaultInjector>> {
+  if cli.is_null() {
+    return None;
+  }
+  env.get_rust_field::<_,_,Arc<FaultInjector>>(cli, "_faults").ok().map(|faults| faults.clone())
+}
+
+fn client_is_poisoned(env: &JNIEnv, cli: JObject) -> bool {
+  matches!(client_poison_flag(env, cli), Some(poison) if poison.is_poisoned())
+}
+
+// Calls that stay available on a poisoned client
+static POISON_SAFE_OPERATIONS: &[&str] = &["closeClient", "getMetrics", "isPoisoned"];
+
+/// Runs an entry point so that a panic is raised as `NativePanic` instead of aborting the JVM. A panic poisons the
+/// client since it may have left its state half updated, after which only teardown and metrics calls are accepted.
+fn guarded<T, F>(env: &JNIEnv, context: &ErrorContext, call: F) -> OpenLimitsJavaResult<T>
+  where F: FnOnce() -> OpenLimitsJavaResult<T> {
+  if !POISON_SAFE_OPERATIONS.contains(&context.operation) && client_is_poisoned(env, context.client) {
+    return Err(OpenlimitsJavaError::NativePanic(String::from("Client is poisoned by an earlier panic and must be recreated")));
+  }
+  let call = || {
+    inject_faults(env, context)?;
+    call()
+  };
+  match panics::catch(|| with_metrics(env, context, call)) {
+    Ok(result) => result,
+    Err(msg) => {
+      log::error!("Panic in {}: {}", context.operation, msg);
+      if let Some(poison) = client_poison_flag(env, context.client) {
+        poison.poison();
+      }
+      if let Some(metrics) = client_metrics(env, context.client) {
+        metrics.record_error("io/nash/openlimits/NativePanic");
+      }
+      Err(OpenlimitsJavaError::NativePanic(msg))
+    }
+  }
+}
+
+// Stay usable whatever rules are installed, so a test can always clean up
+static FAULT_EXEMPT_OPERATIONS: &[&str] = &["closeClient", "injectFault", "clearFaults"];
+
+/// Applies the fault rules a test installed on the client before the call reaches the exchange.
+fn inject_faults(env: &JNIEnv, context: &ErrorContext) -> OpenLimitsJavaResult<()> {
+  // Orders fire theirs after the risk check, see send_order
+  if FAULT_EXEMPT_OPERATIONS.contains(&context.operation) || faults::ORDER_OPERATIONS.contains(&context.operation) {
+    return Ok(());
+  }
+  if !matches!(client_faults(env, context.client), Some(faults) if !faults.is_empty()) {
+    return Ok(());
+  }
+  fire_faults(env, context.client, context.operation, context.market(env).as_deref()).map(|_| ())
+}
+
+/// Sleeps and raises what the matching rules asked for, leaving the rest to the caller.
+fn fire_faults(env: &JNIEnv, cli: JObject, operation: &str, market: Option<&str>) -> OpenLimitsJavaResult<Faults> {
+  let faults = match client_faults(env, cli) {
+    Some(faults) if !faults.is_empty() => faults,
+    _ => return Ok(Faults::default())
+  };
+  let fired = faults.fire(operation, market, false);
+  if fired.latency > std::time::Duration::from_secs(0) {
+    std::thread::sleep(fired.latency);
+  }
+  match fired.error {
+    Some(error) => Err(OpenlimitsJavaError::InjectedFault(error)),
+    None => Ok(fired)
+  }
+}
+
+/// The callback a websocket message is delivered to, which is what stream fault rules match on.
+fn stream_operation(msg: &OpenLimitsWebSocketMessage) -> &'static str {
+  match msg {
+    OpenLimitsWebSocketMessage::Ping => "onPing",
+    OpenLimitsWebSocketMessage::OrderBook(_) | OpenLimitsWebSocketMessage::OrderBookDiff(_) => "onOrderbook",
+    OpenLimitsWebSocketMessage::Trades(_) => "onTrades",
+  }
+}
+
+fn with_metrics<T, F>(env: &JNIEnv, context: &ErrorContext, call: F) -> OpenLimitsJavaResult<T>
+  where F: FnOnce() -> OpenLimitsJavaResult<T> {
+  // Looked up before the call so closeClient is still recorded, and after it so init is too
+  let metrics = client_metrics(env, context.client);
+  let started_at = Instant::now();
+  let result = call();
+  if let Some(metrics) = metrics.or_else(|| client_metrics(env, context.client)) {
+    metrics.record_latency(context.operation, started_at.elapsed());
+    if let Err(err) = &result {
+      metrics.record_error(map_error_to_error_class(err));
+    }
+  }
+  result
+}
+
+/// Runs on the thread calling `System.loadLibrary`, which can see the application class loader. Registers the
+/// natives so a mismatched jar fails right here, and resolves the classes to keep `find_class` off the websocket
+/// callback path. Classes that can't be resolved here are resolved by their first conversion instead.
+///
+/// # Safety
+///
+/// `vm` must be the `JavaVM` the JVM loading the library passes.
+#[no_mangle]
+pub unsafe extern "system" fn JNI_OnLoad(vm: *mut jni::sys::JavaVM, _reserved: *mut std::ffi::c_void) -> jint {
+  let result = panics::catch(|| -> errors::Result<bool> {
+    let vm = JavaVM::from_raw(vm)?;
+    let env = vm.get_env()?;
+    if let Err(reason) = natives::register(&env) {
+      natives::throw_incompatible(&env, &reason);
+      return Ok(false);
+    }
+    // Not fatal, see java_classes
+    if java_classes(&env).is_err() || java_enums(&env).is_err() {
+      env.exception_clear().ok();
+    }
+    Ok(true)
+  });
+  match result {
+    Ok(Ok(true)) => jni::sys::JNI_VERSION_1_8,
+    // loadLibrary raises the pending IncompatibleNativeLibrary, or an UnsatisfiedLinkError if there is none
+    _ => jni::sys::JNI_ERR
+  }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_NativeLogger_install(env: JNIEnv, _class: JClass, level: jint) {
+  let call = move || -> OpenLimitsJavaResult<()> {
+    logging::install(&env, logging::jint_to_level_filter(level))?;
+    Ok(())
+  };
+  let context = ErrorContext::new("install", JObject::null());
+  handle_void_result(env, &context, guarded(&env, &context, call));
 }
 
 #[no_mangle]
-pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_disconnect(env: JNIEnv, _class: JClass,  cli: JObject) {
+pub extern "system" fn Java_io_nash_openlimits_NativeLogger_setNativeLevel(env: JNIEnv, _class: JClass, level: jint) {
   let call = move || -> OpenLimitsJavaResult<()> {
-    let sub_request_tx: MutexGuard<tokio::sync::mpsc::UnboundedSender<SubthreadCmd>> = env.get_rust_field(cli, "_sub_tx")?;
-    match sub_request_tx.send(SubthreadCmd::Disconnect) {
-      Err(e) => Err(
-        OpenlimitsJavaError::SubscribeException(e.to_string())
-      ),
-      _ => Ok(())
-    }?;
+    logging::set_level(logging::jint_to_level_filter(level));
     Ok(())
   };
-  handle_void_result(env, call());
+  let context = ErrorContext::new("setNativeLevel", JObject::null());
+  handle_void_result(env, &context, guarded(&env, &context, call));
 }
 
 #[no_mangle]
-pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_disposeClient(env: JNIEnv, _class: JClass,  cli: JObject) {
-  let call = move || -> OpenLimitsJavaResult<()> {
-    env.take_rust_field(cli, "_client")?;
-    Ok(())
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_getMetrics(env: JNIEnv, _class: JClass,  cli: JObject) -> jobject {
+  let call = move || -> OpenLimitsJavaResult<JObject> {
+    let metrics = env.get_rust_field::<_,_,Arc<Metrics>>(cli, "_metrics")?.clone();
+    Ok(metrics_to_jobject(&env, &metrics)?)
   };
-  handle_void_result(env, call());
+  let context = ErrorContext::new("getMetrics", cli);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
 #[no_mangle]
-pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_closeClient(env: JNIEnv, _class: JClass,  cli: JObject) {
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_isPoisoned(env: JNIEnv, _class: JClass,  cli: JObject) -> jboolean {
+  panics::catch(|| client_is_poisoned(&env, cli)).unwrap_or(true) as jboolean
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_injectFault(env: JNIEnv, _class: JClass,  cli: JObject, rule: JObject) {
   let call = move || -> OpenLimitsJavaResult<()> {
-    env.take_rust_field::<_,_,ThreadManager>(cli, "_thread_manager")?;
-    env.take_rust_field::<_,_,tokio::sync::mpsc::UnboundedSender<SubthreadCmd>>(cli, "_sub_tx" )?;
-    env.take_rust_field::<_,_,InitAnyExchange>(cli, "_config" )?;
-    env.take_rust_field::<_,_,tokio::runtime::Runtime>(cli, "_runtime")?;
+    let rule = get_fault_rule(&env, &rule).map_err(OpenlimitsJavaError::InvalidArgument)?;
+    log::warn!("Injecting fault {:?}", rule);
+    env.get_rust_field::<_,_,Arc<FaultInjector>>(cli, "_faults")?.add(rule);
     Ok(())
   };
-  handle_void_result(env, call());
+  let context = ErrorContext::new("injectFault", cli);
+  handle_void_result(env, &context, guarded(&env, &context, call));
 }
 
-
 #[no_mangle]
-pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_simulateSocketError(env: JNIEnv, _class: JClass,  cli: JObject) {
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_clearFaults(env: JNIEnv, _class: JClass,  cli: JObject) {
   let call = move || -> OpenLimitsJavaResult<()> {
-    let sub_request_tx: MutexGuard<tokio::sync::mpsc::UnboundedSender<SubthreadCmd>> = env.get_rust_field(cli, "_sub_tx")?;
-    match sub_request_tx.send(SubthreadCmd::SimulateError) {
-      Err(e) => Err(
-        OpenlimitsJavaError::SubscribeException(e.to_string())
-      ),
-      _ => Ok(())
-    }?;
+    env.get_rust_field::<_,_,Arc<FaultInjector>>(cli, "_faults")?.clear();
     Ok(())
   };
-  handle_void_result(env, call());
+  let context = ErrorContext::new("clearFaults", cli);
+  handle_void_result(env, &context, guarded(&env, &context, call));
 }
 
-fn handle_jobject_result(env: JNIEnv, result: OpenLimitsJavaResult<JObject>) -> jobject {
+fn handle_jobject_result(env: JNIEnv, context: &ErrorContext, result: OpenLimitsJavaResult<JObject>) -> jobject {
   match result {
     Ok(obj) => obj.into_inner(),
     Err(err) => {
-      handle_void_result(env, Err(err));
+      handle_void_result(env, context, Err(err));
       JObject::null().into_inner()
     }
   }
 }
 
-fn handle_void_result(env: JNIEnv, result: OpenLimitsJavaResult<()>) {
+fn handle_void_result(env: JNIEnv, context: &ErrorContext, result: OpenLimitsJavaResult<()>) {
   match result {
     Ok(_) => {},
     Err(err) => {
@@ -957,9 +2771,28 @@ fn handle_void_result(env: JNIEnv, result: OpenLimitsJavaResult<()>) {
       }
       
       let class_name = map_error_to_error_class(&err);
-      let msg = format!("{:?}", err);
-      
-      env.throw_new(class_name, msg).expect(format!("Failed to raise exception: {}", class_name).as_str());
+      let msg = match &err {
+        // Nothing to redact, and the rule behind it says more than its fields
+        OpenlimitsJavaError::InjectedFault(_) => err.to_string(),
+        _ => redact(&format!("{:?}", err)),
+      };
+
+      if class_name.starts_with("io/nash/openlimits/") {
+        let mut details = error_details(&err);
+        details.exchange = context.exchange(&env).or(details.exchange);
+        let exception = match &err {
+          OpenlimitsJavaError::RiskRejected(violation) => new_risk_rejected(&env, violation, msg.clone(), details, context.operation, context.market(&env)),
+          _ => new_exception(&env, class_name, msg.clone(), details, context.operation, context.market(&env)),
+        };
+        if let Ok(exception) = exception {
+          if env.throw(JThrowable::from(exception)).is_ok() {
+            return;
+          }
+        }
+      }
+      if env.throw_new(class_name, msg).is_err() {
+        log::error!("Failed to raise exception: {}", class_name);
+      }
     }
   }
 }
@@ -977,11 +2810,11 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_orderBook(env: JNI
     };
   
     let resp = runtime.block_on(client.order_book(&req))?;
-    let out = orderbook_resp_to_jobject(&env, resp, market.into())?;
-    Ok(out)
+    any_orderbook_to_jobject(&env, resp, market_pair_str, market.into(), client_scales(&env, cli)?.as_deref())
   };
 
-  handle_jobject_result(env, call())
+  let context = ErrorContext::with_market("orderBook", cli, market);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
 #[no_mangle]
@@ -999,7 +2832,8 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_getPriceTicker(env
     Ok(out)
   };
 
-  handle_jobject_result(env, call())
+  let context = ErrorContext::with_market("getPriceTicker", cli, market);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
 
@@ -1012,11 +2846,11 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_getHistoricRates(e
     let req = get_historic_rates_request(&env, &hist_req).map_err(OpenlimitsJavaError::InvalidArgument)?;
 
     let resp = runtime.block_on(client.get_historic_rates(&req))?;
-    let out = vec_to_jobject(&env, CANDLE_CLS_NAME, resp, candle_to_jobject)?;
-    Ok(out)
+    candles_to_jobject(&env, resp, &req.market_pair, client_scales(&env, cli)?.as_deref())
   };
 
-  handle_jobject_result(env, call())
+  let context = ErrorContext::with_request("getHistoricRates", cli, hist_req);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
 
@@ -1028,11 +2862,105 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_getHistoricTrades(
     let req = get_historic_trades_request(&env, &trades_req).map_err(OpenlimitsJavaError::InvalidArgument)?;
 
     let resp = runtime.block_on(client.get_historic_trades(&req))?;
-    let out = vec_to_jobject(&env, TRADE_CLS_NAME, resp, trade_to_jobject)?;
-    Ok(out)
+    trades_to_jobject(&env, resp, client_scales(&env, cli)?.as_deref())
+  };
+
+  let context = ErrorContext::with_request("getHistoricTrades", cli, trades_req);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
+}
+
+/// Records an order action and its outcome in the audit journal, for clients that keep one.
+fn journaled<T, O, S>(journal: Option<Arc<Journal>>, action: JournalAction, outcome: O, send: S) -> OpenLimitsJavaResult<T>
+  where O: FnOnce(&T) -> (Option<String>, String), S: FnOnce() -> OpenLimitsJavaResult<T> {
+  let journal = match journal {
+    Some(journal) => journal,
+    None => return send(),
+  };
+  let pending = journal.request(action);
+  let result = send();
+  match &result {
+    Ok(value) => {
+      let (order_id, detail) = outcome(value);
+      journal.result(pending, order_id, detail);
+    },
+    Err(err) => journal.error(pending, audit_error(err)),
+  }
+  result
+}
+
+/// What the journal keeps of an error: its class and what the exchange said about it, never the request behind it.
+fn audit_error(err: &OpenlimitsJavaError) -> String {
+  let class_name = map_error_to_error_class(err);
+  let mut detail = String::from(class_name.rsplit('/').next().unwrap_or(class_name));
+  if let OpenlimitsJavaError::RiskRejected(violation) = err {
+    detail.push_str(&format!(" {}", violation));
+  }
+  let details = error_details(err);
+  if let Some(code) = details.error_code {
+    detail.push_str(&format!(" code={}", code));
+  }
+  if let Some(status) = details.http_status {
+    detail.push_str(&format!(" httpStatus={}", status));
+  }
+  if let Some(message) = details.exchange_message {
+    detail.push_str(&format!(" message={}", message));
+  }
+  redact(&detail)
+}
+
+fn limit_action(operation: &'static str, req: &OpenLimitOrderRequest) -> JournalAction {
+  JournalAction {
+    operation,
+    market: Some(req.market_pair.clone()),
+    client_order_id: req.client_order_id.clone(),
+    order_id: None,
+    detail: format!("size={} price={} timeInForce={:?} postOnly={}", req.size, req.price, req.time_in_force, req.post_only),
+  }
+}
+
+fn market_action(operation: &'static str, req: &OpenMarketOrderRequest) -> JournalAction {
+  JournalAction {
+    operation,
+    market: Some(req.market_pair.clone()),
+    client_order_id: req.client_order_id.clone(),
+    order_id: None,
+    detail: format!("size={}", req.size),
+  }
+}
+
+fn cancel_all_action(market: Option<String>, detail: String) -> JournalAction {
+  JournalAction { operation: "cancelAllOrders", market, client_order_id: None, order_id: None, detail }
+}
+
+fn order_outcome(order: &Order) -> (Option<String>, String) {
+  (Some(order.id.clone()), format!("status={:?}", order.status))
+}
+
+fn cancel_all_outcome(canceled: &[OrderCanceled]) -> (Option<String>, String) {
+  let ids: Vec<&str> = canceled.iter().map(|canceled| canceled.id.as_str()).collect();
+  (None, format!("canceled={}", ids.join(",")))
+}
+
+/// Runs before an order is sent. The kill switch stops the orders of every client, limits only apply to clients
+/// configured with them.
+fn check_risk(env: &JNIEnv, cli: JObject, order: &OrderIntent) -> OpenLimitsJavaResult<Option<Admission>> {
+  risk::check_kill_switch().map_err(OpenlimitsJavaError::RiskRejected)?;
+  let risk = match client_risk(env, cli)? {
+    Some(risk) => risk,
+    None => return Ok(None),
   };
+  let positions = client_positions(env, cli)?;
+  let tracker = client_tracker(env, cli)?;
+  risk.admit(order, positions.as_deref(), tracker.as_deref()).map(Some).map_err(OpenlimitsJavaError::RiskRejected)
+}
 
-  handle_jobject_result(env, call())
+/// Sends an order the risk check passed. Its admission goes to `admission`, to be kept until the tracker recorded
+/// the order. The fault rules of the operation fire only once the order passed the limits.
+fn send_order<F>(env: &JNIEnv, cli: JObject, operation: &str, order: OrderIntent, admission: &mut Option<Admission>, send: F) -> OpenLimitsJavaResult<Order>
+  where F: FnOnce() -> OpenLimitsJavaResult<Order> {
+  *admission = check_risk(env, cli, &order)?;
+  fire_faults(env, cli, operation, Some(order.market))?;
+  send()
 }
 
 #[no_mangle]
@@ -1042,11 +2970,19 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_limitBuy(env: JNIE
     let runtime: MutexGuard<tokio::runtime::Runtime> = env.get_rust_field(cli, "_runtime")?;
 
     let req = get_limit_request(&env, &req).map_err(OpenlimitsJavaError::InvalidArgument)?;
-    let resp = runtime.block_on(client.limit_buy(&req))?;
-    
-    Ok(order_to_jobject(&env, resp)?)
+    let mut admission = None;
+    let resp = journaled(client_journal(&env, cli)?, limit_action("limitBuy", &req), order_outcome, || {
+      let order = OrderIntent { market: &req.market_pair, side: Side::Buy, size: req.size, price: Some(req.price) };
+      send_order(&env, cli, "limitBuy", order, &mut admission, || Ok(runtime.block_on(client.limit_buy(&req))?))
+    })?;
+    drop(client);
+    drop(runtime);
+    report_order_changes(&env, cli, |tracker| tracker.record(resp.clone()).into_iter().collect())?;
+    drop(admission);
+    any_order_to_jobject(&env, resp, client_scales(&env, cli)?.as_deref())
   };
-  handle_jobject_result(env, call())
+  let context = ErrorContext::with_request("limitBuy", cli, req);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
 #[no_mangle]
@@ -1056,11 +2992,19 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_limitSell(env: JNI
     let runtime: MutexGuard<tokio::runtime::Runtime> = env.get_rust_field(cli, "_runtime")?;
 
     let req = get_limit_request(&env, &req).map_err(OpenlimitsJavaError::InvalidArgument)?;
-
-    let resp = runtime.block_on(client.limit_sell(&req))?;
-    Ok(order_to_jobject(&env, resp)?)
+    let mut admission = None;
+    let resp = journaled(client_journal(&env, cli)?, limit_action("limitSell", &req), order_outcome, || {
+      let order = OrderIntent { market: &req.market_pair, side: Side::Sell, size: req.size, price: Some(req.price) };
+      send_order(&env, cli, "limitSell", order, &mut admission, || Ok(runtime.block_on(client.limit_sell(&req))?))
+    })?;
+    drop(client);
+    drop(runtime);
+    report_order_changes(&env, cli, |tracker| tracker.record(resp.clone()).into_iter().collect())?;
+    drop(admission);
+    any_order_to_jobject(&env, resp, client_scales(&env, cli)?.as_deref())
   };
-  handle_jobject_result(env, call())
+  let context = ErrorContext::with_request("limitSell", cli, req);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
 
@@ -1071,11 +3015,19 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_marketBuy(env: JNI
     let runtime: MutexGuard<tokio::runtime::Runtime> = env.get_rust_field(cli, "_runtime")?;
 
     let req = get_market_request(&env, &req).map_err(OpenlimitsJavaError::InvalidArgument)?;
-
-    let resp = runtime.block_on(client.market_buy(&req))?;
-    Ok(order_to_jobject(&env, resp)?)
+    let mut admission = None;
+    let resp = journaled(client_journal(&env, cli)?, market_action("marketBuy", &req), order_outcome, || {
+      let order = OrderIntent { market: &req.market_pair, side: Side::Buy, size: req.size, price: None };
+      send_order(&env, cli, "marketBuy", order, &mut admission, || Ok(runtime.block_on(client.market_buy(&req))?))
+    })?;
+    drop(client);
+    drop(runtime);
+    report_order_changes(&env, cli, |tracker| tracker.record(resp.clone()).into_iter().collect())?;
+    drop(admission);
+    any_order_to_jobject(&env, resp, client_scales(&env, cli)?.as_deref())
   };
-  handle_jobject_result(env, call())
+  let context = ErrorContext::with_request("marketBuy", cli, req);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
 #[no_mangle]
@@ -1085,11 +3037,44 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_marketSell(env: JN
     let runtime: MutexGuard<tokio::runtime::Runtime> = env.get_rust_field(cli, "_runtime")?;
 
     let req = get_market_request(&env, &req).map_err(OpenlimitsJavaError::InvalidArgument)?;
+    let mut admission = None;
+    let resp = journaled(client_journal(&env, cli)?, market_action("marketSell", &req), order_outcome, || {
+      let order = OrderIntent { market: &req.market_pair, side: Side::Sell, size: req.size, price: None };
+      send_order(&env, cli, "marketSell", order, &mut admission, || Ok(runtime.block_on(client.market_sell(&req))?))
+    })?;
+    drop(client);
+    drop(runtime);
+    report_order_changes(&env, cli, |tracker| tracker.record(resp.clone()).into_iter().collect())?;
+    drop(admission);
+    any_order_to_jobject(&env, resp, client_scales(&env, cli)?.as_deref())
+  };
+  let context = ErrorContext::with_request("marketSell", cli, req);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_setGlobalKillSwitch(env: JNIEnv, _class: JClass, engaged: jboolean) {
+  let call = move || -> OpenLimitsJavaResult<()> {
+    risk::set_kill_switch(engaged != 0);
+    Ok(())
+  };
+  let context = ErrorContext::new("setGlobalKillSwitch", JObject::null());
+  handle_void_result(env, &context, guarded(&env, &context, call));
+}
 
-    let resp = runtime.block_on(client.market_sell(&req))?;
-    Ok(order_to_jobject(&env, resp)?)
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_isGlobalKillSwitchEngaged(env: JNIEnv, _class: JClass) -> jboolean {
+  let call = move || -> OpenLimitsJavaResult<bool> {
+    Ok(risk::kill_switch_engaged())
   };
-  handle_jobject_result(env, call())
+  let context = ErrorContext::new("isGlobalKillSwitchEngaged", JObject::null());
+  match guarded(&env, &context, call) {
+    Ok(engaged) => engaged as jboolean,
+    Err(err) => {
+      handle_void_result(env, &context, Err(err));
+      false as jboolean
+    }
+  }
 }
 
 #[no_mangle]
@@ -1100,10 +3085,80 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_getAllOpenOrders(e
 
     let resp = runtime.block_on(client.get_all_open_orders())?;
 
-    let out = vec_to_jobject(&env, ORDER_CLS_NAME, resp, order_to_jobject)?;
-    Ok(out)
+    orders_to_jobject(&env, resp, client_scales(&env, cli)?.as_deref())
+  };
+  let context = ErrorContext::new("getAllOpenOrders", cli);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
+}
+
+fn required_tracker(env: &JNIEnv, cli: JObject) -> OpenLimitsJavaResult<Arc<OrderTracker>> {
+  client_tracker(env, cli)?
+    .ok_or_else(|| OpenlimitsJavaError::InvalidArgument(String::from("Order tracking requires ExchangeClientConfig.withOrderTracking()")))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_getTrackedOrders(env: JNIEnv, _class: JClass,  cli: JObject, market: JString) -> jobject {
+  let call = move || -> OpenLimitsJavaResult<JObject> {
+    let tracker = required_tracker(&env, cli)?;
+    let market: Option<String> = match market.is_null() {
+      true => None,
+      false => Some(env.get_string(market)?.into()),
+    };
+    orders_to_jobject(&env, tracker.tracked(market.as_deref()), client_scales(&env, cli)?.as_deref())
+  };
+  let context = ErrorContext::with_market("getTrackedOrders", cli, market);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
+}
+
+fn required_positions(env: &JNIEnv, cli: JObject) -> OpenLimitsJavaResult<Arc<PositionTracker>> {
+  client_positions(env, cli)?
+    .ok_or_else(|| OpenlimitsJavaError::InvalidArgument(String::from("Positions require ExchangeClientConfig.withPositionTracking()")))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_getPositions(env: JNIEnv, _class: JClass,  cli: JObject) -> jobject {
+  let call = move || -> OpenLimitsJavaResult<JObject> {
+    let positions = required_positions(&env, cli)?;
+    Ok(vec_to_jobject(&env, |c| &c.position, positions.positions(), position_to_jobject)?)
   };
-  handle_jobject_result(env, call())
+  let context = ErrorContext::new("getPositions", cli);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_getAssetPositions(env: JNIEnv, _class: JClass,  cli: JObject) -> jobject {
+  let call = move || -> OpenLimitsJavaResult<JObject> {
+    let positions = required_positions(&env, cli)?;
+    Ok(vec_to_jobject(&env, |c| &c.asset_position, positions.assets(), asset_position_to_jobject)?)
+  };
+  let context = ErrorContext::new("getAssetPositions", cli);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_reconcileOrders(env: JNIEnv, _class: JClass,  cli: JObject) {
+  let call = move || -> OpenLimitsJavaResult<()> {
+    let tracker = required_tracker(&env, cli)?;
+    let client: MutexGuard<AnyExchange> = env.get_rust_field(cli, "_client")?;
+    let runtime: MutexGuard<tokio::runtime::Runtime> = env.get_rust_field(cli, "_runtime")?;
+    let Reconciliation { changes, errors } = runtime.block_on(tracker.reconcile(&client))?;
+    drop(client);
+    drop(runtime);
+    // What did change is reported before the first failed lookup is raised
+    report_order_changes(&env, cli, |_| changes)?;
+    let mut errors = errors.into_iter();
+    match errors.next() {
+      Some(err) => {
+        for err in errors {
+          log::warn!("Failed to reconcile another order: {:?}", err);
+        }
+        Err(err.into())
+      },
+      None => Ok(()),
+    }
+  };
+  let context = ErrorContext::new("reconcileOrders", cli);
+  handle_void_result(env, &context, guarded(&env, &context, call));
 }
 
 #[no_mangle]
@@ -1113,10 +3168,10 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_getOrderHistory(en
     let runtime: MutexGuard<tokio::runtime::Runtime> = env.get_rust_field(cli, "_runtime")?;
     let req = get_order_history_request(&env, &req).map_err(OpenlimitsJavaError::InvalidArgument)?;
     let resp = runtime.block_on(client.get_order_history(&req))?;
-    let out = vec_to_jobject(&env, ORDER_CLS_NAME, resp, order_to_jobject)?;
-    Ok(out)
+    orders_to_jobject(&env, resp, client_scales(&env, cli)?.as_deref())
   };
-  handle_jobject_result(env, call())
+  let context = ErrorContext::with_request("getOrderHistory", cli, req);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
 #[no_mangle]
@@ -1128,9 +3183,10 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_getOrder(env: JNIE
 
     let resp = runtime.block_on(client.get_order(&req))?;
 
-    Ok(order_to_jobject(&env, resp)?)
+    any_order_to_jobject(&env, resp, client_scales(&env, cli)?.as_deref())
   };
-  handle_jobject_result(env, call())
+  let context = ErrorContext::with_request("getOrder", cli, req);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
 #[no_mangle]
@@ -1141,10 +3197,10 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_getTradeHistory(en
     let req = get_trade_history_request(&env, &req).map_err(OpenlimitsJavaError::InvalidArgument)?;
 
     let resp = runtime.block_on(client.get_trade_history(&req))?;
-    let out = vec_to_jobject(&env, TRADE_CLS_NAME, resp, trade_to_jobject)?;
-    Ok(out)
+    trades_to_jobject(&env, resp, client_scales(&env, cli)?.as_deref())
   };
-  handle_jobject_result(env, call())
+  let context = ErrorContext::with_request("getTradeHistory", cli, req);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
 #[no_mangle]
@@ -1159,10 +3215,11 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_getAccountBalances
     let paginator = req.transpose().map_err(OpenlimitsJavaError::InvalidArgument)?;
 
     let resp = runtime.block_on(client.get_account_balances(paginator))?;
-    let out = vec_to_jobject(&env, BALANCE_CLS_NAME, resp, balance_to_jobject)?;
+    let out = vec_to_jobject(&env, |c| &c.balance, resp, balance_to_jobject)?;
     Ok(out)
   };
-  handle_jobject_result(env, call())
+  let context = ErrorContext::new("getAccountBalances", cli);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
 
@@ -1172,13 +3229,21 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_cancelOrder(env: J
     let client: MutexGuard<AnyExchange> = env.get_rust_field(cli, "_client")?;
     let runtime: MutexGuard<tokio::runtime::Runtime> = env.get_rust_field(cli, "_runtime")?;
     let req = get_cancel_order_request(&env, &req).map_err(OpenlimitsJavaError::InvalidArgument)?;
-
-    let resp = runtime.block_on(client.cancel_order(&req))?;
+    let action = JournalAction {
+      operation: "cancelOrder", market: req.market_pair.clone(), client_order_id: None, order_id: Some(req.id.clone()), detail: String::new()
+    };
+    let resp = journaled(client_journal(&env, cli)?, action, |canceled: &OrderCanceled| (Some(canceled.id.clone()), String::new()), || {
+      Ok(runtime.block_on(client.cancel_order(&req))?)
+    })?;
+    drop(client);
+    drop(runtime);
+    report_order_changes(&env, cli, |tracker| tracker.cancel(&resp.id).into_iter().collect())?;
 
     let out = order_cancelled_to_jobject(&env, resp)?;
     Ok(out)
   };
-  handle_jobject_result(env, call())
+  let context = ErrorContext::with_request("cancelOrder", cli, req);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
 
@@ -1188,15 +3253,75 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_cancelAllOrders(en
     let client: MutexGuard<AnyExchange> = env.get_rust_field(cli, "_client")?;
     let runtime: MutexGuard<tokio::runtime::Runtime> = env.get_rust_field(cli, "_runtime")?;
     let req = get_cancel_all_orders_request(&env, &req).map_err(OpenlimitsJavaError::InvalidArgument)?;
+    let action = cancel_all_action(req.market_pair.clone(), String::new());
+    let resp = journaled(client_journal(&env, cli)?, action, |canceled: &Vec<OrderCanceled>| cancel_all_outcome(canceled), || {
+      Ok(runtime.block_on(client.cancel_all_orders(&req))?)
+    })?;
+    drop(client);
+    drop(runtime);
+    report_order_changes(&env, cli, |tracker| resp.iter().filter_map(|canceled| tracker.cancel(&canceled.id)).collect())?;
+
+    let out = vec_to_jobject(&env, |c| &c.order_canceled, resp, order_cancelled_to_jobject)?;
+    Ok(out)
+  };
+  let context = ErrorContext::with_request("cancelAllOrders", cli, req);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
+}
 
-    let resp = runtime.block_on(client.cancel_all_orders(&req))?;
 
-    let out = vec_to_jobject(&env, ORDER_CANCELED_CLS_NAME, resp, order_cancelled_to_jobject)?;
-    Ok(out)
+fn audit_record_to_jobject<'a>(env: &JNIEnv<'a>, record: JournalRecord) -> errors::Result<JObject<'a>> {
+  let ctor_args = &[
+    JValue::Long(record.timestamp.timestamp_millis()),
+    env.new_string(record.kind.as_str())?.into(),
+    env.new_string(record.operation)?.into(),
+    optional_string_to_jstring(env, record.market)?,
+    optional_string_to_jstring(env, record.client_order_id)?,
+    optional_string_to_jstring(env, record.order_id)?,
+    JValue::Long(record.latency.map(|latency| latency.as_micros() as i64).unwrap_or(-1)),
+    env.new_string(record.detail)?.into(),
+  ];
+  java_classes(env)?.audit_record.new_object(env, ctor_args)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_AuditJournal_readRecords(env: JNIEnv, _class: JClass, dir: JString, from: jlong, to: jlong) -> jobject {
+  let call = move || -> OpenLimitsJavaResult<JObject> {
+    let dir: String = env.get_string(dir)?.into();
+    // Long.MIN_VALUE and Long.MAX_VALUE are beyond chrono's range and leave that end open
+    let from = Utc.timestamp_millis_opt(from).single();
+    let to = Utc.timestamp_millis_opt(to).single();
+    let records = journal::read(Path::new(&dir), from, to)
+      .map_err(|e| OpenlimitsJavaError::OpenLimitsError(openlimits::errors::OpenLimitsError::IoError(e)))?;
+    Ok(vec_to_jobject(&env, |c| &c.audit_record, records, audit_record_to_jobject)?)
+  };
+  let context = ErrorContext::new("readAuditJournal", JObject::null());
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_readConfigFile(env: JNIEnv, _class: JClass, path: JString) -> jobject {
+  let call = move || -> OpenLimitsJavaResult<JObject> {
+    let path: String = env.get_string(path)?.into();
+    let file = config::read_file(Path::new(&path))
+      .map_err(|errors| invalid_config(&format!("in {}", path), errors))?;
+    Ok(client_file_to_jobject(&env, file)?)
   };
-  handle_jobject_result(env, call())
+  let context = ErrorContext::new("readConfigFile", JObject::null());
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
+#[no_mangle]
+pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_readConfigEnvironment(env: JNIEnv, _class: JClass, prefix: JString) -> jobject {
+  let call = move || -> OpenLimitsJavaResult<JObject> {
+    let prefix: String = env.get_string(prefix)?.into();
+    let vars = std::env::vars_os().filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)));
+    let file = config::read_environment(&prefix, vars)
+      .map_err(|errors| invalid_config(&format!("in the {} environment variables", prefix), errors))?;
+    Ok(client_file_to_jobject(&env, file)?)
+  };
+  let context = ErrorContext::new("readConfigEnvironment", JObject::null());
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
+}
 
 #[no_mangle]
 pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_receivePairs(env: JNIEnv, _class: JClass,  cli: JObject) -> jobject {
@@ -1208,12 +3333,13 @@ pub extern "system" fn Java_io_nash_openlimits_ExchangeClient_receivePairs(env:
     let resp = runtime.block_on(client.retrieve_pairs())?;
     let pairs_maybe: errors::Result<Vec<_>> = resp.into_iter().map(|v| market_pair_to_jobject(&env, v)).collect();
     let pairs = pairs_maybe?;
-    let pairs_cls = env.find_class(MARKET_PAIR_CLS_NAME)?;
+    let pairs_cls = java_classes(&env)?.market_pair.class();
 
     let out = vec_to_java_arr(&env, pairs_cls, &pairs)?;
     Ok(out.l()?)
   };
-  handle_jobject_result(env, call())
+  let context = ErrorContext::new("receivePairs", cli);
+  handle_jobject_result(env, &context, guarded(&env, &context, call))
 }
 
 
@@ -1264,19 +3390,33 @@ fn interval_from_string(
 }
 
 
+enum StreamSubscription {
+  Public(Subscription),
+  Account(AccountStream),
+}
+
 fn get_subscription(
   env: &JNIEnv,
   sub: &JObject
-) -> Result<Subscription, String> {
+) -> Result<StreamSubscription, String> {
   match get_string_non_null(env, sub, "tag")?.as_str() {
     "OrderBook" => {
       let market = get_string_non_null(env, sub, "market")?;
-      Ok(Subscription::OrderBookUpdates(market))
+      Ok(StreamSubscription::Public(Subscription::OrderBookUpdates(market)))
     },
     "Trade" => {
       let market = get_string_non_null(env, sub, "market")?;
-      Ok(Subscription::Trades(market))
+      Ok(StreamSubscription::Public(Subscription::Trades(market)))
+    },
+    "AccountOrders" => {
+      let market = get_string_non_null(env, sub, "market")?;
+      Ok(StreamSubscription::Account(AccountStream::Orders(market)))
+    },
+    "AccountTrades" => {
+      let market = get_string_non_null(env, sub, "market")?;
+      Ok(StreamSubscription::Account(AccountStream::Fills(market)))
     },
+    "AccountBalances" => Ok(StreamSubscription::Account(AccountStream::Balances)),
     s => Err(format!("Invalid subscription type: {}", s))
   }
 }
@@ -1383,7 +3523,7 @@ fn get_historic_rates_request(
   hist_req: &JObject
 ) -> Result<GetHistoricRatesRequest, String> {
   let market_pair = get_string_non_null(env, hist_req, "market")?;
-  let interval = get_string_non_null(env, hist_req, "interval")?;
+  let interval = get_enum_name(env, hist_req, "interval", INTERVAL_CLS_NAME, |e| &e.interval)?;
 
   let paginator = get_object(env, hist_req, "paginator", "Lio/nash/openlimits/Paginator;")?;
   let paginator = paginator.map(|paginator| get_paginator(env, &paginator)).transpose()?;
@@ -1391,7 +3531,7 @@ fn get_historic_rates_request(
   Ok(
     GetHistoricRatesRequest {
       market_pair,
-      interval: interval_from_string(interval)?,
+      interval: interval_from_string(String::from(interval))?,
       paginator
     }
   )
@@ -1403,16 +3543,21 @@ fn get_options_nash_credentials(
 ) -> Result<Option<NashCredentials>, String> {
   let credentials = get_object(&env, nash, "credentials",  NASH_CREDENTIALS_CLS_NAME)?;
 
-  credentials.map(|credentials| {
-    let secret = get_string_non_null(&env, &credentials, "secret")?;
-    let session = get_string_non_null(&env, &credentials, "session")?;
-    Ok(
-      NashCredentials {
-        secret,
-        session
-      }
-    )
-  }).transpose()
+  credentials.map(|credentials| get_nash_credentials(env, &credentials)).transpose()
+}
+
+fn get_nash_credentials(
+  env: &JNIEnv,
+  credentials: &JObject,
+) -> Result<NashCredentials, String> {
+  let secret = get_secret(&env, credentials, "secret")?;
+  let session = get_secret(&env, credentials, "session")?;
+  Ok(
+    NashCredentials {
+      secret: expose(secret),
+      session: expose(session)
+    }
+  )
 }
 
 fn string_to_time_in_force(
@@ -1432,15 +3577,13 @@ fn get_limit_request(
   env: &JNIEnv,
   req: &JObject,
 ) -> Result<OpenLimitOrderRequest, String> {
-  let size = get_string_non_null(env, req, "size")?;
-  let price = get_string_non_null(env, req, "price")?;
-  let time_in_force = get_string_non_null(env, req, "timeInForce")?;
+  let size = get_decimal_non_null(env, req, "size")?;
+  let price = get_decimal_non_null(env, req, "price")?;
+  let time_in_force = get_enum_name(env, req, "timeInForce", TIME_IN_FORCE_CLS_NAME, |e| &e.time_in_force)?;
   let time_in_force_time = get_long_default_with_default(env, req, "timeInForceDurationMs", 0)?;
-  let time_in_force = string_to_time_in_force(time_in_force.as_str(), time_in_force_time as i64)?;
+  let time_in_force = string_to_time_in_force(time_in_force, time_in_force_time as i64)?;
   let market_pair = get_string_non_null(env, req, "market")?;
   let post_only = get_field(env, req, "postOnly",  "Z")?.unwrap_or(JValue::Bool(0)).z().map_err(|_| "Failed to convert boolean to jvalue")?;
-  let size = Decimal::from_str(size.as_str()).map_err(|e|e.to_string())?;
-  let price = Decimal::from_str(price.as_str()).map_err(|e|e.to_string())?;
   let client_order_id = get_string(env, req, "clientOrderId")?;
   Ok(
     OpenLimitOrderRequest {
@@ -1458,9 +3601,8 @@ fn get_market_request(
   env: &JNIEnv,
   req: &JObject,
 ) -> Result<OpenMarketOrderRequest, String> {
-  let size = get_string_non_null(env, req, "size")?;
+  let size = get_decimal_non_null(env, req, "size")?;
   let market_pair = get_string_non_null(env, req, "market")?;
-  let size = Decimal::from_str(size.as_str()).map_err(|e|e.to_string())?;
   let client_order_id = get_string(env, req, "clientOrderId")?;
   Ok(
     OpenMarketOrderRequest {
@@ -1475,7 +3617,6 @@ fn get_options_nash(
   env: &JNIEnv,
   nash: &JObject,
 ) -> Result<InitAnyExchange, String> {
-  let credentials = get_options_nash_credentials(env, nash)?;
   let client_id = get_long_default_with_default(env, nash, "clientId", 0)?;
   let environment = get_string_non_null(env, nash, "environment")?;
   let affiliate_code = get_string(env, nash, "affiliateCode")?;
@@ -1486,6 +3627,8 @@ fn get_options_nash(
     "sandbox" => Environment::Sandbox,
     r => return Err(format!("Invalid environment {}", r))
   };
+  // Last, nothing may fail before they are in a SecretParams
+  let credentials = get_options_nash_credentials(env, nash)?;
 
   Ok(
     InitAnyExchange::Nash(
@@ -1508,16 +3651,21 @@ fn get_options_binance_credentials(
   
   let credentials_opt = get_object(&env, binance, "credentials",  BINANCE_CREDENTIALS_CLS_NAME)?;
 
-  credentials_opt.map(|credentials| {
-    let api_key = get_string_non_null(&env, &credentials, "apiKey")?;
-    let api_secret = get_string_non_null(&env, &credentials, "apiSecret")?;
-    Ok(
-      BinanceCredentials {
-        api_key,
-        api_secret
-      }
-    )
-  }).transpose()
+  credentials_opt.map(|credentials| get_binance_credentials(env, &credentials)).transpose()
+}
+
+fn get_binance_credentials(
+  env: &JNIEnv,
+  credentials: &JObject,
+) -> Result<BinanceCredentials, String> {
+  let api_key = get_string_non_null(&env, credentials, "apiKey")?;
+  let api_secret = get_secret(&env, credentials, "apiSecret")?;
+  Ok(
+    BinanceCredentials {
+      api_key,
+      api_secret: expose(api_secret)
+    }
+  )
 }
 
 fn get_boolean_field(
@@ -1544,8 +3692,8 @@ fn get_options_binance(
   env: &JNIEnv,
   binance: &JObject,
 ) -> Result<InitAnyExchange, String> {
-  let credentials = get_options_binance_credentials(env, binance)?;
   let sandbox = get_boolean_field(env, binance, "sandbox")?;
+  let credentials = get_options_binance_credentials(env, binance)?;
   Ok(
     InitAnyExchange::Binance(
       BinanceParameters {
@@ -1561,18 +3709,42 @@ fn get_options_coinbase_credentials(
   coinbase: &JObject,
 ) -> Result<Option<CoinbaseCredentials>, String> {
   let credentials_opt = get_object(&env, coinbase, "credentials",  COINBASE_CREDENTIALS_CLS_NAME)?;
-  credentials_opt.map(|credentials| {
-    let api_key = get_string_non_null(&env, &credentials, "apiKey")?;
-    let api_secret = get_string_non_null(&env, &credentials, "apiSecret")?;
-    let passphrase = get_string_non_null(&env, &credentials, "passphrase")?;
-    Ok(
-      CoinbaseCredentials {
-        api_key,
-        api_secret,
-        passphrase
-      }
-    )
-  }).transpose()
+  credentials_opt.map(|credentials| get_coinbase_credentials(env, &credentials)).transpose()
+}
+
+fn get_coinbase_credentials(
+  env: &JNIEnv,
+  credentials: &JObject,
+) -> Result<CoinbaseCredentials, String> {
+  let api_key = get_string_non_null(&env, credentials, "apiKey")?;
+  let api_secret = get_secret(&env, credentials, "apiSecret")?;
+  let passphrase = get_secret(&env, credentials, "passphrase")?;
+  Ok(
+    CoinbaseCredentials {
+      api_key,
+      api_secret: expose(api_secret),
+      passphrase: expose(passphrase)
+    }
+  )
+}
+
+/// The parameters of a client with the credentials of a `NashCredentials`, `BinanceCredentials` or
+/// `CoinbaseCredentials`, which has to be of the client's exchange.
+fn get_updated_credentials(
+  env: &JNIEnv,
+  params: &SecretParams,
+  credentials: &JObject,
+) -> Result<SecretParams, String> {
+  let is_instance = |cls_name: &str| env.is_instance_of(*credentials, cls_name.trim_start_matches('L').trim_end_matches(';')).unwrap_or(false);
+  let mut updated = params.clone();
+  updated.scrub();
+  match updated.params_mut() {
+    InitAnyExchange::Nash(nash) if is_instance(NASH_CREDENTIALS_CLS_NAME) => nash.credentials = Some(get_nash_credentials(env, credentials)?),
+    InitAnyExchange::Binance(binance) if is_instance(BINANCE_CREDENTIALS_CLS_NAME) => binance.credentials = Some(get_binance_credentials(env, credentials)?),
+    InitAnyExchange::Coinbase(coinbase) if is_instance(COINBASE_CREDENTIALS_CLS_NAME) => coinbase.credentials = Some(get_coinbase_credentials(env, credentials)?),
+    _ => return Err(format!("Expected the credentials of a {} client", exchange_name(params.params()))),
+  }
+  Ok(updated)
 }
 
 fn get_options_coinbase(
@@ -1580,8 +3752,8 @@ fn get_options_coinbase(
   coinbase: &JObject,
 ) -> Result<InitAnyExchange, String> {
 
-  let credentials = get_options_coinbase_credentials(env, coinbase)?;
   let sandbox = get_boolean_field(env, coinbase, "sandbox")?;
+  let credentials = get_options_coinbase_credentials(env, coinbase)?;
 
   Ok(
     InitAnyExchange::Coinbase(
@@ -1607,4 +3779,323 @@ fn get_options(
     // (_, Ok(binance)) => {},
     _ => Err(String::from("Invalid config, no config found"))
   }
-}
\ No newline at end of file
+}
+
+/// Every problem of a configuration, one per line.
+fn invalid_config(source: &str, errors: Vec<String>) -> OpenlimitsJavaError {
+  let lines: Vec<String> = errors.iter().map(|error| format!("  {}", error)).collect();
+  OpenlimitsJavaError::InvalidArgument(format!("Invalid configuration {}\n{}", source, lines.join("\n")))
+}
+
+/// A `char[]` of a secret, which `get_secret` clears once the client has read it.
+fn secret_to_jchars<'a>(env: &JNIEnv<'a>, secret: &str) -> errors::Result<JObject<'a>> {
+  let chars: Zeroizing<Vec<u16>> = Zeroizing::new(secret.encode_utf16().collect());
+  let array = env.new_char_array(chars.len() as jsize)?;
+  env.set_char_array_region(array, 0, &chars)?;
+  Ok(JObject::from(array))
+}
+
+fn strings_to_jarray<'a>(env: &JNIEnv<'a>, strings: &[String]) -> errors::Result<JValue<'a>> {
+  let strings: Vec<JObject> = strings.iter().map(|s| env.new_string(s).map(JObject::from)).collect::<errors::Result<_>>()?;
+  vec_to_java_arr(env, env.find_class("java/lang/String")?, &strings)
+}
+
+/// The `ExchangeClientConfig` of a configuration, built the way Java would build it. Credentials are handed over as
+/// `char[]`, so they never become Java strings.
+fn client_file_to_jobject<'a>(env: &JNIEnv<'a>, file: ClientFile) -> errors::Result<JObject<'a>> {
+  let ClientFile { params, options } = file;
+  let config = match params.params() {
+    InitAnyExchange::Nash(nash) => {
+      let credentials = match &nash.credentials {
+        Some(credentials) => env.new_object(
+          "io/nash/openlimits/NashCredentials",
+          "([C[C)V",
+          &[secret_to_jchars(env, &credentials.secret)?.into(), secret_to_jchars(env, &credentials.session)?.into()]
+        )?,
+        None => JObject::null(),
+      };
+      let environment = match nash.environment {
+        Environment::Production => "production",
+        Environment::Sandbox => "sandbox",
+      };
+      let nash = env.new_object(
+        "io/nash/openlimits/NashConfig",
+        "(Lio/nash/openlimits/NashCredentials;JLjava/lang/String;JLjava/lang/String;)V",
+        &[
+          credentials.into(),
+          JValue::Long(nash.client_id as i64),
+          env.new_string(environment)?.into(),
+          JValue::Long(nash.timeout.as_millis() as i64),
+          optional_string_to_jstring(env, nash.affiliate_code.clone())?,
+        ]
+      )?;
+      env.new_object("io/nash/openlimits/ExchangeClientConfig", "(Lio/nash/openlimits/NashConfig;)V", &[nash.into()])?
+    },
+    InitAnyExchange::Binance(binance) => {
+      let credentials = match &binance.credentials {
+        Some(credentials) => env.new_object(
+          "io/nash/openlimits/BinanceCredentials",
+          "(Ljava/lang/String;[C)V",
+          &[env.new_string(&credentials.api_key)?.into(), secret_to_jchars(env, &credentials.api_secret)?.into()]
+        )?,
+        None => JObject::null(),
+      };
+      let binance = env.new_object(
+        "io/nash/openlimits/BinanceConfig",
+        "(ZLio/nash/openlimits/BinanceCredentials;)V",
+        &[JValue::Bool(binance.sandbox as u8), credentials.into()]
+      )?;
+      env.new_object("io/nash/openlimits/ExchangeClientConfig", "(Lio/nash/openlimits/BinanceConfig;)V", &[binance.into()])?
+    },
+    InitAnyExchange::Coinbase(coinbase) => {
+      let credentials = match &coinbase.credentials {
+        Some(credentials) => env.new_object(
+          "io/nash/openlimits/CoinbaseCredentials",
+          "(Ljava/lang/String;[C[C)V",
+          &[
+            env.new_string(&credentials.api_key)?.into(),
+            secret_to_jchars(env, &credentials.api_secret)?.into(),
+            secret_to_jchars(env, &credentials.passphrase)?.into(),
+          ]
+        )?,
+        None => JObject::null(),
+      };
+      let coinbase = env.new_object(
+        "io/nash/openlimits/CoinbaseConfig",
+        "(ZLio/nash/openlimits/CoinbaseCredentials;)V",
+        &[JValue::Bool(coinbase.sandbox as u8), credentials.into()]
+      )?;
+      env.new_object("io/nash/openlimits/ExchangeClientConfig", "(Lio/nash/openlimits/CoinbaseConfig;)V", &[coinbase.into()])?
+    },
+  };
+  with_client_options(env, config, options)
+}
+
+/// Applies the options of a configuration through the `with...` methods of `ExchangeClientConfig`.
+fn with_client_options<'a>(env: &JNIEnv<'a>, mut config: JObject<'a>, options: ClientOptions) -> errors::Result<JObject<'a>> {
+  let mut with = |name: &str, sig: &str, args: &[JValue]| -> errors::Result<()> {
+    config = env.call_method(config, name, format!("({})Lio/nash/openlimits/ExchangeClientConfig;", sig), args)?.l()?;
+    Ok(())
+  };
+  let enum_value = |cls: &str, name: &str| -> errors::Result<JValue<'a>> {
+    env.call_static_method(cls, "valueOf", format!("(Ljava/lang/String;)L{};", cls), &[env.new_string(name)?.into()])
+  };
+  if options.backpressure_policy.is_some() || options.callback_queue_capacity.is_some() || options.conflate_orderbooks {
+    let policy = enum_value("io/nash/openlimits/BackpressurePolicy", options.backpressure_policy.as_deref().unwrap_or("Block"))?;
+    let capacity = JValue::Int(options.callback_queue_capacity.unwrap_or(100) as i32);
+    with("withBackpressure", "Lio/nash/openlimits/BackpressurePolicy;IZ", &[policy, capacity, JValue::Bool(options.conflate_orderbooks as u8)])?;
+  }
+  if let Some(mode) = &options.streaming_mode {
+    let mode = enum_value("io/nash/openlimits/StreamingMode", mode)?;
+    with("withStreamingMode", "Lio/nash/openlimits/StreamingMode;", &[mode])?;
+  }
+  if options.direct_orderbooks {
+    with("withDirectOrderbooks", "", &[])?;
+  }
+  if options.fixed_point {
+    with("withFixedPoint", "", &[])?;
+  }
+  if let Some(millis) = options.account_poll_millis {
+    with("withAccountPollInterval", "J", &[JValue::Long(millis as i64)])?;
+  }
+  if options.track_orders {
+    with("withOrderTracking", "", &[])?;
+  }
+  if !options.position_markets.is_empty() {
+    with("withPositionTracking", "[Ljava/lang/String;", &[strings_to_jarray(env, &options.position_markets)?])?;
+  }
+  if let Some(limits) = options.risk_limits {
+    let mut risk_limits = env.new_object(RISK_LIMITS_CLS_NAME.trim_start_matches('L').trim_end_matches(';'), "()V", &[])?;
+    let mut limit = |name: &str, sig: &str, value: JValue| -> errors::Result<()> {
+      risk_limits = env.call_method(risk_limits, name, format!("({})Lio/nash/openlimits/RiskLimits;", sig), &[value])?.l()?;
+      Ok(())
+    };
+    if let Some(max) = limits.max_order_notional {
+      limit("withMaxOrderNotional", "Ljava/math/BigDecimal;", decimal_to_jvalue(env, max)?)?;
+    }
+    if let Some(max) = limits.max_position {
+      limit("withMaxPosition", "Ljava/math/BigDecimal;", decimal_to_jvalue(env, max)?)?;
+    }
+    if let Some(max) = limits.max_open_orders {
+      limit("withMaxOpenOrders", "I", JValue::Int(max as i32))?;
+    }
+    if let Some(band) = limits.price_band {
+      limit("withPriceBand", "Ljava/math/BigDecimal;", decimal_to_jvalue(env, band)?)?;
+    }
+    with("withRiskLimits", "Lio/nash/openlimits/RiskLimits;", &[risk_limits.into()])?;
+  }
+  if let Some((millis, markets)) = &options.dead_mans_switch {
+    with("withDeadMansSwitch", "J[Ljava/lang/String;", &[JValue::Long(*millis as i64), strings_to_jarray(env, markets)?])?;
+  }
+  if let Some((dir, max_bytes)) = &options.audit_journal {
+    with("withAuditJournal", "Ljava/lang/String;J", &[env.new_string(dir)?.into(), JValue::Long(*max_bytes as i64)])?;
+  }
+  Ok(config)
+}
+
+fn get_queue_options(
+  env: &JNIEnv,
+  opts: &JObject,
+) -> Result<QueueOptions, String> {
+  let policy = match get_enum_name(env, opts, "backpressurePolicy", BACKPRESSURE_POLICY_CLS_NAME, |e| &e.backpressure_policy)? {
+    "Block" => OverflowPolicy::Block,
+    "DropOldest" => OverflowPolicy::DropOldest,
+    "DropNewest" => OverflowPolicy::DropNewest,
+    p => return Err(format!("Invalid backpressure policy {}", p))
+  };
+  let capacity = get_field(env, opts, "callbackQueueCapacity", "I")?
+    .ok_or("Could not find field callbackQueueCapacity")?
+    .i()
+    .map_err(|_| String::from("callbackQueueCapacity not int"))?;
+  if capacity <= 0 {
+    return Err(format!("callbackQueueCapacity must be positive, got {}", capacity));
+  }
+  let conflate_orderbooks = get_boolean_field(env, opts, "conflateOrderbooks")?;
+
+  Ok(QueueOptions { policy, capacity: capacity as usize, conflate_orderbooks })
+}
+
+fn get_account_poll_interval(
+  env: &JNIEnv,
+  opts: &JObject,
+) -> Result<std::time::Duration, String> {
+  let millis = get_field(env, opts, "accountPollMillis", "J")?
+    .ok_or("Could not find field accountPollMillis")?
+    .j()
+    .map_err(|_| String::from("accountPollMillis not long"))?;
+  if millis <= 0 {
+    return Err(format!("accountPollMillis must be positive, got {}", millis));
+  }
+  Ok(std::time::Duration::from_millis(millis as u64))
+}
+
+/// The directory and file size limit of `withAuditJournal`, `None` without it.
+fn get_audit_journal_options(env: &JNIEnv, opts: &JObject) -> Result<Option<(String, u64)>, String> {
+  let dir = match get_string(env, opts, "auditJournalDirectory")? {
+    Some(dir) => dir,
+    None => return Ok(None),
+  };
+  let max_bytes = get_field(env, opts, "auditJournalMaxFileBytes", "J")?
+    .ok_or("Could not find field auditJournalMaxFileBytes")?
+    .j()
+    .map_err(|_| String::from("auditJournalMaxFileBytes not long"))?;
+  if max_bytes <= 0 {
+    return Err(format!("auditJournalMaxFileBytes must be positive, got {}", max_bytes));
+  }
+  Ok(Some((dir, max_bytes as u64)))
+}
+
+/// The heartbeat timeout and markets of `withDeadMansSwitch`, `None` without it.
+fn get_dead_mans_switch_options(env: &JNIEnv, opts: &JObject) -> Result<Option<(std::time::Duration, Vec<String>)>, String> {
+  let millis = get_field(env, opts, "deadMansSwitchMillis", "J")?
+    .ok_or("Could not find field deadMansSwitchMillis")?
+    .j()
+    .map_err(|_| String::from("deadMansSwitchMillis not long"))?;
+  if millis == 0 {
+    return Ok(None);
+  }
+  if millis < 0 {
+    return Err(format!("deadMansSwitchMillis must be positive, got {}", millis));
+  }
+  let markets = get_string_array(env, opts, "deadMansSwitchMarkets")?;
+  if markets.is_empty() {
+    return Err(String::from("withDeadMansSwitch needs at least one market to cancel"));
+  }
+  Ok(Some((std::time::Duration::from_millis(millis as u64), markets)))
+}
+
+/// `None` without `withRiskLimits`. Limits that need a tracker the client wasn't configured with are refused
+/// rather than never enforced.
+fn get_risk_limits(env: &JNIEnv, opts: &JObject, track_orders: bool, track_positions: bool) -> Result<Option<RiskLimits>, String> {
+  let limits = match get_object(env, opts, "riskLimits", RISK_LIMITS_CLS_NAME)? {
+    Some(limits) => limits,
+    None => return Ok(None),
+  };
+  let positive = |field: &str| -> Result<Option<Decimal>, String> {
+    match get_decimal(env, &limits, field)? {
+      Some(value) if value <= Decimal::default() => Err(format!("{} must be positive, got {}", field, value)),
+      value => Ok(value),
+    }
+  };
+  let max_order_notional = positive("maxOrderNotional")?;
+  let max_position = positive("maxPosition")?;
+  let price_band = positive("priceBandPercent")?;
+  let max_open_orders = get_field(env, &limits, "maxOpenOrders", "I")?
+    .ok_or("Could not find field maxOpenOrders")?
+    .i()
+    .map_err(|_| String::from("maxOpenOrders not int"))?;
+  if max_open_orders < 0 {
+    return Err(format!("maxOpenOrders must not be negative, got {}", max_open_orders));
+  }
+  if max_position.is_some() && !(track_positions && track_orders) {
+    return Err(String::from("maxPosition requires ExchangeClientConfig.withPositionTracking() and withOrderTracking()"));
+  }
+  if max_open_orders > 0 && !track_orders {
+    return Err(String::from("maxOpenOrders requires ExchangeClientConfig.withOrderTracking()"));
+  }
+  Ok(Some(RiskLimits {
+    max_order_notional,
+    max_position,
+    max_open_orders: if max_open_orders > 0 { Some(max_open_orders as usize) } else { None },
+    price_band,
+  }))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum StreamingMode {
+  // Open the websocket when the client is created
+  Eager,
+  // Open the websocket on the first subscribe
+  Lazy,
+  // REST only, subscribing fails
+  Disabled,
+}
+
+fn get_streaming_mode(
+  env: &JNIEnv,
+  opts: &JObject,
+) -> Result<StreamingMode, String> {
+  match get_enum_name(env, opts, "streamingMode", STREAMING_MODE_CLS_NAME, |e| &e.streaming_mode)? {
+    "Eager" => Ok(StreamingMode::Eager),
+    "Lazy" => Ok(StreamingMode::Lazy),
+    "Disabled" => Ok(StreamingMode::Disabled),
+    m => Err(format!("Invalid streaming mode {}", m))
+  }
+}
+
+fn get_fault_rule(env: &JNIEnv, rule: &JObject) -> Result<FaultRule, String> {
+  let action = match get_enum_name(env, rule, "type", FAULT_TYPE_CLS_NAME, |e| &e.fault_type)? {
+    "Error" => {
+      let error = get_string_non_null(env, rule, "error")?;
+      FaultAction::Error(faults::injectable_error(&error).ok_or(format!("Cannot inject {}", error))?)
+    },
+    "Latency" => {
+      let latency_ms = get_field(env, rule, "latencyMs", "J")?
+        .ok_or("Could not find field latencyMs")?
+        .j()
+        .map_err(|_| String::from("latencyMs not long"))?;
+      if latency_ms < 0 {
+        return Err(format!("latencyMs must not be negative, got {}", latency_ms));
+      }
+      FaultAction::Latency(std::time::Duration::from_millis(latency_ms as u64))
+    },
+    "DropMessage" => FaultAction::DropMessage,
+    "DuplicateMessage" => FaultAction::DuplicateMessage,
+    "Disconnect" => FaultAction::Disconnect,
+    t => return Err(format!("Invalid fault type {}", t))
+  };
+  let times = get_field(env, rule, "times", "I")?
+    .ok_or("Could not find field times")?
+    .i()
+    .map_err(|_| String::from("times not int"))?;
+  if times < 0 {
+    return Err(format!("times must not be negative, got {}", times));
+  }
+
+  Ok(FaultRule {
+    operation: get_string(env, rule, "operation")?,
+    market: get_string(env, rule, "market")?,
+    action,
+    remaining: if times == 0 { None } else { Some(times as u64) },
+  })
+}
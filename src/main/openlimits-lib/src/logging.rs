@@ -0,0 +1,107 @@
+use jni::{JNIEnv, JavaVM};
+use jni::objects::{GlobalRef, JClass, JObject, JValue};
+use jni::sys::jint;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::Once;
+use crate::redact::redact;
+
+static NATIVE_LOGGER_CLS_NAME: &str = "io/nash/openlimits/NativeLogger";
+static INSTALL: Once = Once::new();
+
+/// Forwards `log` records to `io.nash.openlimits.NativeLogger.log(int, String, String)`.
+struct JvmLogger {
+  jvm: JavaVM,
+  // Pinned here because threads attached from native code cannot resolve application classes
+  class: GlobalRef,
+}
+
+fn level_to_jint(level: Level) -> jint {
+  match level {
+    Level::Error => 1,
+    Level::Warn => 2,
+    Level::Info => 3,
+    Level::Debug => 4,
+    Level::Trace => 5,
+  }
+}
+
+pub fn jint_to_level_filter(level: jint) -> LevelFilter {
+  match level {
+    i32::MIN..=0 => LevelFilter::Off,
+    1 => LevelFilter::Error,
+    2 => LevelFilter::Warn,
+    3 => LevelFilter::Info,
+    4 => LevelFilter::Debug,
+    _ => LevelFilter::Trace,
+  }
+}
+
+impl JvmLogger {
+  fn forward(&self, env: &JNIEnv, record: &Record, message: &str) -> jni::errors::Result<()> {
+    env.with_local_frame(4, || {
+      let target = env.new_string(record.target())?;
+      let message = env.new_string(message)?;
+      env.call_static_method(
+        JClass::from(self.class.as_obj()),
+        "log",
+        "(ILjava/lang/String;Ljava/lang/String;)V",
+        &[JValue::Int(level_to_jint(record.level())), target.into(), message.into()]
+      )?;
+      Ok(JObject::null())
+    })?;
+    Ok(())
+  }
+}
+
+impl Log for JvmLogger {
+  fn enabled(&self, metadata: &Metadata) -> bool {
+    metadata.level() <= log::max_level()
+  }
+
+  fn log(&self, record: &Record) {
+    if !self.enabled(record.metadata()) {
+      return;
+    }
+    let message = redact(&record.args().to_string());
+    // Daemon attachment is permanent, so tokio worker threads only pay for it once and never block JVM exit
+    let result = self.jvm.attach_current_thread_as_daemon().and_then(|env| {
+      // Calling into the JVM with a pending exception is undefined behaviour, and the exception is the caller's to
+      // handle, so the record goes to stderr instead
+      if env.exception_check()? {
+        return Err(jni::errors::Error::JavaException);
+      }
+      let result = self.forward(&env, record, &message);
+      // Only ever the exception the logger itself raised
+      if result.is_err() && env.exception_check().unwrap_or(false) {
+        env.exception_clear().ok();
+      }
+      result
+    });
+    if result.is_err() {
+      eprintln!("[{}] {}: {}", record.level(), record.target(), message);
+    }
+  }
+
+  fn flush(&self) {}
+}
+
+/// Installs the JVM logger as the global `log` backend. Only the first call has any effect.
+pub fn install(env: &JNIEnv, level: LevelFilter) -> jni::errors::Result<()> {
+  let mut result = Ok(());
+  INSTALL.call_once(|| {
+    let logger = env.get_java_vm().and_then(|jvm| {
+      let class = env.new_global_ref(env.find_class(NATIVE_LOGGER_CLS_NAME)?)?;
+      Ok(JvmLogger { jvm, class })
+    });
+    result = logger.map(|logger| {
+      if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(level);
+      }
+    });
+  });
+  result
+}
+
+pub fn set_level(level: LevelFilter) {
+  log::set_max_level(level);
+}
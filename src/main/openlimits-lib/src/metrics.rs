@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds (inclusive) of the latency buckets in microseconds. Anything slower lands in the overflow bucket.
+pub static LATENCY_BUCKET_BOUNDS_MICROS: [u64; 14] = [
+  100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000, 1_000_000, 5_000_000
+];
+
+#[derive(Clone, Default)]
+pub struct LatencyHistogram {
+  pub count: u64,
+  pub sum_micros: u64,
+  pub max_micros: u64,
+  // One extra bucket at the end for samples above the last bound
+  pub buckets: [u64; 15],
+}
+
+impl LatencyHistogram {
+  fn record(&mut self, elapsed: Duration) {
+    let micros = elapsed.as_micros() as u64;
+    let bucket = LATENCY_BUCKET_BOUNDS_MICROS.iter()
+      .position(|bound| micros <= *bound)
+      .unwrap_or(LATENCY_BUCKET_BOUNDS_MICROS.len());
+    self.buckets[bucket] += 1;
+    self.count += 1;
+    self.sum_micros += micros;
+    self.max_micros = self.max_micros.max(micros);
+  }
+}
+
+/// Per client counters, shared between the JNI entry points and the websocket threads.
+#[derive(Default)]
+pub struct Metrics {
+  latencies: Mutex<HashMap<&'static str, LatencyHistogram>>,
+  errors: Mutex<HashMap<&'static str, u64>>,
+  subscription_messages: Mutex<HashMap<String, u64>>,
+  queue_depth: AtomicI64,
+  max_queue_depth: AtomicI64,
+}
+
+pub struct MetricsSnapshot {
+  pub latencies: Vec<(&'static str, LatencyHistogram)>,
+  pub errors: Vec<(&'static str, u64)>,
+  pub subscription_messages: Vec<(String, u64)>,
+  pub queue_depth: i64,
+  pub max_queue_depth: i64,
+}
+
+impl Metrics {
+  pub fn record_latency(&self, operation: &'static str, elapsed: Duration) {
+    if let Ok(mut latencies) = self.latencies.lock() {
+      latencies.entry(operation).or_default().record(elapsed);
+    }
+  }
+
+  pub fn record_error(&self, error_class: &'static str) {
+    if let Ok(mut errors) = self.errors.lock() {
+      *errors.entry(error_class).or_default() += 1;
+    }
+  }
+
+  pub fn record_subscription_message(&self, subscription: &str) {
+    if let Ok(mut messages) = self.subscription_messages.lock() {
+      match messages.get_mut(subscription) {
+        Some(count) => *count += 1,
+        None => {
+          messages.insert(String::from(subscription), 1);
+        }
+      }
+    }
+  }
+
+  pub fn message_enqueued(&self) {
+    let depth = self.queue_depth.fetch_add(1, Ordering::Relaxed) + 1;
+    self.max_queue_depth.fetch_max(depth, Ordering::Relaxed);
+  }
+
+  pub fn message_dequeued(&self) {
+    self.queue_depth.fetch_sub(1, Ordering::Relaxed);
+  }
+
+  pub fn snapshot(&self) -> MetricsSnapshot {
+    let latencies = self.latencies.lock()
+      .map(|l| l.iter().map(|(k, v)| (*k, v.clone())).collect())
+      .unwrap_or_default();
+    let errors = self.errors.lock()
+      .map(|e| e.iter().map(|(k, v)| (*k, *v)).collect())
+      .unwrap_or_default();
+    let subscription_messages = self.subscription_messages.lock()
+      .map(|m| m.iter().map(|(k, v)| (k.clone(), *v)).collect())
+      .unwrap_or_default();
+
+    MetricsSnapshot {
+      latencies,
+      errors,
+      subscription_messages,
+      queue_depth: self.queue_depth.load(Ordering::Relaxed).max(0),
+      max_queue_depth: self.max_queue_depth.load(Ordering::Relaxed),
+    }
+  }
+}
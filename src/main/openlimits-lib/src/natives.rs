@@ -0,0 +1,112 @@
+use std::ffi::c_void;
+use jni::JNIEnv;
+use jni::NativeMethod;
+use jni::sys::jint;
+use crate::*;
+
+/// Bumped whenever a native method, its signature or a class built by the library changes. Has to match
+/// `NativeProtocol.VERSION` of the jar the library is loaded from.
+pub const PROTOCOL_VERSION: jint = 13;
+
+static NATIVE_PROTOCOL_CLS_NAME: &str = "io/nash/openlimits/NativeProtocol";
+static INCOMPATIBLE_NATIVE_LIBRARY_CLS_NAME: &str = "io/nash/openlimits/IncompatibleNativeLibrary";
+static EXCHANGE_CLIENT_CLS_NAME: &str = "io/nash/openlimits/ExchangeClient";
+static NATIVE_LOGGER_CLS_NAME: &str = "io/nash/openlimits/NativeLogger";
+static AUDIT_JOURNAL_CLS_NAME: &str = "io/nash/openlimits/AuditJournal";
+
+macro_rules! native {
+  ($name:expr, $sig:expr, $f:path) => { ($name, $sig, $f as *mut c_void) };
+}
+
+type Native = (&'static str, &'static str, *mut c_void);
+
+fn exchange_client_natives() -> Vec<Native> {
+  vec![
+    native!("init", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/ExchangeClientConfig;)V", Java_io_nash_openlimits_ExchangeClient_init),
+    native!("subscribe", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/Subscription;)V", Java_io_nash_openlimits_ExchangeClient_subscribe),
+    native!("disconnect", "(Lio/nash/openlimits/ExchangeClient;)V", Java_io_nash_openlimits_ExchangeClient_disconnect),
+    native!("connect", "(Lio/nash/openlimits/ExchangeClient;)Z", Java_io_nash_openlimits_ExchangeClient_connect),
+    native!("closeClient", "(Lio/nash/openlimits/ExchangeClient;)V", Java_io_nash_openlimits_ExchangeClient_closeClient),
+    native!("updateCredentials", "(Lio/nash/openlimits/ExchangeClient;Ljava/lang/Object;)Z", Java_io_nash_openlimits_ExchangeClient_updateCredentials),
+    native!("heartbeat", "(Lio/nash/openlimits/ExchangeClient;)V", Java_io_nash_openlimits_ExchangeClient_heartbeat),
+    native!("simulateSocketError", "(Lio/nash/openlimits/ExchangeClient;)V", Java_io_nash_openlimits_ExchangeClient_simulateSocketError),
+    native!("simulateMessages", "(Lio/nash/openlimits/ExchangeClient;Ljava/lang/String;J)V", Java_io_nash_openlimits_ExchangeClient_simulateMessages),
+    native!("getMetrics", "(Lio/nash/openlimits/ExchangeClient;)Lio/nash/openlimits/MetricsSnapshot;", Java_io_nash_openlimits_ExchangeClient_getMetrics),
+    native!("isPoisoned", "(Lio/nash/openlimits/ExchangeClient;)Z", Java_io_nash_openlimits_ExchangeClient_isPoisoned),
+    native!("injectFault", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/FaultRule;)V", Java_io_nash_openlimits_ExchangeClient_injectFault),
+    native!("clearFaults", "(Lio/nash/openlimits/ExchangeClient;)V", Java_io_nash_openlimits_ExchangeClient_clearFaults),
+    native!("orderBook", "(Lio/nash/openlimits/ExchangeClient;Ljava/lang/String;)Lio/nash/openlimits/OrderbookResponse;", Java_io_nash_openlimits_ExchangeClient_orderBook),
+    native!("getPriceTicker", "(Lio/nash/openlimits/ExchangeClient;Ljava/lang/String;)Lio/nash/openlimits/Ticker;", Java_io_nash_openlimits_ExchangeClient_getPriceTicker),
+    native!("getHistoricRates", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/GetHistoryRatesRequest;)[Lio/nash/openlimits/Candle;", Java_io_nash_openlimits_ExchangeClient_getHistoricRates),
+    native!("getHistoricTrades", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/GetHistoryTradeRequest;)[Lio/nash/openlimits/Trade;", Java_io_nash_openlimits_ExchangeClient_getHistoricTrades),
+    native!("limitBuy", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/LimitRequest;)Lio/nash/openlimits/Order;", Java_io_nash_openlimits_ExchangeClient_limitBuy),
+    native!("limitSell", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/LimitRequest;)Lio/nash/openlimits/Order;", Java_io_nash_openlimits_ExchangeClient_limitSell),
+    native!("marketBuy", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/MarketRequest;)Lio/nash/openlimits/Order;", Java_io_nash_openlimits_ExchangeClient_marketBuy),
+    native!("marketSell", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/MarketRequest;)Lio/nash/openlimits/Order;", Java_io_nash_openlimits_ExchangeClient_marketSell),
+    native!("setGlobalKillSwitch", "(Z)V", Java_io_nash_openlimits_ExchangeClient_setGlobalKillSwitch),
+    native!("readConfigFile", "(Ljava/lang/String;)Lio/nash/openlimits/ExchangeClientConfig;", Java_io_nash_openlimits_ExchangeClient_readConfigFile),
+    native!("readConfigEnvironment", "(Ljava/lang/String;)Lio/nash/openlimits/ExchangeClientConfig;", Java_io_nash_openlimits_ExchangeClient_readConfigEnvironment),
+    native!("isGlobalKillSwitchEngaged", "()Z", Java_io_nash_openlimits_ExchangeClient_isGlobalKillSwitchEngaged),
+    native!("getAllOpenOrders", "(Lio/nash/openlimits/ExchangeClient;)[Lio/nash/openlimits/Order;", Java_io_nash_openlimits_ExchangeClient_getAllOpenOrders),
+    native!("getTrackedOrders", "(Lio/nash/openlimits/ExchangeClient;Ljava/lang/String;)[Lio/nash/openlimits/Order;", Java_io_nash_openlimits_ExchangeClient_getTrackedOrders),
+    native!("getPositions", "(Lio/nash/openlimits/ExchangeClient;)[Lio/nash/openlimits/Position;", Java_io_nash_openlimits_ExchangeClient_getPositions),
+    native!("getAssetPositions", "(Lio/nash/openlimits/ExchangeClient;)[Lio/nash/openlimits/AssetPosition;", Java_io_nash_openlimits_ExchangeClient_getAssetPositions),
+    native!("reconcileOrders", "(Lio/nash/openlimits/ExchangeClient;)V", Java_io_nash_openlimits_ExchangeClient_reconcileOrders),
+    native!("getOrderHistory", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/GetOrderHistoryRequest;)[Lio/nash/openlimits/Order;", Java_io_nash_openlimits_ExchangeClient_getOrderHistory),
+    native!("getOrder", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/GetOrderRequest;)Lio/nash/openlimits/Order;", Java_io_nash_openlimits_ExchangeClient_getOrder),
+    native!("getTradeHistory", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/TradeHistoryRequest;)[Lio/nash/openlimits/Trade;", Java_io_nash_openlimits_ExchangeClient_getTradeHistory),
+    native!("getAccountBalances", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/Paginator;)[Lio/nash/openlimits/Balance;", Java_io_nash_openlimits_ExchangeClient_getAccountBalances),
+    native!("cancelOrder", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/CancelOrderRequest;)Lio/nash/openlimits/OrderCanceled;", Java_io_nash_openlimits_ExchangeClient_cancelOrder),
+    native!("cancelAllOrders", "(Lio/nash/openlimits/ExchangeClient;Lio/nash/openlimits/CancelAllOrdersRequest;)[Lio/nash/openlimits/OrderCanceled;", Java_io_nash_openlimits_ExchangeClient_cancelAllOrders),
+    native!("receivePairs", "(Lio/nash/openlimits/ExchangeClient;)[Lio/nash/openlimits/MarketPair;", Java_io_nash_openlimits_ExchangeClient_receivePairs),
+  ]
+}
+
+fn audit_journal_natives() -> Vec<Native> {
+  vec![
+    native!("readRecords", "(Ljava/lang/String;JJ)[Lio/nash/openlimits/AuditRecord;", Java_io_nash_openlimits_AuditJournal_readRecords),
+  ]
+}
+
+fn native_logger_natives() -> Vec<Native> {
+  vec![
+    native!("install", "(I)V", Java_io_nash_openlimits_NativeLogger_install),
+    native!("setNativeLevel", "(I)V", Java_io_nash_openlimits_NativeLogger_setNativeLevel),
+  ]
+}
+
+/// Checks that the jar speaks the same protocol and binds every native method. Errors describe the mismatch.
+pub fn register(env: &JNIEnv) -> Result<(), String> {
+  let version = env.get_static_field(NATIVE_PROTOCOL_CLS_NAME, "VERSION", "I")
+    .and_then(|version| version.i())
+    .map_err(|_| format!("the jar has no {}, it is older than the native library", NATIVE_PROTOCOL_CLS_NAME.replace('/', ".")))?;
+  if version != PROTOCOL_VERSION {
+    return Err(format!("the jar speaks protocol version {}, the native library version {}", version, PROTOCOL_VERSION));
+  }
+
+  register_class(env, EXCHANGE_CLIENT_CLS_NAME, exchange_client_natives())?;
+  register_class(env, AUDIT_JOURNAL_CLS_NAME, audit_journal_natives())?;
+  register_class(env, NATIVE_LOGGER_CLS_NAME, native_logger_natives())
+}
+
+fn register_class(env: &JNIEnv, class_name: &str, natives: Vec<Native>) -> Result<(), String> {
+  let cls = env.find_class(class_name).map_err(|_| format!("the jar has no {}", class_name.replace('/', ".")))?;
+  // One at a time, so the error names the method that doesn't match
+  for (name, sig, fn_ptr) in natives {
+    let method = NativeMethod { name: name.into(), sig: sig.into(), fn_ptr };
+    env.register_native_methods(cls, &[method])
+      .map_err(|_| format!("{}.{}{} does not match the jar", class_name.replace('/', "."), name, sig))?;
+  }
+  Ok(())
+}
+
+/// Replaces whatever the failed lookup left pending with an `IncompatibleNativeLibrary`, which `System.loadLibrary`
+/// rethrows. Falls back to `UnsatisfiedLinkError` for jars that predate it.
+pub fn throw_incompatible(env: &JNIEnv, reason: &str) {
+  env.exception_clear().ok();
+  let msg = format!("Incompatible openlimits_java native library: {}", reason);
+  if env.throw_new(INCOMPATIBLE_NATIVE_LIBRARY_CLS_NAME, msg.as_str()).is_err() {
+    env.exception_clear().ok();
+    env.throw_new("java/lang/UnsatisfiedLinkError", msg).ok();
+  }
+}
@@ -0,0 +1,34 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once any native code belonging to a client has panicked. Shared with the websocket threads.
+#[derive(Clone, Default)]
+pub struct PoisonFlag(Arc<AtomicBool>);
+
+impl PoisonFlag {
+  pub fn poison(&self) {
+    self.0.store(true, Ordering::SeqCst);
+  }
+
+  pub fn is_poisoned(&self) -> bool {
+    self.0.load(Ordering::SeqCst)
+  }
+}
+
+/// Runs `f` and turns a panic into its message, so it never unwinds across an `extern "system"` function
+/// or kills a thread silently.
+pub fn catch<T, F: FnOnce() -> T>(f: F) -> Result<T, String> {
+  panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| panic_message(payload.as_ref()))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+  if let Some(msg) = payload.downcast_ref::<&str>() {
+    String::from(*msg)
+  } else if let Some(msg) = payload.downcast_ref::<String>() {
+    msg.clone()
+  } else {
+    String::from("Unknown panic")
+  }
+}
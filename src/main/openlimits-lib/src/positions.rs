@@ -0,0 +1,276 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use rust_decimal::Decimal;
+use openlimits::exchange::traits::info::MarketPair;
+use openlimits::model::{OrderBookResponse, Side, Trade};
+
+/// The position built from the fills of one market, average cost based.
+#[derive(Clone, Default)]
+pub struct Position {
+  pub market: String,
+  // Positive when long, negative when short, in the base asset
+  pub net: Decimal,
+  // Zero while flat
+  pub average_entry: Decimal,
+  pub realised: Decimal,
+  pub fees: Decimal,
+  pub mid: Option<Decimal>,
+}
+
+impl Position {
+  pub fn unrealised(&self) -> Option<Decimal> {
+    self.mid.map(|mid| (mid - self.average_entry) * self.net)
+  }
+
+  fn fill(&mut self, side: Side, price: Decimal, qty: Decimal, fees: Decimal) {
+    let signed = match side {
+      Side::Buy => qty,
+      Side::Sell => -qty,
+    };
+    self.fees += fees;
+    // Nothing to average while flat
+    if qty.is_zero() {
+      return;
+    }
+    if self.net.is_zero() || self.net.is_sign_positive() == signed.is_sign_positive() {
+      let size = self.net.abs() + qty;
+      self.average_entry = (self.average_entry * self.net.abs() + price * qty) / size;
+      self.net += signed;
+      return;
+    }
+    // Reduces the position, and whatever is left over of the fill opens one on the other side
+    let closed = qty.min(self.net.abs());
+    let was_long = self.net.is_sign_positive();
+    self.realised += match was_long {
+      true => (price - self.average_entry) * closed,
+      false => (self.average_entry - price) * closed,
+    };
+    self.net += signed;
+    if self.net.is_zero() {
+      self.average_entry = Decimal::default();
+    } else if self.net.is_sign_positive() != was_long {
+      self.average_entry = price;
+    }
+  }
+}
+
+/// What the fills moved of one asset. openlimits doesn't say which asset a fee was paid in, fees are counted in the
+/// quote asset of their market.
+#[derive(Clone, Default)]
+pub struct AssetPosition {
+  pub asset: String,
+  pub net: Decimal,
+  pub fees: Decimal,
+}
+
+#[derive(Default)]
+struct Book {
+  positions: HashMap<String, Position>,
+  // Fills arrive from the trade history and from polling, both may repeat what was applied already
+  applied: HashSet<String>,
+}
+
+/// The positions of the markets the client was configured with, from their fills.
+pub struct PositionTracker {
+  // Lowercase market to (base, quote), for the markets that are tracked
+  assets: HashMap<String, (String, String)>,
+  book: Mutex<Book>,
+}
+
+impl PositionTracker {
+  /// Fails with the name of a market the exchange doesn't list.
+  pub fn new(markets: &[String], pairs: &[MarketPair]) -> Result<Self, String> {
+    let mut assets = HashMap::new();
+    for market in markets {
+      let pair = pairs.iter().find(|pair| pair.symbol.eq_ignore_ascii_case(market))
+        .ok_or_else(|| format!("Cannot track the position of unknown market {}", market))?;
+      assets.insert(market.to_lowercase(), (pair.base.clone(), pair.quote.clone()));
+    }
+    Ok(PositionTracker { assets, book: Mutex::new(Book::default()) })
+  }
+
+  pub fn markets(&self) -> Vec<String> {
+    self.assets.keys().cloned().collect()
+  }
+
+  /// Applies the fills of tracked markets that weren't applied before, oldest first.
+  pub fn apply(&self, mut fills: Vec<Trade>) {
+    fills.sort_by_key(|fill| fill.created_at);
+    let mut book = self.book.lock().unwrap_or_else(|e| e.into_inner());
+    for fill in fills {
+      let market = fill.market_pair.to_lowercase();
+      if !self.assets.contains_key(&market) || !book.applied.insert(fill.id.clone()) {
+        continue;
+      }
+      let position = book.positions.entry(market).or_insert_with(|| Position { market: fill.market_pair.clone(), ..Default::default() });
+      position.fill(fill.side, fill.price, fill.qty, fill.fees.unwrap_or_default());
+    }
+  }
+
+  /// Marks the position of a market to the mid of an order book snapshot. One-sided books leave the mark as is.
+  pub fn mark(&self, market: &str, orderbook: &OrderBookResponse) {
+    let key = market.to_lowercase();
+    if !self.assets.contains_key(&key) {
+      return;
+    }
+    let best_bid = orderbook.bids.iter().map(|level| level.price).max();
+    let best_ask = orderbook.asks.iter().map(|level| level.price).min();
+    if let (Some(bid), Some(ask)) = (best_bid, best_ask) {
+      let mut book = self.book.lock().unwrap_or_else(|e| e.into_inner());
+      let position = book.positions.entry(key).or_insert_with(|| Position { market: String::from(market), ..Default::default() });
+      position.mid = Some((bid + ask) / Decimal::new(2, 0));
+    }
+  }
+
+  /// The net position of a market, zero until its first fill. `None` when the market isn't tracked.
+  pub fn net(&self, market: &str) -> Option<Decimal> {
+    let key = market.to_lowercase();
+    if !self.assets.contains_key(&key) {
+      return None;
+    }
+    let book = self.book.lock().unwrap_or_else(|e| e.into_inner());
+    Some(book.positions.get(&key).map(|position| position.net).unwrap_or_default())
+  }
+
+  /// Every market that has seen a fill or a mark, by market.
+  pub fn positions(&self) -> Vec<Position> {
+    let book = self.book.lock().unwrap_or_else(|e| e.into_inner());
+    let mut positions: Vec<Position> = book.positions.values().cloned().collect();
+    positions.sort_by(|a, b| a.market.cmp(&b.market));
+    positions
+  }
+
+  /// The net flow of every asset the fills moved, by asset.
+  pub fn assets(&self) -> Vec<AssetPosition> {
+    let mut assets: BTreeMap<String, AssetPosition> = BTreeMap::new();
+    for position in self.positions() {
+      let (base, quote) = match self.assets.get(&position.market.to_lowercase()) {
+        Some(pair) => pair,
+        None => continue,
+      };
+      // What was paid for the open position and what closing it realised, in the quote asset
+      let quote_net = position.realised - position.average_entry * position.net - position.fees;
+      let entry = |asset: &String| AssetPosition { asset: asset.clone(), ..Default::default() };
+      assets.entry(base.clone()).or_insert_with(|| entry(base)).net += position.net;
+      let quote_position = assets.entry(quote.clone()).or_insert_with(|| entry(quote));
+      quote_position.net += quote_net;
+      quote_position.fees += position.fees;
+    }
+    assets.into_values().collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+  }
+
+  fn tracker() -> PositionTracker {
+    let pair = MarketPair {
+      base: String::from("BNB"),
+      quote: String::from("BTC"),
+      symbol: String::from("BNBBTC"),
+      base_increment: dec("0.01"),
+      quote_increment: dec("0.0000001"),
+      min_base_trade_size: None,
+      min_quote_trade_size: None,
+    };
+    PositionTracker::new(&[String::from("BNBBTC")], &[pair]).unwrap()
+  }
+
+  fn fill(id: &str, side: Side, qty: &str, price: &str, fees: &str) -> Trade {
+    Trade {
+      id: String::from(id),
+      buyer_order_id: None,
+      seller_order_id: None,
+      market_pair: String::from("BNBBTC"),
+      price: dec(price),
+      qty: dec(qty),
+      fees: Some(dec(fees)),
+      side,
+      liquidity: None,
+      // Applied in the order of their ids
+      created_at: id.parse().unwrap(),
+    }
+  }
+
+  fn only_position(tracker: &PositionTracker) -> Position {
+    tracker.positions().pop().unwrap()
+  }
+
+  #[test]
+  fn long_add_averages_the_entry() {
+    let tracker = tracker();
+    tracker.apply(vec![fill("1", Side::Buy, "1", "100", "0"), fill("2", Side::Buy, "3", "200", "0")]);
+    let position = only_position(&tracker);
+    assert_eq!(position.net, dec("4"));
+    assert_eq!(position.average_entry, dec("175"));
+    assert_eq!(position.realised, dec("0"));
+  }
+
+  #[test]
+  fn partial_close_realises_and_keeps_the_entry() {
+    let tracker = tracker();
+    tracker.apply(vec![fill("1", Side::Buy, "4", "175", "0"), fill("2", Side::Sell, "1", "300", "0")]);
+    let position = only_position(&tracker);
+    assert_eq!(position.net, dec("3"));
+    assert_eq!(position.average_entry, dec("175"));
+    assert_eq!(position.realised, dec("125"));
+  }
+
+  #[test]
+  fn close_through_zero_opens_a_short_at_the_fill_price() {
+    let tracker = tracker();
+    tracker.apply(vec![fill("1", Side::Buy, "3", "175", "0"), fill("2", Side::Sell, "5", "150", "0")]);
+    let position = only_position(&tracker);
+    assert_eq!(position.net, dec("-2"));
+    assert_eq!(position.average_entry, dec("150"));
+    assert_eq!(position.realised, dec("-75"));
+    // Buying back below the entry of a short gains
+    tracker.apply(vec![fill("3", Side::Buy, "2", "140", "0")]);
+    let position = only_position(&tracker);
+    assert_eq!(position.net, dec("0"));
+    assert_eq!(position.average_entry, dec("0"));
+    assert_eq!(position.realised, dec("-55"));
+  }
+
+  #[test]
+  fn fees_add_up_in_the_quote_asset() {
+    let tracker = tracker();
+    tracker.apply(vec![fill("1", Side::Buy, "2", "100", "0.5"), fill("2", Side::Sell, "1", "110", "0.25")]);
+    let position = only_position(&tracker);
+    assert_eq!(position.fees, dec("0.75"));
+    assert_eq!(position.realised, dec("10"));
+    let assets = tracker.assets();
+    assert_eq!(assets.len(), 2);
+    assert_eq!(assets[0].asset, "BNB");
+    assert_eq!(assets[0].net, dec("1"));
+    assert_eq!(assets[1].asset, "BTC");
+    // Paid 200, got 110 back and paid the fees
+    assert_eq!(assets[1].net, dec("-90.75"));
+    assert_eq!(assets[1].fees, dec("0.75"));
+  }
+
+  #[test]
+  fn applied_fills_are_skipped() {
+    let tracker = tracker();
+    tracker.apply(vec![fill("1", Side::Buy, "1", "100", "0.1")]);
+    tracker.apply(vec![fill("1", Side::Buy, "1", "100", "0.1"), fill("2", Side::Buy, "1", "120", "0")]);
+    let position = only_position(&tracker);
+    assert_eq!(position.net, dec("2"));
+    assert_eq!(position.average_entry, dec("110"));
+    assert_eq!(position.fees, dec("0.1"));
+  }
+
+  #[test]
+  fn untracked_markets_are_ignored() {
+    let tracker = tracker();
+    tracker.apply(vec![Trade { market_pair: String::from("ETHBTC"), ..fill("1", Side::Buy, "1", "100", "0") }]);
+    assert!(tracker.positions().is_empty());
+    assert_eq!(tracker.net("ETHBTC"), None);
+    assert_eq!(tracker.net("bnbbtc"), Some(dec("0")));
+  }
+}
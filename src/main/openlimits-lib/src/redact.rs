@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use once_cell::sync::Lazy;
+use zeroize::Zeroizing;
+
+const REDACTED: &str = "[REDACTED]";
+// Shorter values would mostly blank out unrelated text
+const MIN_SECRET_LEN: usize = 4;
+
+// Lowercase key fragments whose values are credentials or signatures, in query strings, headers, JSON and the debug
+// output of requests. A fragment also matches longer keys, e.g. `apiSecret` or `X-MBX-APIKEY`.
+const SENSITIVE_KEYS: &[&str] = &[
+  "signature",
+  "apikey",
+  "api_key",
+  "api-key",
+  "secret",
+  "passphrase",
+  "session",
+  "token",
+  "authorization",
+  "cb-access-key",
+  "cb-access-sign",
+];
+
+// The credentials of every open client, by registration
+static SECRETS: Lazy<Mutex<HashMap<u64, Vec<Zeroizing<String>>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The credentials of a client, redacted from every message while this lives.
+pub struct RegisteredSecrets {
+  id: u64,
+}
+
+pub fn register(secrets: Vec<Zeroizing<String>>) -> RegisteredSecrets {
+  let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+  let secrets = secrets.into_iter().filter(|secret| secret.len() >= MIN_SECRET_LEN).collect();
+  SECRETS.lock().unwrap_or_else(|e| e.into_inner()).insert(id, secrets);
+  RegisteredSecrets { id }
+}
+
+impl RegisteredSecrets {
+  /// Redacts these too, e.g. credentials a client is switching to.
+  pub fn add(&self, secrets: Vec<Zeroizing<String>>) {
+    let mut registered = SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+    registered.entry(self.id).or_default().extend(secrets.into_iter().filter(|secret| secret.len() >= MIN_SECRET_LEN));
+  }
+
+  /// Redacts only these from now on, e.g. once rotated credentials replaced the old ones.
+  pub fn replace(&self, secrets: Vec<Zeroizing<String>>) {
+    let mut registered = SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+    registered.insert(self.id, secrets.into_iter().filter(|secret| secret.len() >= MIN_SECRET_LEN).collect());
+  }
+}
+
+impl Drop for RegisteredSecrets {
+  fn drop(&mut self) {
+    SECRETS.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.id);
+  }
+}
+
+/// Replaces the credentials of open clients and the values of sensitive keys with `[REDACTED]`.
+pub fn redact(msg: &str) -> String {
+  let mut redacted = String::from(msg);
+  {
+    // Never log while holding this, the logger redacts too
+    let secrets = SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+    for secret in secrets.values().flatten() {
+      if redacted.contains(secret.as_str()) {
+        redacted = redacted.replace(secret.as_str(), REDACTED);
+      }
+    }
+  }
+  redact_values(&redacted)
+}
+
+fn redact_values(msg: &str) -> String {
+  let lower = msg.to_ascii_lowercase();
+  let lower = lower.as_bytes();
+  let bytes = msg.as_bytes();
+  let len = bytes.len();
+  let mut out = String::with_capacity(len);
+  let mut copied = 0;
+  let mut i = 0;
+  while i < len {
+    let key_len = match SENSITIVE_KEYS.iter().find(|key| lower[i..].starts_with(key.as_bytes())) {
+      Some(key) => key.len(),
+      None => {
+        i += 1;
+        continue;
+      }
+    };
+    let mut j = i + key_len;
+    // The rest of the key, then its closing quotes
+    while j < len && (bytes[j].is_ascii_alphanumeric() || bytes[j] == b'_' || bytes[j] == b'-') {
+      j += 1;
+    }
+    while j < len && matches!(bytes[j], b'"' | b'\'' | b'\\' | b' ') {
+      j += 1;
+    }
+    if j >= len || !matches!(bytes[j], b'=' | b':') {
+      i += key_len;
+      continue;
+    }
+    j += 1;
+    while j < len && matches!(bytes[j], b' ' | b'\\') {
+      j += 1;
+    }
+    let quoted = j < len && matches!(bytes[j], b'"' | b'\'');
+    while j < len && matches!(bytes[j], b'"' | b'\'' | b'\\') {
+      j += 1;
+    }
+    let start = j;
+    j = value_end(bytes, start, quoted);
+    // `Authorization: Bearer <token>`
+    let scheme = &lower[start..j];
+    if !quoted && (scheme == b"bearer" || scheme == b"basic") && j < len && bytes[j] == b' ' {
+      j = value_end(bytes, j + 1, false);
+    }
+    if j > start {
+      out.push_str(&msg[copied..start]);
+      out.push_str(REDACTED);
+      copied = j;
+    }
+    i = j.max(i + key_len);
+  }
+  out.push_str(&msg[copied..]);
+  out
+}
+
+fn value_end(bytes: &[u8], mut j: usize, quoted: bool) -> usize {
+  while j < bytes.len() {
+    let end = match quoted {
+      true => matches!(bytes[j], b'"' | b'\'' | b'\\'),
+      false => matches!(bytes[j], b'&' | b',' | b';' | b')' | b'}' | b']' | b'"' | b'\'' | b'\\') || bytes[j].is_ascii_whitespace(),
+    };
+    if end {
+      break;
+    }
+    j += 1;
+  }
+  j
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn secrets(values: &[&str]) -> Vec<Zeroizing<String>> {
+    values.iter().map(|value| Zeroizing::new(String::from(*value))).collect()
+  }
+
+  #[test]
+  fn query_strings() {
+    assert_eq!(
+      redact("GET /api/v3/order?symbol=BNBBTC&timestamp=1&signature=9f86d081884c7d65 failed"),
+      "GET /api/v3/order?symbol=BNBBTC&timestamp=1&signature=[REDACTED] failed"
+    );
+  }
+
+  #[test]
+  fn headers() {
+    assert_eq!(redact("X-MBX-APIKEY: vmPUZE6mv9SD5VNHk4\nHost: testnet"), "X-MBX-APIKEY: [REDACTED]\nHost: testnet");
+    assert_eq!(redact("authorization: Bearer eyJhbGciOi.eyJzdWIi, retry"), "authorization: [REDACTED], retry");
+  }
+
+  #[test]
+  fn json() {
+    assert_eq!(
+      redact(r#"{"apiSecret": "q7Hx2mWp", "symbol": "BNBBTC", "session":"abc123"}"#),
+      r#"{"apiSecret": "[REDACTED]", "symbol": "BNBBTC", "session":"[REDACTED]"}"#
+    );
+    // Escaped as in the debug output of a request
+    assert_eq!(redact(r#"body: "{\"token\":\"abc123\"}""#), r#"body: "{\"token\":\"[REDACTED]\"}""#);
+  }
+
+  #[test]
+  fn keys_without_values() {
+    assert_eq!(redact("invalid signature, session expired"), "invalid signature, session expired");
+  }
+
+  #[test]
+  fn registered_secrets() {
+    let registered = register(secrets(&["Zk4wQm9xTn", "abc"]));
+    // Too short to redact anywhere
+    assert_eq!(redact("rejected Zk4wQm9xTn for abc"), "rejected [REDACTED] for abc");
+    registered.add(secrets(&["Pq8rSt2uVw"]));
+    assert_eq!(redact("Zk4wQm9xTn Pq8rSt2uVw"), "[REDACTED] [REDACTED]");
+    registered.replace(secrets(&["Pq8rSt2uVw"]));
+    assert_eq!(redact("Zk4wQm9xTn Pq8rSt2uVw"), "Zk4wQm9xTn [REDACTED]");
+    drop(registered);
+    assert_eq!(redact("Zk4wQm9xTn Pq8rSt2uVw"), "Zk4wQm9xTn Pq8rSt2uVw");
+  }
+}
@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use rust_decimal::Decimal;
+use openlimits::model::{OrderBookResponse, Side, Trade};
+use crate::positions::PositionTracker;
+use crate::tracker::OrderTracker;
+
+// Process wide, so one switch stops every client
+static KILL_SWITCH: AtomicBool = AtomicBool::new(false);
+
+pub fn set_kill_switch(engaged: bool) {
+  KILL_SWITCH.store(engaged, Ordering::SeqCst);
+  if engaged {
+    log::warn!("Kill switch engaged, rejecting all orders");
+  } else {
+    log::info!("Kill switch released");
+  }
+}
+
+pub fn kill_switch_engaged() -> bool {
+  KILL_SWITCH.load(Ordering::SeqCst)
+}
+
+/// Which limit an order broke, named like the field of `RiskLimits` in Java.
+#[derive(Debug)]
+pub struct RiskViolation {
+  pub limit: &'static str,
+  pub reason: String,
+}
+
+impl fmt::Display for RiskViolation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: {}", self.limit, self.reason)
+  }
+}
+
+fn violation(limit: &'static str, reason: String) -> Result<(), RiskViolation> {
+  Err(RiskViolation { limit, reason })
+}
+
+/// An order about to be sent. Market orders have no price.
+pub struct OrderIntent<'a> {
+  pub market: &'a str,
+  pub side: Side,
+  pub size: Decimal,
+  pub price: Option<Decimal>,
+}
+
+#[derive(Default)]
+pub struct RiskLimits {
+  pub max_order_notional: Option<Decimal>,
+  // In the base asset, either side of flat
+  pub max_position: Option<Decimal>,
+  pub max_open_orders: Option<usize>,
+  // Percent either side of the reference price
+  pub price_band: Option<Decimal>,
+}
+
+// Orders that passed the limits and aren't tracked yet, by admission
+#[derive(Default)]
+struct InFlight {
+  next_id: u64,
+  // Lowercase market and the signed size
+  orders: HashMap<u64, (String, Decimal)>,
+}
+
+/// An order let through by `RiskChecker::admit`, counted as open until dropped. Dropped once the order is sent
+/// and recorded by the tracker, or failed.
+pub struct Admission {
+  in_flight: Arc<Mutex<InFlight>>,
+  id: u64,
+}
+
+impl Drop for Admission {
+  fn drop(&mut self) {
+    let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+    in_flight.orders.remove(&self.id);
+  }
+}
+
+/// Enforces the limits of a client, with the last trade or mid price of every market the callback thread saw.
+pub struct RiskChecker {
+  limits: RiskLimits,
+  // Lowercase market to the latest price, whichever of trade and order book came last
+  references: Mutex<HashMap<String, Decimal>>,
+  in_flight: Arc<Mutex<InFlight>>,
+}
+
+impl RiskChecker {
+  pub fn new(limits: RiskLimits) -> Self {
+    RiskChecker { limits, references: Mutex::new(HashMap::new()), in_flight: Arc::new(Mutex::new(InFlight::default())) }
+  }
+
+  pub fn last_trades(&self, market: &str, trades: &[Trade]) {
+    if let Some(trade) = trades.iter().max_by_key(|trade| trade.created_at) {
+      self.set_reference(market, trade.price);
+    }
+  }
+
+  pub fn mark(&self, market: &str, orderbook: &OrderBookResponse) {
+    let best_bid = orderbook.bids.iter().map(|level| level.price).max();
+    let best_ask = orderbook.asks.iter().map(|level| level.price).min();
+    if let (Some(bid), Some(ask)) = (best_bid, best_ask) {
+      self.set_reference(market, (bid + ask) / Decimal::new(2, 0));
+    }
+  }
+
+  fn set_reference(&self, market: &str, price: Decimal) {
+    let mut references = self.references.lock().unwrap_or_else(|e| e.into_inner());
+    references.insert(market.to_lowercase(), price);
+  }
+
+  fn reference(&self, market: &str) -> Option<Decimal> {
+    let references = self.references.lock().unwrap_or_else(|e| e.into_inner());
+    references.get(&market.to_lowercase()).copied()
+  }
+
+  /// Checks an order against the positions and open orders of the client, counting the orders admitted before
+  /// that aren't tracked yet. Checking and admitting are one step, so orders sent at once can't all take the last
+  /// open order or the rest of the position.
+  pub fn admit(&self, order: &OrderIntent, positions: Option<&PositionTracker>, tracker: Option<&OrderTracker>) -> Result<Admission, RiskViolation> {
+    let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+    let market = order.market.to_lowercase();
+    let pending = in_flight.orders.values()
+      .filter(|(pending_market, size)| *pending_market == market && same_side(*size, &order.side))
+      .map(|(_, size)| *size)
+      .sum::<Decimal>();
+    let open = tracker.map(|tracker| tracker.unfinished());
+    let resting = open.as_ref().map(|open| {
+      open.iter()
+        .filter(|open| open.market_pair.eq_ignore_ascii_case(order.market))
+        .map(|open| signed(&open.side, open.remaining.unwrap_or(open.size)))
+        .filter(|size| same_side(*size, &order.side))
+        .sum::<Decimal>() + pending
+    });
+    let open_orders = open.map(|open| open.len() + in_flight.orders.len());
+    let position = positions.and_then(|positions| positions.net(order.market));
+    self.check(order, position, resting, open_orders)?;
+    let id = in_flight.next_id;
+    in_flight.next_id += 1;
+    in_flight.orders.insert(id, (market, signed(&order.side, order.size)));
+    Ok(Admission { in_flight: self.in_flight.clone(), id })
+  }
+
+  /// `position` is the net position of the order's market, `resting` the signed size of the open orders of the
+  /// market on the order's side and `open_orders` the number of open orders, when tracked. Limits that would need
+  /// something untracked reject the order rather than let it through unchecked.
+  fn check(&self, order: &OrderIntent, position: Option<Decimal>, resting: Option<Decimal>, open_orders: Option<usize>) -> Result<(), RiskViolation> {
+    let reference = self.reference(order.market);
+    if let Some(max) = self.limits.max_order_notional {
+      let price = match order.price.or(reference) {
+        Some(price) => price,
+        None => return violation("maxOrderNotional", format!("No last trade or mid price of {} to value the order", order.market)),
+      };
+      let notional = price * order.size;
+      if notional > max {
+        return violation("maxOrderNotional", format!("Notional {} exceeds {}", notional, max));
+      }
+    }
+    if let Some(max) = self.limits.max_position {
+      let (net, resting) = match (position, resting) {
+        (Some(net), Some(resting)) => (net, resting),
+        (None, _) => return violation("maxPosition", format!("The position of {} is not tracked", order.market)),
+        (_, None) => return violation("maxPosition", String::from("The open orders are not tracked")),
+      };
+      // As if every open order of the side filled
+      let before = net + resting;
+      let after = before + signed(&order.side, order.size);
+      // Orders that bring the position closer to flat always pass
+      if after.abs() > max && after.abs() > before.abs() {
+        return violation("maxPosition", format!("Position of {} would be {} with its open orders, beyond {}", order.market, after, max));
+      }
+    }
+    if let (Some(max), Some(open)) = (self.limits.max_open_orders, open_orders) {
+      if open >= max {
+        return violation("maxOpenOrders", format!("{} orders are open already", open));
+      }
+    }
+    if let (Some(band), Some(price)) = (self.limits.price_band, order.price) {
+      let reference = match reference {
+        Some(reference) => reference,
+        None => return violation("priceBand", format!("No last trade or mid price of {} to compare with", order.market)),
+      };
+      if reference.is_zero() {
+        return violation("priceBand", format!("The reference price of {} is zero", order.market));
+      }
+      let deviation = ((price - reference) / reference * Decimal::new(100, 0)).abs();
+      if deviation > band {
+        return violation("priceBand", format!("Price {} is {}% away from {}, more than {}%", price, deviation.round_dp(2), reference, band));
+      }
+    }
+    Ok(())
+  }
+}
+
+fn signed(side: &Side, size: Decimal) -> Decimal {
+  match side {
+    Side::Buy => size,
+    Side::Sell => -size,
+  }
+}
+
+fn same_side(size: Decimal, side: &Side) -> bool {
+  match side {
+    Side::Buy => size.is_sign_positive(),
+    Side::Sell => size.is_sign_negative(),
+  }
+}
+
+/// Checked before any limit, whether the client has limits or not.
+pub fn check_kill_switch() -> Result<(), RiskViolation> {
+  if kill_switch_engaged() {
+    return violation("killSwitch", String::from("The kill switch is engaged"));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use openlimits::exchange::traits::info::MarketPair;
+  use openlimits::model::{Order, OrderStatus, OrderType};
+
+  fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+  }
+
+  fn limit(side: Side, size: &str, price: &str) -> OrderIntent<'static> {
+    OrderIntent { market: "BNBBTC", side, size: dec(size), price: Some(dec(price)) }
+  }
+
+  fn market(side: Side, size: &str) -> OrderIntent<'static> {
+    OrderIntent { market: "BNBBTC", side, size: dec(size), price: None }
+  }
+
+  fn checker(limits: RiskLimits) -> RiskChecker {
+    let checker = RiskChecker::new(limits);
+    checker.set_reference("BNBBTC", dec("100"));
+    checker
+  }
+
+  fn rejected_by(result: Result<Admission, RiskViolation>) -> Option<&'static str> {
+    result.err().map(|violation| violation.limit)
+  }
+
+  fn positions(net: &str) -> PositionTracker {
+    let pair = MarketPair {
+      base: String::from("BNB"),
+      quote: String::from("BTC"),
+      symbol: String::from("BNBBTC"),
+      base_increment: dec("0.01"),
+      quote_increment: dec("0.0000001"),
+      min_base_trade_size: None,
+      min_quote_trade_size: None,
+    };
+    let positions = PositionTracker::new(&[String::from("BNBBTC")], &[pair]).unwrap();
+    let net = dec(net);
+    if !net.is_zero() {
+      positions.apply(vec![openlimits::model::Trade {
+        id: String::from("1"),
+        buyer_order_id: None,
+        seller_order_id: None,
+        market_pair: String::from("BNBBTC"),
+        price: dec("100"),
+        qty: net.abs(),
+        fees: None,
+        side: if net.is_sign_positive() { Side::Buy } else { Side::Sell },
+        liquidity: None,
+        created_at: 0,
+      }]);
+    }
+    positions
+  }
+
+  fn open_order(tracker: &OrderTracker, id: &str, side: Side, size: &str) {
+    tracker.record(Order {
+      id: String::from(id),
+      market_pair: String::from("BNBBTC"),
+      client_order_id: None,
+      created_at: Some(0),
+      order_type: OrderType::Limit,
+      side,
+      status: OrderStatus::New,
+      size: dec(size),
+      price: Some(dec("100")),
+      remaining: Some(dec(size)),
+      trades: Vec::new(),
+    });
+  }
+
+  #[test]
+  fn max_order_notional() {
+    let checker = checker(RiskLimits { max_order_notional: Some(dec("1000")), ..Default::default() });
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Buy, "10", "100"), None, None)), None);
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Buy, "10.01", "100"), None, None)), Some("maxOrderNotional"));
+    // Market orders are valued at the reference price
+    assert_eq!(rejected_by(checker.admit(&market(Side::Sell, "10"), None, None)), None);
+    assert_eq!(rejected_by(checker.admit(&market(Side::Sell, "11"), None, None)), Some("maxOrderNotional"));
+    let unpriced = RiskChecker::new(RiskLimits { max_order_notional: Some(dec("1000")), ..Default::default() });
+    assert_eq!(rejected_by(unpriced.admit(&market(Side::Buy, "1"), None, None)), Some("maxOrderNotional"));
+  }
+
+  #[test]
+  fn price_band() {
+    let checker = checker(RiskLimits { price_band: Some(dec("5")), ..Default::default() });
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Buy, "1", "105"), None, None)), None);
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Sell, "1", "94.9"), None, None)), Some("priceBand"));
+    // Market orders have no price to compare
+    assert_eq!(rejected_by(checker.admit(&market(Side::Buy, "1"), None, None)), None);
+    checker.set_reference("BNBBTC", Decimal::default());
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Buy, "1", "100"), None, None)), Some("priceBand"));
+    let unpriced = RiskChecker::new(RiskLimits { price_band: Some(dec("5")), ..Default::default() });
+    assert_eq!(rejected_by(unpriced.admit(&limit(Side::Buy, "1", "100"), None, None)), Some("priceBand"));
+  }
+
+  #[test]
+  fn max_open_orders_counts_admitted_orders() {
+    let checker = checker(RiskLimits { max_open_orders: Some(2), ..Default::default() });
+    let tracker = OrderTracker::default();
+    open_order(&tracker, "1", Side::Buy, "1");
+    let admission = checker.admit(&limit(Side::Buy, "1", "100"), None, Some(&tracker)).unwrap();
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Sell, "1", "100"), None, Some(&tracker))), Some("maxOpenOrders"));
+    drop(admission);
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Sell, "1", "100"), None, Some(&tracker))), None);
+    // Without tracking there is nothing to count
+    open_order(&tracker, "2", Side::Buy, "1");
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Buy, "1", "100"), None, None)), None);
+  }
+
+  #[test]
+  fn max_position_counts_open_orders() {
+    let checker = checker(RiskLimits { max_position: Some(dec("10")), ..Default::default() });
+    let positions = positions("6");
+    let tracker = OrderTracker::default();
+    open_order(&tracker, "1", Side::Buy, "3");
+    // Orders on the other side don't add to the position
+    open_order(&tracker, "2", Side::Sell, "5");
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Buy, "1", "100"), Some(&positions), Some(&tracker))), None);
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Buy, "1.01", "100"), Some(&positions), Some(&tracker))), Some("maxPosition"));
+    let admission = checker.admit(&limit(Side::Buy, "0.5", "100"), Some(&positions), Some(&tracker)).unwrap();
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Buy, "0.51", "100"), Some(&positions), Some(&tracker))), Some("maxPosition"));
+    drop(admission);
+    // 6 long with 5 to sell is 1 long
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Sell, "11", "100"), Some(&positions), Some(&tracker))), None);
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Sell, "11.01", "100"), Some(&positions), Some(&tracker))), Some("maxPosition"));
+  }
+
+  #[test]
+  fn max_position_passes_orders_towards_flat() {
+    let checker = checker(RiskLimits { max_position: Some(dec("10")), ..Default::default() });
+    let positions = positions("-20");
+    let tracker = OrderTracker::default();
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Buy, "5", "100"), Some(&positions), Some(&tracker))), None);
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Sell, "1", "100"), Some(&positions), Some(&tracker))), Some("maxPosition"));
+  }
+
+  #[test]
+  fn max_position_needs_tracking() {
+    let checker = checker(RiskLimits { max_position: Some(dec("10")), ..Default::default() });
+    let tracker = OrderTracker::default();
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Buy, "1", "100"), None, Some(&tracker))), Some("maxPosition"));
+    assert_eq!(rejected_by(checker.admit(&limit(Side::Buy, "1", "100"), Some(&positions("0")), None)), Some("maxPosition"));
+  }
+}
@@ -0,0 +1,79 @@
+use openlimits::exchange::any::InitAnyExchange;
+use zeroize::{Zeroize, Zeroizing};
+
+/// The init parameters of a client. Its credentials are wiped when dropped, copies included. The copies handed to
+/// openlimits to instantiate a client are out of reach.
+#[derive(Clone)]
+pub struct SecretParams {
+  params: InitAnyExchange,
+}
+
+impl SecretParams {
+  pub fn new(params: InitAnyExchange) -> Self {
+    SecretParams { params }
+  }
+
+  pub fn params(&self) -> &InitAnyExchange {
+    &self.params
+  }
+
+  pub fn params_mut(&mut self) -> &mut InitAnyExchange {
+    &mut self.params
+  }
+
+  /// A copy for `OpenLimits::instantiate` and `OpenLimitsWs::instantiate`.
+  pub fn instantiate_params(&self) -> InitAnyExchange {
+    self.params.clone()
+  }
+
+  /// Every credential, for redaction.
+  pub fn secrets(&self) -> Vec<Zeroizing<String>> {
+    credentials(&self.params).into_iter().map(|secret| Zeroizing::new(secret.clone())).collect()
+  }
+
+  pub fn scrub(&mut self) {
+    for secret in credentials_mut(&mut self.params) {
+      secret.zeroize();
+    }
+  }
+}
+
+impl Drop for SecretParams {
+  fn drop(&mut self) {
+    self.scrub();
+  }
+}
+
+/// Moves a credential out to build the openlimits credentials with, which only take plain strings. They end up in a
+/// `SecretParams`.
+pub fn expose(mut secret: Zeroizing<String>) -> String {
+  std::mem::take(&mut *secret)
+}
+
+fn credentials(params: &InitAnyExchange) -> Vec<&String> {
+  match params {
+    InitAnyExchange::Nash(params) => params.credentials.iter()
+      .flat_map(|credentials| vec![&credentials.secret, &credentials.session])
+      .collect(),
+    InitAnyExchange::Binance(params) => params.credentials.iter()
+      .flat_map(|credentials| vec![&credentials.api_key, &credentials.api_secret])
+      .collect(),
+    InitAnyExchange::Coinbase(params) => params.credentials.iter()
+      .flat_map(|credentials| vec![&credentials.api_key, &credentials.api_secret, &credentials.passphrase])
+      .collect(),
+  }
+}
+
+fn credentials_mut(params: &mut InitAnyExchange) -> Vec<&mut String> {
+  match params {
+    InitAnyExchange::Nash(params) => params.credentials.iter_mut()
+      .flat_map(|credentials| vec![&mut credentials.secret, &mut credentials.session])
+      .collect(),
+    InitAnyExchange::Binance(params) => params.credentials.iter_mut()
+      .flat_map(|credentials| vec![&mut credentials.api_key, &mut credentials.api_secret])
+      .collect(),
+    InitAnyExchange::Coinbase(params) => params.credentials.iter_mut()
+      .flat_map(|credentials| vec![&mut credentials.api_key, &mut credentials.api_secret, &mut credentials.passphrase])
+      .collect(),
+  }
+}
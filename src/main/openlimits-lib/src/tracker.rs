@@ -0,0 +1,114 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use openlimits::errors::OpenLimitsError;
+use openlimits::exchange::any::AnyExchange;
+use openlimits::exchange::traits::ExchangeAccount;
+use openlimits::model::{GetOrderRequest, Order, OrderStatus};
+
+// How long a finished order stays tracked, for getTrackedOrders and to tell stale updates from new orders
+const FINISHED_RETENTION: Duration = Duration::from_secs(600);
+
+/// An order whose status moved, delivered to `onOrderStateChange`. `old` is `None` for an order seen first.
+pub struct OrderChange {
+  pub order: Order,
+  pub old: Option<OrderStatus>,
+  pub new: OrderStatus,
+}
+
+/// What a reconciliation changed, and the lookups that failed. The orders whose lookup failed are left as they were
+/// and looked up again by the next reconciliation.
+pub struct Reconciliation {
+  pub changes: Vec<OrderChange>,
+  pub errors: Vec<OpenLimitsError>,
+}
+
+struct Tracked {
+  order: Order,
+  finished_at: Option<Instant>,
+}
+
+/// The client's view of its orders, from the orders it placed and canceled, the account streams and
+/// reconciliation with the exchange. Finished orders are forgotten after a while.
+#[derive(Default)]
+pub struct OrderTracker {
+  orders: Mutex<HashMap<String, Tracked>>,
+}
+
+impl OrderTracker {
+  /// Keeps the latest state of an order. An order that finished is not reopened by an update that was already
+  /// stale when it arrived.
+  pub fn record(&self, order: Order) -> Option<OrderChange> {
+    let mut orders = self.orders.lock().unwrap_or_else(|e| e.into_inner());
+    orders.retain(|_, tracked| !matches!(tracked.finished_at, Some(finished_at) if finished_at.elapsed() > FINISHED_RETENTION));
+    let old = orders.get(&order.id).map(|tracked| tracked.order.status);
+    if matches!(old, Some(old) if is_finished(old) && !is_finished(order.status)) {
+      return None;
+    }
+    let finished_at = match (old, is_finished(order.status)) {
+      (Some(old), true) if is_finished(old) => orders.get(&order.id).and_then(|tracked| tracked.finished_at),
+      (_, true) => Some(Instant::now()),
+      (_, false) => None,
+    };
+    orders.insert(order.id.clone(), Tracked { order: order.clone(), finished_at });
+    if old == Some(order.status) {
+      return None;
+    }
+    Some(OrderChange { new: order.status, order, old })
+  }
+
+  /// Marks an order the exchange confirmed canceling. Orders the tracker never saw are left alone, there's no
+  /// state to keep for them.
+  pub fn cancel(&self, id: &str) -> Option<OrderChange> {
+    let order = {
+      let orders = self.orders.lock().unwrap_or_else(|e| e.into_inner());
+      orders.get(id).map(|tracked| &tracked.order).filter(|order| !is_finished(order.status)).cloned()
+    };
+    order.and_then(|order| self.record(Order { status: OrderStatus::Canceled, ..order }))
+  }
+
+  /// Every tracked order of a market, or of all markets, oldest first.
+  pub fn tracked(&self, market: Option<&str>) -> Vec<Order> {
+    let orders = self.orders.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tracked: Vec<Order> = orders.values()
+      .map(|tracked| &tracked.order)
+      .filter(|order| match market {
+        Some(market) => order.market_pair.eq_ignore_ascii_case(market),
+        None => true,
+      })
+      .cloned()
+      .collect();
+    tracked.sort_by_key(|order| order.created_at);
+    tracked
+  }
+
+  pub fn unfinished(&self) -> Vec<Order> {
+    let orders = self.orders.lock().unwrap_or_else(|e| e.into_inner());
+    orders.values().filter(|tracked| tracked.finished_at.is_none()).map(|tracked| tracked.order.clone()).collect()
+  }
+
+  /// Brings the tracker in line with the exchange, e.g. after updates were missed while disconnected. Open orders
+  /// are taken as they are, and orders that were open before but aren't anymore are looked up for their final
+  /// state. Every change recorded is returned, also when some lookups failed.
+  pub async fn reconcile(&self, client: &AnyExchange) -> Result<Reconciliation, OpenLimitsError> {
+    let open = client.get_all_open_orders().await?;
+    let open_ids: HashSet<String> = open.iter().map(|order| order.id.clone()).collect();
+    let mut changes: Vec<OrderChange> = open.into_iter().filter_map(|order| self.record(order)).collect();
+    let mut errors = Vec::new();
+    for order in self.unfinished() {
+      if open_ids.contains(&order.id) {
+        continue;
+      }
+      let req = GetOrderRequest { id: order.id, market_pair: Some(order.market_pair) };
+      match client.get_order(&req).await {
+        Ok(order) => changes.extend(self.record(order)),
+        Err(err) => errors.push(err),
+      }
+    }
+    Ok(Reconciliation { changes, errors })
+  }
+}
+
+pub fn is_finished(status: OrderStatus) -> bool {
+  matches!(status, OrderStatus::Filled | OrderStatus::Canceled | OrderStatus::Rejected | OrderStatus::Expired)
+}
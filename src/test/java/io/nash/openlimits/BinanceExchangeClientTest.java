@@ -2,7 +2,18 @@ package io.nash.openlimits;
 
 import junit.framework.TestCase;
 
+import java.io.IOException;
+import java.math.BigDecimal;
+import java.nio.file.Files;
+import java.nio.file.Paths;
+import java.util.ArrayList;
 import java.util.Arrays;
+import java.util.LinkedHashMap;
+import java.util.List;
+import java.util.Map;
+import java.util.concurrent.ConcurrentHashMap;
+import java.util.concurrent.TimeUnit;
+import java.util.concurrent.atomic.AtomicLong;
 
 public class BinanceExchangeClientTest extends TestCase {
     static ExchangeClient client;
@@ -21,6 +32,27 @@ public class BinanceExchangeClientTest extends TestCase {
 
         client = new ExchangeClient(new ExchangeClientConfig(config));
     }
+    public void tearDown() throws Exception {
+        // Whatever a failed test left open, so its runtime, websocket and dead man's switch don't outlive it
+        for (ExchangeClient open : openClients) {
+            open.close();
+        }
+        openClients.clear();
+        client.close();
+        super.tearDown();
+    }
+
+    private final List<ExchangeClient> openClients = new ArrayList<>();
+    // Closed by tearDown unless the test closes it with close(client)
+    private ExchangeClient newClient(ExchangeClientConfig config) {
+        ExchangeClient newClient = new ExchangeClient(config);
+        openClients.add(newClient);
+        return newClient;
+    }
+    private void close(ExchangeClient open) {
+        openClients.remove(open);
+        open.close();
+    }
     public void testError() {
         try {
             client.getHistoricRates(new GetHistoryRatesRequest("sadsdqwe", "OneHour"));
@@ -29,9 +61,315 @@ public class BinanceExchangeClientTest extends TestCase {
             System.out.println(e.getMessage());
         }
     }
+    public void testCharArrayCredentials() {
+        char[] secret = System.getenv("BINANCE_API_SECRET").toCharArray();
+        BinanceConfig config = new BinanceConfig(true, new BinanceCredentials(System.getenv("BINANCE_API_KEY"), secret));
+        ExchangeClient charClient = newClient(new ExchangeClientConfig(config));
+        // Cleared once read
+        assertTrue(Arrays.equals(new char[secret.length], secret));
+        // Signed with the secret read from the array
+        assertNotNull(charClient.getAccountBalances(null));
+    }
     public void testOrderBook() {
         System.out.println(client.orderBook("BNBBTC"));
     }
+    public void testRestOnly() {
+        ExchangeClient restClient = newClient(new ExchangeClientConfig(new BinanceConfig(true, null)).restOnly());
+        assertEquals("BNBBTC", restClient.orderBook("BNBBTC").market);
+        try {
+            restClient.subscribeOrderbook("BNBBTC", System.out::println);
+            fail("Expected to get an exception");
+        } catch(SubscribeException e) {
+            assertTrue(e.getMessage().contains("REST only"));
+        }
+    }
+    public void testInjectedFault() {
+        client.injectFault(FaultRule.error("orderBook", "BNBBTC", ServiceUnavailable.class).times(1));
+        try {
+            client.orderBook("BNBBTC");
+            fail("Expected to get an exception");
+        } catch(ServiceUnavailable e) {
+            assertTrue(e.retryable);
+            System.out.println(e);
+        }
+        System.out.println(client.orderBook("BNBBTC"));
+        client.clearFaults();
+    }
+    // Waits until the callback thread delivered count messages, with or without callbacks to deliver them to
+    private static void awaitDelivered(ExchangeClient soakClient, long count) throws InterruptedException {
+        long deadline = System.currentTimeMillis() + TimeUnit.MINUTES.toMillis(10);
+        while (true) {
+            long delivered = 0;
+            for (LatencyHistogram latency : soakClient.getMetrics().latencies) {
+                if (latency.operation.equals("onOrderbook") || latency.operation.equals("onTrades")) {
+                    delivered += latency.count;
+                }
+            }
+            if (delivered >= count) {
+                return;
+            }
+            assertTrue(System.currentTimeMillis() < deadline);
+            Thread.sleep(100);
+        }
+    }
+    // Resident set size of the process, which includes the native memory leaked local references would hold
+    private static long residentBytes() throws IOException {
+        for (String line : Files.readAllLines(Paths.get("/proc/self/status"))) {
+            if (line.startsWith("VmRSS:")) {
+                return Long.parseLong(line.replaceAll("[^0-9]", "")) * 1024;
+            }
+        }
+        throw new IOException("No VmRSS in /proc/self/status");
+    }
+    public void testSoakCallbackMemory() throws InterruptedException, IOException {
+        // Run by the soakTest task, and only where the resident set size can be read
+        if (!Boolean.getBoolean("openlimits.soak") || !Files.exists(Paths.get("/proc/self/status"))) {
+            return;
+        }
+        final long warmup = 200_000;
+        final long messages = 2_000_000;
+        // Lazy and never subscribed, so the callback thread only sees the simulated messages
+        ExchangeClient soakClient = newClient(new ExchangeClientConfig(new BinanceConfig(true, null)).withStreamingMode(StreamingMode.Lazy));
+        soakClient.simulateMessages("BNBBTC", warmup);
+        awaitDelivered(soakClient, warmup);
+        System.gc();
+        long residentBefore = residentBytes();
+        soakClient.simulateMessages("BNBBTC", messages);
+        awaitDelivered(soakClient, warmup + messages);
+        System.gc();
+        long residentAfter = residentBytes();
+        MetricsSnapshot metrics = soakClient.getMetrics();
+        System.out.println("Resident before " + residentBefore + ", after " + residentAfter);
+        // Every converted object would stay referenced, more than 20 local references per order book
+        assertTrue(residentAfter - residentBefore < 64 * 1024 * 1024);
+        assertEquals(0, metrics.queueDepth);
+    }
+    // Both callbacks run on callback threads, so they only collect and the test thread compares
+    private static void assertSameLevels(long updateId, AskBid[] expected, AskBid[] actual) {
+        assertEquals("levels of update " + updateId, expected.length, actual.length);
+        for (int i = 0; i < expected.length; i++) {
+            assertEquals("price of update " + updateId + " level " + i, 0, expected[i].price.compareTo(actual[i].price));
+            assertEquals("qty of update " + updateId + " level " + i, 0, expected[i].qty.compareTo(actual[i].qty));
+        }
+    }
+    public void testDirectOrderbooks() throws InterruptedException {
+        ExchangeClient directClient = newClient(new ExchangeClientConfig(new BinanceConfig(true, null)).withDirectOrderbooks());
+        ExchangeClient plainClient = newClient(new ExchangeClientConfig(new BinanceConfig(true, null)));
+        // The synthetic books have the even update ids below 200, far below those of the live books
+        Map<Long, OrderbookResponse> direct = new ConcurrentHashMap<>();
+        Map<Long, OrderbookResponse> viewCopies = new ConcurrentHashMap<>();
+        Map<Long, OrderbookResponse> plain = new ConcurrentHashMap<>();
+        directClient.subscribeOrderbookView("BNBBTC", book -> {
+            if (book.updateId() < 200) {
+                direct.put(book.updateId(), book.toResponse());
+            }
+        });
+        directClient.subscribeOrderbook("BNBBTC", orderbook -> {
+            if (orderbook.updateId < 200) {
+                viewCopies.put(orderbook.updateId, orderbook);
+            }
+        });
+        plainClient.subscribeOrderbook("BNBBTC", orderbook -> {
+            if (orderbook.updateId < 200) {
+                plain.put(orderbook.updateId, orderbook);
+            }
+        });
+        directClient.simulateMessages("BNBBTC", 200);
+        plainClient.simulateMessages("BNBBTC", 200);
+        for (int attempt = 0; attempt < 600 && (direct.size() < 100 || viewCopies.size() < 100 || plain.size() < 100); attempt++) {
+            Thread.sleep(100);
+        }
+        // No more callbacks while comparing
+        close(directClient);
+        close(plainClient);
+        assertEquals(100, plain.size());
+        assertEquals(plain.keySet(), direct.keySet());
+        assertEquals(plain.keySet(), viewCopies.keySet());
+        for (Map.Entry<Long, OrderbookResponse> expected : plain.entrySet()) {
+            long updateId = expected.getKey();
+            assertEquals("BNBBTC", viewCopies.get(updateId).market);
+            for (OrderbookResponse actual : Arrays.asList(direct.get(updateId), viewCopies.get(updateId))) {
+                assertSameLevels(updateId, expected.getValue().asks, actual.asks);
+                assertSameLevels(updateId, expected.getValue().bids, actual.bids);
+            }
+        }
+    }
+    public void testUpdateCredentials() throws InterruptedException {
+        BinanceConfig config = new BinanceConfig(true, new BinanceCredentials(System.getenv("BINANCE_API_KEY"), System.getenv("BINANCE_API_SECRET")));
+        ExchangeClient rotatingClient = new ExchangeClient(new ExchangeClientConfig(config));
+        try {
+            AtomicLong updates = new AtomicLong();
+            rotatingClient.subscribeOrderbook("BNBBTC", orderbook -> updates.incrementAndGet());
+            rotatingClient.updateCredentials(new BinanceCredentials(System.getenv("BINANCE_API_KEY"), System.getenv("BINANCE_API_SECRET")));
+            // Still subscribed on the new websocket
+            long before = updates.get();
+            for (int i = 0; i < 60 && updates.get() == before; i++) {
+                Thread.sleep(1000);
+            }
+            assertTrue(updates.get() > before);
+            System.out.println(rotatingClient.getAccountBalances(null));
+            try {
+                rotatingClient.updateCredentials(new NashCredentials("secret", "session"));
+                fail("Expected to get an exception");
+            } catch(InvalidArgument e) {
+                System.out.println(e.getMessage());
+            }
+        } finally {
+            rotatingClient.close();
+        }
+    }
+    public void testFromConfigFile() throws IOException {
+        java.nio.file.Path config = Files.createTempFile("openlimits", ".toml");
+        Files.write(config, ("exchange = \"binance\"\nsandbox = true\n[options]\nstreaming_mode = \"Disabled\"\n").getBytes());
+        ExchangeClient fileClient = ExchangeClient.fromConfigFile(config.toString());
+        openClients.add(fileClient);
+        assertNotNull(fileClient.getPriceTicker("BNBBTC").price);
+        try {
+            fileClient.subscribeTrades("BNBBTC", trades -> {});
+            fail("Expected streaming to be disabled by the file");
+        } catch(SubscribeException e) {
+            assertTrue(e.getMessage().contains("REST only"));
+        }
+
+        Files.write(config, ("exchange = \"binance\"\nsandbox = \"maybe\"\n[options]\naccount_poll_millis = 0\n").getBytes());
+        try {
+            ExchangeClient.fromConfigFile(config.toString());
+            fail("Expected to get an exception");
+        } catch(InvalidArgument e) {
+            // Every problem at once
+            assertTrue(e.getMessage().contains("sandbox must be true or false"));
+            assertTrue(e.getMessage().contains("options.account_poll_millis must be from 1"));
+        }
+    }
+    public void testFixedPoint() {
+        ExchangeClient fixedClient = newClient(new ExchangeClientConfig(new BinanceConfig(true, null)).withFixedPoint().restOnly());
+        OrderbookResponse orderbook = fixedClient.orderBook("BNBBTC");
+        assertTrue(orderbook.bids.length > 0);
+        for (AskBid bid : orderbook.bids) {
+            assertNull(bid.price);
+            assertTrue(bid.priceFixed > 0);
+        }
+    }
+    // The arithmetic is unit tested natively, this checks the client seeds the position from every page of the history
+    public void testPositions() {
+        BinanceConfig config = new BinanceConfig(true, new BinanceCredentials(System.getenv("BINANCE_API_KEY"), System.getenv("BINANCE_API_SECRET")));
+        ExchangeClient positionClient = newClient(new ExchangeClientConfig(config).withPositionTracking("BNBBTC"));
+        Map<String, Trade> history = new LinkedHashMap<>();
+        long start = 1;
+        while (true) {
+            Trade[] page = positionClient.getTradeHistory(new TradeHistoryRequest("BNBBTC", null, new Paginator(start, 0, 500, null, null)));
+            long latest = start;
+            for (Trade fill : page) {
+                history.putIfAbsent(fill.id, fill);
+                latest = Math.max(latest, fill.createdAt);
+            }
+            if (page.length < 500) {
+                break;
+            }
+            assertTrue(latest > start);
+            start = latest;
+        }
+        BigDecimal net = BigDecimal.ZERO;
+        for (Trade fill : history.values()) {
+            net = fill.side == Side.Buy ? net.add(fill.qty) : net.subtract(fill.qty);
+        }
+        Position[] positions = positionClient.getPositions();
+        if (history.isEmpty()) {
+            assertEquals(0, positions.length);
+            return;
+        }
+        assertEquals(1, positions.length);
+        assertEquals("BNBBTC", positions[0].market);
+        assertEquals(0, net.compareTo(positions[0].net));
+        assertNull(positions[0].unrealisedPnl);
+    }
+    // For orders a test placed on the testnet, whether or not the test got to cancel them
+    private static void cancelQuietly(ExchangeClient orderClient, Order order) {
+        try {
+            orderClient.cancelOrder(new CancelOrderRequest(order.id, order.market));
+        } catch(OpenLimitsException e) {
+            // Canceled already
+        }
+    }
+    private static boolean isOpen(ExchangeClient orderClient, Order order) {
+        for (Order open : orderClient.getAllOpenOrders()) {
+            if (open.id.equals(order.id)) {
+                return true;
+            }
+        }
+        return false;
+    }
+    public void testDeadMansSwitch() throws InterruptedException {
+        BinanceConfig config = new BinanceConfig(true, new BinanceCredentials(System.getenv("BINANCE_API_KEY"), System.getenv("BINANCE_API_SECRET")));
+        ExchangeClient switchClient = newClient(new ExchangeClientConfig(config).withDeadMansSwitch(500, "BNBBTC"));
+        Order order = switchClient.limitBuy(LimitRequest.goodTillCancelled("0.001", "1", "BNBBTC"));
+        try {
+            // No heartbeats, so the switch cancels the order, after retries with backoff if it has to
+            long deadline = System.currentTimeMillis() + 20000;
+            while (isOpen(switchClient, order)) {
+                assertTrue("Order still open", System.currentTimeMillis() < deadline);
+                Thread.sleep(250);
+            }
+        } finally {
+            cancelQuietly(switchClient, order);
+        }
+    }
+    public void testDeadMansSwitchOnSocketError() throws IOException, InterruptedException {
+        String directory = Files.createTempDirectory("audit").toString();
+        BinanceConfig config = new BinanceConfig(true, new BinanceCredentials(System.getenv("BINANCE_API_KEY"), System.getenv("BINANCE_API_SECRET")));
+        // Far from timing out, only the lost websocket trips the switch
+        ExchangeClient switchClient = newClient(new ExchangeClientConfig(config)
+                .withDeadMansSwitch(600000, "BNBBTC")
+                .withAuditJournal(directory, 1 << 20));
+        switchClient.simulateSocketError();
+        AuditRecord[] records = new AuditRecord[0];
+        for (int attempt = 0; attempt < 100 && records.length < 2; attempt++) {
+            Thread.sleep(100);
+            records = AuditJournal.read(directory);
+        }
+        close(switchClient);
+        assertEquals(2, records.length);
+        assertEquals("request", records[0].kind);
+        assertEquals("cancelAllOrders", records[0].operation);
+        assertEquals("BNBBTC", records[0].market);
+        assertEquals("dead man's switch: simulated socket error", records[0].detail);
+        assertTrue(records[1].kind.equals("result") || records[1].kind.equals("error"));
+    }
+    public void testAuditJournal() throws IOException {
+        String directory = Files.createTempDirectory("audit").toString();
+        BinanceConfig config = new BinanceConfig(true, new BinanceCredentials(System.getenv("BINANCE_API_KEY"), System.getenv("BINANCE_API_SECRET")));
+        ExchangeClient journalClient = newClient(new ExchangeClientConfig(config).withAuditJournal(directory, 1 << 20));
+        Order order = journalClient.limitBuy(LimitRequest.goodTillCancelled("0.001", "1", "BNBBTC"));
+        try {
+            // Every record is written as it happens
+            AuditRecord[] placed = AuditJournal.read(directory);
+            assertEquals(2, placed.length);
+            assertEquals("request", placed[0].kind);
+            assertEquals("limitBuy", placed[0].operation);
+            assertEquals("result", placed[1].kind);
+            assertEquals(order.id, placed[1].orderId);
+            assertTrue(placed[1].latencyMicros >= 0);
+        } finally {
+            journalClient.cancelOrder(new CancelOrderRequest(order.id, "BNBBTC"));
+        }
+        AuditRecord[] records = AuditJournal.read(directory);
+        assertEquals(4, records.length);
+        assertEquals("cancelOrder", records[2].operation);
+        assertEquals(order.id, records[2].orderId);
+        assertEquals("result", records[3].kind);
+    }
+    public void testKillSwitch() {
+        ExchangeClient.setKillSwitch(true);
+        try {
+            client.limitBuy(LimitRequest.goodTillCancelled("0.001", "1", "BNBBTC"));
+            fail("Expected to get an exception");
+        } catch(RiskRejected e) {
+            assertEquals("killSwitch", e.limit);
+            assertEquals("limitBuy", e.operation);
+        } finally {
+            ExchangeClient.setKillSwitch(false);
+        }
+    }
     public void testLimitBuy() {
         System.out.println(client.limitBuy(LimitRequest.goodTillCancelled(
                 "0.001",
@@ -2,17 +2,23 @@ package io.nash.openlimits;
 
 import junit.framework.TestCase;
 
+import java.util.ArrayList;
 import java.util.Arrays;
+import java.util.Collections;
+import java.util.List;
+import java.util.concurrent.CountDownLatch;
+import java.util.concurrent.TimeUnit;
 
 public class NashExchangeClientTest extends TestCase {
     static ExchangeClient client;
+    static NashConfig nashConfig;
 
     public void setUp() throws Exception {
         super.setUp();
         String apiKey = System.getenv("NASH_API_KEY");
         String secret = System.getenv("NASH_API_SECRET");
 
-        NashConfig nashConfig = new NashConfig(
+        nashConfig = new NashConfig(
                 new NashCredentials(
                         secret,
                         apiKey
@@ -25,6 +31,11 @@ public class NashExchangeClientTest extends TestCase {
         client = new ExchangeClient(new ExchangeClientConfig(nashConfig));
     }
 
+    public void tearDown() throws Exception {
+        client.close();
+        super.tearDown();
+    }
+
     public void testCanCatchInvalidConfig() {
         try {
             NashConfig nashConfig = new NashConfig(
@@ -43,10 +54,38 @@ public class NashExchangeClientTest extends TestCase {
         }
     }
 
+    public void testCanCatchInvalidBackpressureConfig() {
+        try {
+            NashConfig nashConfig = new NashConfig(null, 0, "sandbox", 10000);
+            new ExchangeClient(new ExchangeClientConfig(nashConfig).withBackpressure(BackpressurePolicy.DropOldest, 0, true));
+            fail("Expected to get an exception");
+        } catch(InvalidArgument e) {
+            System.out.println(e.getMessage());
+        }
+    }
+
     public void testOrderBook() {
         System.out.println(client.orderBook("btc_usdc"));
     }
 
+    public void testRestAfterDisconnect() throws InterruptedException {
+        CountDownLatch disconnected = new CountDownLatch(1);
+        client.subscribeDisconnect(disconnected::countDown);
+        client.disconnect();
+        assertTrue(disconnected.await(10, TimeUnit.SECONDS));
+        System.out.println(client.orderBook("btc_usdc"));
+        client.reconnect();
+        client.subscribeOrderbook("btc_usdc", System.out::println);
+    }
+
+    public void testMetrics() {
+        client.orderBook("btc_usdc");
+        MetricsSnapshot metrics = client.getMetrics();
+        System.out.println(metrics);
+        assertNotNull(metrics.latency("orderBook"));
+        assertEquals(1, metrics.latency("orderBook").count);
+    }
+
     public void testGetHistoryRates() {
         System.out.println(Arrays.toString(client.getHistoricRates( new GetHistoryRatesRequest(
                 "btc_usdc",
@@ -115,6 +154,65 @@ public class NashExchangeClientTest extends TestCase {
         client.cancelOrder(cancelOrderRequest);
     }
 
+    private static void cancelQuietly(ExchangeClient orderClient, Order order) {
+        try {
+            orderClient.cancelOrder(new CancelOrderRequest(order.id, order.market));
+        } catch(OpenLimitsException e) {
+            // Canceled already
+        }
+    }
+    public void testOrderUpdates() throws InterruptedException {
+        CountDownLatch canceled = new CountDownLatch(1);
+        client.subscribeOrderUpdates("eth_btc", order -> {
+            System.out.println(order);
+            if (order.status == OrderStatus.Canceled) {
+                canceled.countDown();
+            }
+        });
+        Order order = client.limitSell(LimitRequest.goodTillCancelled(
+                "1.0",
+                "0.10000",
+                "eth_btc"
+        ));
+        try {
+            client.cancelOrder(new CancelOrderRequest(order.id, "eth_btc"));
+            assertTrue(canceled.await(1, TimeUnit.MINUTES));
+        } finally {
+            cancelQuietly(client, order);
+        }
+    }
+
+    public void testOrderTracking() throws InterruptedException {
+        ExchangeClient trackingClient = new ExchangeClient(new ExchangeClientConfig(nashConfig).withOrderTracking());
+        try {
+            // Written by the calling thread and the callback thread
+            List<OrderStateChange> changes = Collections.synchronizedList(new ArrayList<>());
+            CountDownLatch canceled = new CountDownLatch(1);
+            trackingClient.subscribeOrderStateChanges(change -> {
+                changes.add(change);
+                if (change.newStatus == OrderStatus.Canceled) {
+                    canceled.countDown();
+                }
+            });
+            Order order = trackingClient.limitSell(LimitRequest.goodTillCancelled(
+                    "1.0",
+                    "0.10000",
+                    "eth_btc"
+            ));
+            try {
+                trackingClient.cancelOrder(new CancelOrderRequest(order.id, "eth_btc"));
+                assertTrue(canceled.await(1, TimeUnit.MINUTES));
+                assertEquals(OrderStatus.Canceled, changes.get(changes.size() - 1).newStatus);
+                Order[] tracked = trackingClient.getTrackedOrders("eth_btc");
+                assertEquals(order.id, tracked[tracked.length - 1].id);
+            } finally {
+                cancelQuietly(trackingClient, order);
+            }
+        } finally {
+            trackingClient.close();
+        }
+    }
+
     public void testCancelAllOrders() {
         client.limitSell(LimitRequest.goodTillCancelled(
                 "1.0",